@@ -149,6 +149,12 @@
 //!   assert!(finder.find(&ast, None));
 //! });
 //! ```
+//!
+//! As this find-then-mutate flow is pretty common, the mutator wrapper of an implementor combining
+//! both macros comes with an `ensure` method doing exactly that, which also reports which
+//! implementors were applied and which ones were already satisfied. Single implementors loaded
+//! into a [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html)
+//! have an analogous `ensure` method.
 //! # Defining new implementors
 //!
 //! If the set of predefined implementors isn't enough, defining a new implementor is perfectly
@@ -39,12 +39,17 @@
 //!     });
 //! ```
 
+mod location;
 #[cfg(test)]
 mod tests;
 
-use std::fmt::Debug;
+use std::{any::TypeId, fmt::Debug};
 use syn::{visit::Visit, File};
 
+#[cfg(feature = "span-locations")]
+pub use location::LineColumnSpan;
+pub use location::{Location, LocationSegment};
+
 /// A placeholder finder which does not perform any specific search.
 /// This is used as the default for a [`Finder`](https://docs.rs/rust_writer/latest/rust_writer/ast/finder/struct.Finder.html).
 #[derive(Debug)]
@@ -101,6 +106,9 @@ pub struct Finder<'a, T: Debug, const N: usize> {
 	pub found: [bool; N],
 	/// Placeholder to load an implementor.
 	pub finder: &'a T,
+	// The nodes that made the search succeed, identified by their type and address in the AST.
+	// They're recorded by the implementors so that `locate` can tell where they are.
+	matches: Vec<(TypeId, usize)>,
 }
 
 /// This trait is used to create a new `Finder` variable.
@@ -113,19 +121,20 @@ pub trait ToFind<'a, T: Debug, const N: usize> {
 
 impl Default for Finder<'_, EmptyFinder, 1> {
 	fn default() -> Self {
-		Self { found: [false], finder: &EmptyFinder }
+		Self::new(&EmptyFinder)
 	}
 }
 
 impl<'a, T, const N: usize> Finder<'a, T, N>
 where
 	T: Debug,
-	Finder<'a, T, N>: Visit<'a>,
 {
-	/// Apply all the searches defined by the implementor.
-	pub fn find(&mut self, ast: &'a File) -> bool {
-		self.visit_file(ast);
-		self.found.iter().all(|&x| x)
+	/// Create a new `Finder` loading the given implementor, with none of its searches succeeded
+	/// yet. This is the building block of the
+	/// [`ToFind`](https://docs.rs/rust_writer/latest/rust_writer/ast/finder/trait.ToFind.html)
+	/// implementations.
+	pub fn new(finder: &'a T) -> Self {
+		Self { found: [false; N], finder, matches: Vec::new() }
 	}
 }
 
@@ -133,8 +142,45 @@ impl<T, const N: usize> Finder<'_, T, N>
 where
 	T: Debug,
 {
+	/// Apply all the searches defined by the implementor.
+	pub fn find<'ast>(&mut self, ast: &'ast File) -> bool
+	where
+		Self: Visit<'ast>,
+	{
+		// The matches recorded by a previous search may point to nodes of another AST.
+		self.matches.clear();
+		self.visit_file(ast);
+		self.found.iter().all(|&x| x)
+	}
+
 	/// Reset the `found` array to all `false` values.
 	pub fn reset(&mut self) {
 		self.found = [false; N];
+		self.matches.clear();
+	}
+
+	/// Records the AST node that made the search succeed, so that it can be located later.
+	pub(crate) fn record_match<M: 'static>(&mut self, node: &M) {
+		self.matches.push((TypeId::of::<M>(), node as *const M as usize));
+	}
+}
+
+impl<'a, T> Finder<'a, T, 1>
+where
+	T: Debug,
+	for<'ast> Finder<'a, T, 1>: Visit<'ast>,
+{
+	/// Apply the search defined by the implementor, returning the location of every matched node
+	/// instead of just a `bool`. Each [`Location`] contains the chain of nodes enclosing the match
+	/// (modules, `impl` blocks, traits, structs, enums, functions, and the statements and blocks
+	/// nesting it inside a function body) and the index of the matched node within its parent.
+	/// With the `span-locations` feature, it also contains the line/column span of the node if it
+	/// comes from parsed source code.
+	/// The locations are recorded while the implementor visits the AST, so they point to the very
+	/// nodes that made the search succeed.
+	///
+	/// The `found` array is updated as [`find`](#method.find) would do.
+	pub fn locate(&mut self, ast: &File) -> Vec<Location> {
+		location::locate(self, ast)
 	}
 }
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::ast::finder::Finder;
+#[cfg(feature = "span-locations")]
+use proc_macro2::LineColumn;
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::{any::TypeId, fmt::Debug, mem};
+use syn::{
+	spanned::Spanned, visit::Visit, Block, File, ImplItemFn, ItemEnum, ItemFn, ItemImpl, ItemMod,
+	ItemStruct, ItemTrait, Macro, Stmt, TraitItemFn,
+};
+
+/// The line/column span of a matched node in the parsed source code. Lines are 1-indexed while
+/// columns are 0-indexed.
+#[cfg(feature = "span-locations")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumnSpan {
+	pub start: LineColumn,
+	pub end: LineColumn,
+}
+
+#[cfg(feature = "span-locations")]
+impl LineColumnSpan {
+	// Nodes that don't come from parsed source code (eg, nodes created with `parse_quote!`) have
+	// an empty span, which isn't a meaningful location.
+	fn from_span(span: Span) -> Option<Self> {
+		let (start, end) = (span.start(), span.end());
+		(start != end).then_some(Self { start, end })
+	}
+}
+
+/// A node enclosing a matched node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationSegment {
+	/// The kind of the enclosing node: `mod`, `impl`, `trait`, `struct`, `enum` or `fn` for items,
+	/// `stmt` for a statement of a function body or of a block, and `block` for a block nested in
+	/// a statement, eg the body of an `if` or of a closure.
+	pub kind: &'static str,
+	/// The enclosing item's name. For `impl` blocks, this is the implemented type, preceded by
+	/// the implemented trait if any, eg `SomeTrait for SomeType`. It's empty for statements and
+	/// blocks.
+	pub name: String,
+	/// The index of the enclosing node within its parent. For blocks, this is their position among
+	/// the blocks nested in the same statement, eg 1 for the `else` block of an `if`.
+	pub index: usize,
+}
+
+/// The location of a node matched by a [`Finder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+	/// The chain of items enclosing the matched node, from the outermost to the innermost. It's
+	/// empty if the matched node is a top level item of the file.
+	pub path: Vec<LocationSegment>,
+	/// The index of the matched node within its parent.
+	pub index: usize,
+	/// The line/column span of the matched node, if it comes from parsed source code.
+	#[cfg(feature = "span-locations")]
+	pub span: Option<LineColumnSpan>,
+}
+
+pub(super) fn locate<'a, T>(finder: &mut Finder<'a, T, 1>, ast: &File) -> Vec<Location>
+where
+	T: Debug,
+	for<'ast> Finder<'a, T, 1>: Visit<'ast>,
+{
+	finder.reset();
+	finder.find(ast);
+	// The recorded nodes are only meaningful while `ast` is borrowed, so they aren't kept once
+	// they're located.
+	let matches = mem::take(&mut finder.matches);
+	let mut locator =
+		Locator { matches: &matches, path: Vec::new(), index: 0, blocks: 0, locations: Vec::new() };
+	locator.visit_file(ast);
+	locator.locations
+}
+
+// The locator walks the AST looking for the nodes recorded by the finder while it visited the
+// same AST, keeping track of the nodes enclosing the current node and of its index within its
+// parent.
+struct Locator<'m> {
+	matches: &'m [(TypeId, usize)],
+	path: Vec<LocationSegment>,
+	index: usize,
+	// The number of blocks visited so far in the current statement.
+	blocks: usize,
+	locations: Vec<Location>,
+}
+
+impl Locator<'_> {
+	fn is_match<N: 'static>(&self, node: &N) -> bool {
+		self.matches.contains(&(TypeId::of::<N>(), node as *const N as usize))
+	}
+
+	#[cfg_attr(not(feature = "span-locations"), allow(unused_variables))]
+	fn push_location(&mut self, span: Span) {
+		self.locations.push(Location {
+			path: self.path.clone(),
+			index: self.index,
+			#[cfg(feature = "span-locations")]
+			span: LineColumnSpan::from_span(span),
+		});
+	}
+
+	fn segment(&self, kind: &'static str, name: String) -> LocationSegment {
+		LocationSegment { kind, name, index: self.index }
+	}
+
+	// Visits the children of a node, keeping track of their index. If a segment is given, it's
+	// added to the path while the children are visited.
+	fn visit_children<'ast, N: Spanned + 'static>(
+		&mut self,
+		segment: Option<LocationSegment>,
+		children: impl IntoIterator<Item = &'ast N>,
+		visit: impl Fn(&mut Self, &'ast N),
+	) {
+		let has_segment = segment.is_some();
+		self.path.extend(segment);
+		let parent_index = self.index;
+		for (index, child) in children.into_iter().enumerate() {
+			self.index = index;
+			if self.is_match(child) {
+				self.push_location(child.span());
+			}
+			visit(self, child);
+		}
+		self.index = parent_index;
+		if has_segment {
+			self.path.pop();
+		}
+	}
+}
+
+impl<'ast> Visit<'ast> for Locator<'_> {
+	fn visit_file(&mut self, file: &'ast File) {
+		self.visit_children(None, &file.items, Self::visit_item);
+	}
+
+	fn visit_item_mod(&mut self, item_mod: &'ast ItemMod) {
+		if let Some((_, ref items)) = item_mod.content {
+			let segment = self.segment("mod", item_mod.ident.to_string());
+			self.visit_children(Some(segment), items, Self::visit_item);
+		}
+	}
+
+	fn visit_item_impl(&mut self, item_impl: &'ast ItemImpl) {
+		let name = match item_impl.trait_ {
+			Some((bang, ref path, _)) => format!(
+				"{}{} for {}",
+				if bang.is_some() { "!" } else { "" },
+				path.to_token_stream(),
+				item_impl.self_ty.to_token_stream()
+			),
+			None => item_impl.self_ty.to_token_stream().to_string(),
+		};
+		let segment = self.segment("impl", name);
+		self.visit_children(Some(segment), &item_impl.items, Self::visit_impl_item);
+	}
+
+	fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
+		let segment = self.segment("trait", item_trait.ident.to_string());
+		self.visit_children(Some(segment), &item_trait.items, Self::visit_trait_item);
+	}
+
+	fn visit_item_struct(&mut self, item_struct: &'ast ItemStruct) {
+		let segment = self.segment("struct", item_struct.ident.to_string());
+		self.visit_children(Some(segment), &item_struct.fields, Self::visit_field);
+	}
+
+	fn visit_item_enum(&mut self, item_enum: &'ast ItemEnum) {
+		let segment = self.segment("enum", item_enum.ident.to_string());
+		self.visit_children(Some(segment), &item_enum.variants, Self::visit_variant);
+	}
+
+	fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+		let segment = self.segment("fn", item_fn.sig.ident.to_string());
+		self.visit_children(Some(segment), &item_fn.block.stmts, Self::visit_stmt);
+	}
+
+	fn visit_impl_item_fn(&mut self, impl_item_fn: &'ast ImplItemFn) {
+		let segment = self.segment("fn", impl_item_fn.sig.ident.to_string());
+		self.visit_children(Some(segment), &impl_item_fn.block.stmts, Self::visit_stmt);
+	}
+
+	fn visit_trait_item_fn(&mut self, trait_item_fn: &'ast TraitItemFn) {
+		if let Some(ref block) = trait_item_fn.default {
+			let segment = self.segment("fn", trait_item_fn.sig.ident.to_string());
+			self.visit_children(Some(segment), &block.stmts, Self::visit_stmt);
+		}
+	}
+
+	fn visit_block(&mut self, block: &'ast Block) {
+		let segment = LocationSegment { kind: "block", name: String::new(), index: self.blocks };
+		self.blocks += 1;
+		self.visit_children(Some(segment), &block.stmts, Self::visit_stmt);
+	}
+
+	// Items and macro invocations are children of the block containing them, as they're for a
+	// file. Any other statement is added to the path while its nested blocks are visited.
+	fn visit_stmt(&mut self, stmt: &'ast Stmt) {
+		match stmt {
+			Stmt::Item(item) => {
+				if self.is_match(item) {
+					self.push_location(item.span());
+				}
+				self.visit_item(item);
+			},
+			Stmt::Macro(stmt_macro) => self.visit_macro(&stmt_macro.mac),
+			_ => {
+				let segment = self.segment("stmt", String::new());
+				self.path.push(segment);
+				let blocks = mem::replace(&mut self.blocks, 0);
+				syn::visit::visit_stmt(self, stmt);
+				self.blocks = blocks;
+				self.path.pop();
+			},
+		}
+	}
+
+	// Macro invocations are located at the item or statement containing them, but their span is
+	// the invocation's one.
+	fn visit_macro(&mut self, macro_: &'ast Macro) {
+		if self.is_match(macro_) {
+			self.push_location(macro_.span());
+		}
+	}
+}
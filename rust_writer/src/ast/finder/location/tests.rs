@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::ast::{
+	finder::ToFind,
+	implementors::{
+		FieldToStruct, ItemToFile, ItemToImpl, ItemToMod, StmtToFn, TokenStreamToMacro,
+	},
+};
+use syn::{parse_quote, Item};
+use test_builder::TestBuilder;
+
+#[cfg(feature = "span-locations")]
+fn line_column(line: usize, column: usize) -> LineColumn {
+	LineColumn { line, column }
+}
+
+#[test]
+fn locate_item_inside_impl_block() {
+	TestBuilder::default().with_impl_block_ast().execute(|builder| {
+		let item_to_impl: ItemToImpl = (
+			Some("SomeTrait"),
+			"SomeImplementor",
+			parse_quote! { fn some_func(&self) -> bool { true } },
+		)
+			.into();
+
+		let ast = builder.get_ref_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert_eq!(
+			finder.locate(ast),
+			vec![Location {
+				path: vec![LocationSegment {
+					kind: "impl",
+					name: "SomeTrait for SomeImplementor".to_owned(),
+					index: 0
+				}],
+				index: 1,
+				#[cfg(feature = "span-locations")]
+				span: Some(LineColumnSpan { start: line_column(6, 4), end: line_column(8, 5) })
+			}]
+		);
+		assert!(finder.found[0]);
+	});
+}
+
+#[test]
+fn locate_item_inside_mod() {
+	TestBuilder::default().with_mod_ast().execute(|builder| {
+		let item_to_mod: ItemToMod =
+			("SomeMod", parse_quote! { fn some_super_func(&self) -> bool { true } }).into();
+
+		let ast = builder.get_ref_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		let locations = finder.locate(ast);
+		assert_eq!(locations.len(), 1);
+		assert_eq!(
+			locations[0].path,
+			vec![LocationSegment { kind: "mod", name: "SomeMod".to_owned(), index: 0 }]
+		);
+		assert_eq!(locations[0].index, 1);
+	});
+}
+
+#[test]
+fn locate_field_inside_struct() {
+	TestBuilder::default().with_struct_ast().execute(|builder| {
+		let field_to_struct: FieldToStruct =
+			("MyStruct", parse_quote! { pub(crate) path: PathBuf }).into();
+
+		let ast = builder.get_ref_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert_eq!(
+			finder.locate(ast),
+			vec![Location {
+				path: vec![LocationSegment {
+					kind: "struct",
+					name: "MyStruct".to_owned(),
+					index: 1
+				}],
+				index: 2,
+				#[cfg(feature = "span-locations")]
+				span: Some(LineColumnSpan { start: line_column(9, 4), end: line_column(11, 28) })
+			}]
+		);
+	});
+}
+
+#[test]
+fn locate_whole_item_isnt_mistaken_for_its_child() {
+	TestBuilder::default().with_file_ast().execute(|builder| {
+		let item: Item = parse_quote! { impl A for u8 { fn some_func(&self) {} } };
+		let item_to_file: ItemToFile = item.into();
+
+		let ast = builder.get_ref_ast_file("file.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert_eq!(
+			finder.locate(ast),
+			vec![Location {
+				path: Vec::new(),
+				index: 5,
+				#[cfg(feature = "span-locations")]
+				span: Some(LineColumnSpan { start: line_column(28, 0), end: line_column(30, 1) })
+			}]
+		);
+	});
+}
+
+#[test]
+fn locate_token_stream_returns_enclosing_item() {
+	TestBuilder::default().with_file_ast().execute(|builder| {
+		let token_stream_to_macro: TokenStreamToMacro =
+			(parse_quote! { some_macro }, None, parse_quote! { uses some }).into();
+
+		let ast = builder.get_ref_ast_file("file.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&token_stream_to_macro);
+		let locations = finder.locate(ast);
+		assert_eq!(locations.len(), 1);
+		assert!(locations[0].path.is_empty());
+		assert_eq!(locations[0].index, 3);
+	});
+}
+
+#[test]
+fn locate_stmt_inside_fn_body() {
+	let ast: File = parse_quote! {
+		impl App {
+			fn new() -> Self {
+				Self
+			}
+
+			fn run(&self) {
+				self.init();
+				self.run();
+			}
+		}
+	};
+	let stmt_to_fn: StmtToFn = ("run", parse_quote! { self.run(); }).into();
+
+	let mut finder = Finder::default().to_find(&stmt_to_fn);
+	assert_eq!(
+		finder.locate(&ast),
+		vec![Location {
+			path: vec![
+				LocationSegment { kind: "impl", name: "App".to_owned(), index: 0 },
+				LocationSegment { kind: "fn", name: "run".to_owned(), index: 1 }
+			],
+			index: 1,
+			#[cfg(feature = "span-locations")]
+			span: None
+		}]
+	);
+}
+
+#[test]
+fn locate_item_inside_fn_body() {
+	let ast: File = parse_quote! {
+		fn main() {
+			let app = App::new();
+
+			impl App {
+				fn new() -> Self {
+					Self
+				}
+			}
+
+			app.run();
+		}
+	};
+	let item_to_impl: ItemToImpl = (None, "App", parse_quote! { fn new() -> Self { Self } }).into();
+
+	let mut finder = Finder::default().to_find(&item_to_impl);
+	let locations = finder.locate(&ast);
+	assert_eq!(locations.len(), 1);
+	assert_eq!(
+		locations[0].path,
+		vec![
+			LocationSegment { kind: "fn", name: "main".to_owned(), index: 0 },
+			LocationSegment { kind: "impl", name: "App".to_owned(), index: 1 }
+		]
+	);
+	assert_eq!(locations[0].index, 0);
+}
+
+#[test]
+fn locate_item_nested_in_blocks() {
+	let ast: File = parse_quote! {
+		fn main() {
+			let app = App::new();
+			if cfg!(debug_assertions) {
+				app.debug();
+			} else {
+				app.init();
+				let plugin = || {
+					struct Plugin {
+						name: String,
+					}
+				};
+			}
+		}
+	};
+	let field_to_struct: FieldToStruct = ("Plugin", parse_quote! { name: String }).into();
+
+	let mut finder = Finder::default().to_find(&field_to_struct);
+	let locations = finder.locate(&ast);
+	assert_eq!(locations.len(), 1);
+	assert_eq!(
+		locations[0].path,
+		vec![
+			LocationSegment { kind: "fn", name: "main".to_owned(), index: 0 },
+			LocationSegment { kind: "stmt", name: String::new(), index: 1 },
+			LocationSegment { kind: "block", name: String::new(), index: 1 },
+			LocationSegment { kind: "stmt", name: String::new(), index: 1 },
+			LocationSegment { kind: "block", name: String::new(), index: 0 },
+			LocationSegment { kind: "struct", name: "Plugin".to_owned(), index: 0 }
+		]
+	);
+	assert_eq!(locations[0].index, 0);
+}
+
+#[test]
+fn locate_returns_every_match() {
+	let ast: File = parse_quote! {
+		impl A {
+			fn some_func() {}
+		}
+
+		struct B;
+
+		impl A {
+			fn some_func() {}
+		}
+	};
+	let item_to_impl: ItemToImpl = (None, "A", parse_quote! { fn some_func() {} }).into();
+
+	let mut finder = Finder::default().to_find(&item_to_impl);
+	let locations = finder.locate(&ast);
+	assert_eq!(locations.len(), 2);
+	assert_eq!(locations[0].path[0].index, 0);
+	assert_eq!(locations[1].path[0].index, 2);
+}
+
+#[test]
+fn locate_returns_empty_vec_if_not_found() {
+	TestBuilder::default().with_mod_ast().execute(|builder| {
+		let item_to_mod: ItemToMod = ("SomeMod", parse_quote! { fn other_func() {} }).into();
+
+		let ast = builder.get_ref_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(finder.locate(ast).is_empty());
+		assert!(!finder.found[0]);
+	});
+}
+
+#[test]
+#[cfg(feature = "span-locations")]
+fn locate_doesnt_return_span_if_node_not_parsed_from_source() {
+	let ast: File = parse_quote! {
+		mod SomeMod {
+			fn some_func() {}
+		}
+	};
+	let item_to_mod: ItemToMod = ("SomeMod", parse_quote! { fn some_func() {} }).into();
+
+	let mut finder = Finder::default().to_find(&item_to_mod);
+	let locations = finder.locate(&ast);
+	assert_eq!(locations.len(), 1);
+	assert_eq!(locations[0].span, None);
+}
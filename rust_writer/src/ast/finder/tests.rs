@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use super::*;
-use crate::ast::implementors::ItemToTrait;
+use crate::ast::{finder::ToFind, implementors::ItemToTrait};
 use syn::{parse_quote, TraitItem};
 use test_builder::TestBuilder;
 
@@ -23,3 +23,19 @@ fn finder_works_for_a_implementor() {
 		assert!(!finder.found[0]);
 	});
 }
+
+#[test]
+fn finder_find_only_keeps_the_matches_of_the_last_search() {
+	let item_to_trait: ItemToTrait =
+		("MyTrait", TraitItem::Type(parse_quote! {type Type1: From<String>;})).into();
+	let ast: File = parse_quote! {
+		trait MyTrait {
+			type Type1: From<String>;
+		}
+	};
+
+	let mut finder = Finder::default().to_find(&item_to_trait);
+	assert!(finder.find(&ast));
+	assert!(finder.find(&ast));
+	assert_eq!(finder.matches.len(), 1);
+}
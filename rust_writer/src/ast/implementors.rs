@@ -13,20 +13,51 @@
 //! [`#[mutator]`](https://docs.rs/rust_writer/latest/rust_writer/ast/attr.mutator.html) macros
 //! come in handy to create a new implementor merging other implementors functionalities.
 //!
+//! The implementors are created from their required data using [`From`], while their optional
+//! settings are adjusted with their `with_*` methods:
+//!
+//! ```
+//! use rust_writer::ast::implementors::{ItemToImpl, MutationMode, Occurrence};
+//! use syn::parse_quote;
+//!
+//! let item_to_impl = ItemToImpl::from((None, "MyStruct", parse_quote! { const A: u8 = 1; }))
+//!     .with_mode(MutationMode::Upsert)
+//!     .with_occurrence(Occurrence::First);
+//! ```
+//!
 //! # Disclaimer
 //!
 //! The set of implementors is still limited and will be updated as needed. Any PR to
 //! [the repo](https://github.com/tsenovilla/rust_writer) introducing new implementors is more than
 //! welcome.
 
+mod field_to_struct;
+pub(crate) mod helpers;
+mod item_from_file;
+mod item_from_impl;
+mod item_from_mod;
+mod item_from_trait;
 mod item_to_file;
 mod item_to_impl;
 mod item_to_mod;
 mod item_to_trait;
+mod options;
+mod stmt_to_fn;
 mod token_stream_to_macro;
+mod variant_to_enum;
 
+pub use field_to_struct::FieldToStruct;
+pub use item_from_file::ItemFromFile;
+pub use item_from_impl::ItemFromImpl;
+pub use item_from_mod::ItemFromMod;
+pub use item_from_trait::ItemFromTrait;
 pub use item_to_file::ItemToFile;
 pub use item_to_impl::ItemToImpl;
 pub use item_to_mod::ItemToMod;
 pub use item_to_trait::ItemToTrait;
+pub use options::{
+	AttrsMatching, ImplHeader, ImplMatching, Insertion, MutationMode, Occurrence, StmtInsertion,
+};
+pub use stmt_to_fn::StmtToFn;
 pub use token_stream_to_macro::TokenStreamToMacro;
+pub use variant_to_enum::VariantToEnum;
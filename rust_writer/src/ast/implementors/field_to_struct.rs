@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{helpers, Insertion, Occurrence},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use syn::{visit::Visit, visit_mut::VisitMut, Field, Fields, File, ItemStruct};
+
+/// This implementor targets a named field inside a struct definition.
+///
+/// When it's used with [`Finder`], it doesn't take attributes into account, this is, if the
+/// following is contained in the target struct
+///
+/// ```no_compile
+/// /// Some nice docs
+/// #[some_attr]
+/// pub config: Config,
+/// ```
+///
+/// and the target field is `pub config: Config`, the [`find`] method will return true.
+///
+/// When it's used with [`Mutator`], the [`insertion`](#structfield.insertion) field defines where
+/// the target field is inserted among the existing ones. See [`Insertion`] for further details.
+///
+/// Tuple structs and unit structs are never targeted by this implementor.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FieldToStruct<'a> {
+	/// The struct's name.
+	pub struct_name: &'a str,
+	/// The target field.
+	pub field: Field,
+	/// Where the target field is inserted. Defaults to [`Insertion::End`].
+	pub insertion: Insertion,
+	/// Which of the structs named after `struct_name` receive the target field. Defaults to
+	/// [`Occurrence::All`].
+	pub occurrence: Occurrence,
+}
+
+impl<'a> From<(&'a str, Field)> for FieldToStruct<'a> {
+	fn from(tuple: (&'a str, Field)) -> Self {
+		Self {
+			struct_name: tuple.0,
+			field: tuple.1,
+			insertion: Insertion::default(),
+			occurrence: Occurrence::default(),
+		}
+	}
+}
+
+impl FieldToStruct<'_> {
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: Insertion) -> Self {
+		self.insertion = insertion;
+		self
+	}
+
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
+	}
+}
+
+fn field_without_attrs(field: &Field) -> Field {
+	Field { attrs: Vec::new(), ..field.clone() }
+}
+
+impl<'a> ToFind<'a, FieldToStruct<'a>, 1> for Finder<'a, EmptyFinder, 1> {
+	fn to_find(self, finder: &'a FieldToStruct<'a>) -> Finder<'a, FieldToStruct<'a>, 1> {
+		Finder::new(finder)
+	}
+}
+
+impl<'a, 'ast> Visit<'ast> for Finder<'a, FieldToStruct<'a>, 1> {
+	fn visit_item_struct(&mut self, item_struct: &'ast ItemStruct) {
+		let self_field_no_attrs = field_without_attrs(&self.finder.field);
+		match item_struct.fields {
+			Fields::Named(ref fields) if item_struct.ident == self.finder.struct_name =>
+				for field in &fields.named {
+					if field_without_attrs(field) == self_field_no_attrs {
+						self.found[0] = true;
+						self.record_match(field);
+					}
+				},
+			_ => (),
+		}
+	}
+}
+
+impl<'a> ToMutate<'a, FieldToStruct<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a FieldToStruct<'a>) -> Mutator<'a, FieldToStruct<'a>, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for FieldToStruct<'_> {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		let field = match self.field.ident {
+			Some(ref ident) => format!("field `{}`", ident),
+			None => "the field".to_owned(),
+		};
+		helpers::describe_addition_failure(
+			&format!("struct `{}` with named fields", self.struct_name),
+			&field,
+			occurrences,
+		)
+	}
+}
+
+impl Preserve for FieldToStruct<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "struct".into(),
+			name: self.struct_name.into(),
+		})]
+	}
+}
+
+impl<'a> VisitMut for Mutator<'a, FieldToStruct<'a>, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+	}
+
+	fn visit_item_struct_mut(&mut self, item_struct: &mut ItemStruct) {
+		match item_struct.fields {
+			Fields::Named(ref mut fields)
+				if item_struct.ident == self.mutator.struct_name &&
+					helpers::select_occurrence(self, self.mutator.occurrence) =>
+				if let Some(index) = helpers::insertion_index(
+					fields.named.iter(),
+					&self.mutator.field,
+					&self.mutator.insertion,
+				) {
+					self.mutated[0] = true;
+					fields.named.insert(index, self.mutator.field.clone());
+				},
+			_ => (),
+		}
+	}
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{Error, MutationFailure};
+use syn::{parse_quote, Item};
+use test_builder::TestBuilder;
+
+#[test]
+fn field_to_struct_finder_find_field_if_present() {
+	TestBuilder::default().with_struct_ast().execute(|builder| {
+		let field_to_struct: FieldToStruct =
+			("MyStruct", parse_quote! { pub field2: String }).into();
+
+		let ast = builder.get_ref_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn field_to_struct_finder_find_field_if_present_despite_attrs() {
+	TestBuilder::default().with_struct_ast().execute(|builder| {
+		let field_to_struct: FieldToStruct =
+			("MyStruct", parse_quote! { pub(crate) path: PathBuf }).into();
+
+		let ast = builder.get_ref_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn field_to_struct_finder_cannot_find_field_if_struct_name_incorrect() {
+	TestBuilder::default().with_struct_ast().execute(|builder| {
+		let field_to_struct: FieldToStruct =
+			("OtherStruct", parse_quote! { pub field2: String }).into();
+
+		let ast = builder.get_ref_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn field_to_struct_finder_cannot_find_field_if_field_incorrect() {
+	TestBuilder::default().with_struct_ast().execute(|builder| {
+		// Visibility matters.
+		let field_to_struct: FieldToStruct = ("MyStruct", parse_quote! { field2: String }).into();
+
+		let ast = builder.get_ref_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn field_to_struct_mutate_works() {
+	TestBuilder::default().with_struct_ast().execute(|mut builder| {
+		let field_to_struct: FieldToStruct =
+			("MyStruct", parse_quote! { pub config: Config }).into();
+
+		let ast = builder.get_mut_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(!finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&field_to_struct);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn field_to_struct_mutate_with_insertion_works() {
+	TestBuilder::default().with_struct_ast().execute(|builder| {
+		let ast = builder.get_ref_ast_file("struct.rs").expect("This exists; qed;");
+		let mutated_field_names = |insertion| {
+			let field_to_struct =
+				FieldToStruct::from(("MyStruct", parse_quote! { pub config: Config }))
+					.with_insertion(insertion);
+			let mut ast = ast.clone();
+			let mut mutator = Mutator::default().to_mutate(&field_to_struct);
+			assert!(mutator.mutate(&mut ast).is_ok());
+			match ast.items[1] {
+				Item::Struct(ItemStruct { fields: Fields::Named(ref fields), .. }) => fields
+					.named
+					.iter()
+					.map(|field| field.ident.as_ref().expect("Named fields; qed;").to_string())
+					.collect::<Vec<_>>(),
+				_ => unreachable!("By construction this is MyStruct; qed;"),
+			}
+		};
+
+		assert_eq!(mutated_field_names(Insertion::Start), ["config", "field1", "field2", "path"]);
+		assert_eq!(
+			mutated_field_names(Insertion::Before(parse_quote! { path })),
+			["field1", "field2", "config", "path"]
+		);
+		assert_eq!(
+			mutated_field_names(Insertion::After(parse_quote! { field1 })),
+			["field1", "config", "field2", "path"]
+		);
+		assert_eq!(mutated_field_names(Insertion::Sorted), ["config", "field1", "field2", "path"]);
+		assert_eq!(mutated_field_names(Insertion::Grouped), ["field1", "field2", "path", "config"]);
+	});
+}
+
+#[test]
+fn field_to_struct_mutate_fails_if_anchor_not_found() {
+	TestBuilder::default().with_struct_ast().execute(|mut builder| {
+		let field_to_struct =
+			FieldToStruct::from(("MyStruct", parse_quote! { pub config: Config }))
+				.with_insertion(Insertion::After(parse_quote! { field3 }));
+
+		let ast = builder.get_mut_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&field_to_struct);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures[0].description ==
+				"cannot add field `config` to struct `MyStruct` with named fields"
+		));
+	});
+}
+
+#[test]
+fn field_to_struct_mutate_fails_if_struct_isnt_named_fields_struct() {
+	TestBuilder::default().with_struct_ast().execute(|mut builder| {
+		let field_to_struct: FieldToStruct =
+			("TupleStruct", parse_quote! { pub config: Config }).into();
+
+		let ast = builder.get_mut_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&field_to_struct);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "struct `TupleStruct` with named fields not found".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn field_to_struct_mutate_fails_if_cannot_find_struct() {
+	TestBuilder::default().with_struct_ast().execute(|mut builder| {
+		let field_to_struct: FieldToStruct =
+			("UnexistingStruct", parse_quote! { pub config: Config }).into();
+
+		let ast = builder.get_mut_ast_file("struct.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(!finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&field_to_struct);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "struct `UnexistingStruct` with named fields not found".to_owned()
+			}]
+		));
+
+		let mut finder = Finder::default().to_find(&field_to_struct);
+		assert!(!finder.find(ast));
+	});
+}
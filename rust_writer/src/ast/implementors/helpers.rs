@@ -0,0 +1,479 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		implementors::{AttrsMatching, Insertion, MutationMode, Occurrence},
+		mutator::Mutator,
+	},
+	preserver::{self, Lookup},
+};
+use quote::ToTokens;
+use regex::Regex;
+use rustilities::parsing::attrs_mut::{self, AttrsMut};
+use std::fmt::Debug;
+use syn::{
+	visit_mut::{self, VisitMut},
+	Attribute, Expr, ExprLit, Field, File, Ident, ImplItem, Item, ItemMod, ItemUse, Lit, LitStr,
+	Meta, MetaNameValue, Stmt, TraitItem, UseName, UsePath, UseRename, UseTree, Variant,
+};
+
+/// Identity of an item inside its container: its kind and its ident.
+pub(crate) trait Identity {
+	fn identity(&self) -> Option<(&'static str, &Ident)>;
+
+	/// The key used to sort and group items: their kind and their ident as a string.
+	fn sort_key(&self) -> Option<(&'static str, String)> {
+		self.identity().map(|(kind, ident)| (kind, ident.to_string()))
+	}
+
+	/// Whether the item is one of the `extern crate` or `mod` declarations that usually lead a
+	/// file, which new `use` declarations are placed after if there's no other `use` declaration.
+	fn is_leading_declaration(&self) -> bool {
+		false
+	}
+
+	/// Moves the non-preserved code at the head of the container from `next` to `self`, so
+	/// inserting `self` just before `next` in a preserved AST doesn't place it before eg the inner
+	/// attributes of the file.
+	fn take_head_from(&mut self, _next: &mut Self) {}
+}
+
+impl Identity for Item {
+	fn identity(&self) -> Option<(&'static str, &Ident)> {
+		match self {
+			Item::Const(item) => Some(("const", &item.ident)),
+			Item::Enum(item) => Some(("enum", &item.ident)),
+			Item::ExternCrate(item) => Some(("extern crate", &item.ident)),
+			Item::Fn(item) => Some(("fn", &item.sig.ident)),
+			Item::Macro(item) => item.ident.as_ref().map(|ident| ("macro", ident)),
+			Item::Mod(item) => Some(("mod", &item.ident)),
+			Item::Static(item) => Some(("static", &item.ident)),
+			Item::Struct(item) => Some(("struct", &item.ident)),
+			Item::Trait(item) => Some(("trait", &item.ident)),
+			Item::TraitAlias(item) => Some(("trait alias", &item.ident)),
+			Item::Type(item) => Some(("type", &item.ident)),
+			Item::Union(item) => Some(("union", &item.ident)),
+			_ => None,
+		}
+	}
+
+	fn sort_key(&self) -> Option<(&'static str, String)> {
+		match self {
+			Item::Use(item) => Some(("use", item.tree.to_token_stream().to_string())),
+			_ => self.identity().map(|(kind, ident)| (kind, ident.to_string())),
+		}
+	}
+
+	fn is_leading_declaration(&self) -> bool {
+		matches!(self, Item::ExternCrate(_) | Item::Mod(_))
+	}
+
+	fn take_head_from(&mut self, next: &mut Self) {
+		if let (Some(attrs), Some(next_attrs)) = (self.attrs_mut(), next.attrs_mut()) {
+			attrs.splice(0..0, preserver::take_non_preserved_head(next_attrs));
+		}
+	}
+}
+
+impl Identity for ImplItem {
+	fn identity(&self) -> Option<(&'static str, &Ident)> {
+		match self {
+			ImplItem::Const(item) => Some(("const", &item.ident)),
+			ImplItem::Fn(item) => Some(("fn", &item.sig.ident)),
+			ImplItem::Type(item) => Some(("type", &item.ident)),
+			_ => None,
+		}
+	}
+}
+
+impl Identity for TraitItem {
+	fn identity(&self) -> Option<(&'static str, &Ident)> {
+		match self {
+			TraitItem::Const(item) => Some(("const", &item.ident)),
+			TraitItem::Fn(item) => Some(("fn", &item.sig.ident)),
+			TraitItem::Type(item) => Some(("type", &item.ident)),
+			_ => None,
+		}
+	}
+}
+
+impl Identity for Field {
+	fn identity(&self) -> Option<(&'static str, &Ident)> {
+		self.ident.as_ref().map(|ident| ("field", ident))
+	}
+}
+
+impl Identity for Variant {
+	fn identity(&self) -> Option<(&'static str, &Ident)> {
+		Some(("variant", &self.ident))
+	}
+}
+
+impl Identity for Stmt {
+	fn identity(&self) -> Option<(&'static str, &Ident)> {
+		match self {
+			Stmt::Item(item) => item.identity(),
+			_ => None,
+		}
+	}
+}
+
+/// Gets a copy of `item` without the attributes ignored by `attrs_matching`, so it can be compared
+/// against another item stripped in the same way.
+pub(super) fn without_ignored_attrs<T: AttrsMut + Clone>(
+	item: &T,
+	attrs_matching: AttrsMatching,
+) -> T {
+	match attrs_matching {
+		AttrsMatching::Exact => with_normalized_docs(item),
+		AttrsMatching::IgnoreDocs => attrs_mut::tt_without_docs(item),
+		AttrsMatching::IgnoreAll => attrs_mut::tt_without_attrs(item),
+	}
+}
+
+/// Tells whether a container with the given `attrs` is targeted by an implementor expecting
+/// `container_attrs`. Doc comments aside, the attributes must be exactly the same and in the same
+/// order. Any container is targeted if `container_attrs` is `None`.
+pub(super) fn container_attrs_match(
+	attrs: &[Attribute],
+	container_attrs: Option<&[Attribute]>,
+) -> bool {
+	let without_docs = |attrs: &[Attribute]| {
+		attrs
+			.iter()
+			.filter(|attr| !attr.path().is_ident("doc"))
+			.cloned()
+			.collect::<Vec<_>>()
+	};
+	container_attrs
+		.is_none_or(|container_attrs| without_docs(attrs) == without_docs(container_attrs))
+}
+
+/// Gives a container created by an implementor the attributes it's looking for, so the container
+/// is matched by [`container_attrs_match`] from then on. Doc comments are kept.
+pub(super) fn set_container_attrs(
+	attrs: &mut Vec<Attribute>,
+	container_attrs: Option<&[Attribute]>,
+) {
+	if let Some(container_attrs) = container_attrs {
+		attrs.retain(|attr| attr.path().is_ident("doc"));
+		attrs.extend(container_attrs.iter().filter(|attr| !attr.path().is_ident("doc")).cloned());
+	}
+}
+
+// Doc comments and `#[doc = "..."]` attributes carry the same content, but their literals are
+// represented differently, so they aren't equal unless the literals are rebuilt from their value.
+fn with_normalized_docs<T: AttrsMut + Clone>(item: &T) -> T {
+	let mut output = item.clone();
+	for attr in output.attrs_mut().into_iter().flatten() {
+		if let Meta::NameValue(MetaNameValue {
+			ref path,
+			value: Expr::Lit(ExprLit { lit: Lit::Str(ref mut lit), .. }),
+			..
+		}) = attr.meta
+		{
+			if path.is_ident("doc") {
+				*lit = LitStr::new(&lit.value(), lit.span());
+			}
+		}
+	}
+	output
+}
+
+/// Computes the index where `item` should be inserted into `items` following `insertion`. Returns
+/// `None` if the anchor item cannot be found.
+pub(super) fn insertion_index<'a, T, I>(items: I, item: &T, insertion: &Insertion) -> Option<usize>
+where
+	T: Identity + 'a,
+	I: ExactSizeIterator<Item = &'a T> + Clone,
+{
+	let is_anchor = |anchor: &Ident, existing: &T| {
+		existing.identity().is_some_and(|(_, ident)| ident == anchor)
+	};
+	match insertion {
+		Insertion::End => Some(items.len()),
+		Insertion::Start => Some(0),
+		Insertion::Before(anchor) => items.clone().position(|existing| is_anchor(anchor, existing)),
+		Insertion::After(anchor) => items
+			.clone()
+			.position(|existing| is_anchor(anchor, existing))
+			.map(|index| index + 1),
+		Insertion::Sorted | Insertion::Grouped => {
+			let Some((kind, key)) = item.sort_key() else {
+				return Some(items.len());
+			};
+			let mut last_same_kind = None;
+			for (index, existing) in items.clone().enumerate() {
+				match existing.sort_key() {
+					Some((existing_kind, existing_key)) if existing_kind == kind => {
+						if *insertion == Insertion::Sorted && existing_key > key {
+							return Some(index);
+						}
+						last_same_kind = Some(index);
+					},
+					_ => (),
+				}
+			}
+			Some(match last_same_kind {
+				Some(index) => index + 1,
+				None if kind == "use" => leading_declarations_len(items),
+				None => items.len(),
+			})
+		},
+	}
+}
+
+// The number of `extern crate` and `mod` declarations leading `items`.
+fn leading_declarations_len<'a, T: Identity + 'a>(items: impl Iterator<Item = &'a T>) -> usize {
+	items.take_while(|item| item.is_leading_declaration()).count()
+}
+
+/// Adds `item` to `items` following `mode` and `insertion`. Returns whether `items` has been
+/// mutated.
+pub(super) fn add_item<T: Identity + AttrsMut + Clone>(
+	items: &mut Vec<T>,
+	item: &T,
+	mode: MutationMode,
+	insertion: &Insertion,
+) -> bool {
+	if mode != MutationMode::Insert {
+		let position = item.identity().and_then(|identity| {
+			items.iter().position(|existing| existing.identity() == Some(identity))
+		});
+		if let Some(position) = position {
+			let mut new_item = item.clone();
+			let mut attrs = items[position].attrs_mut().cloned().unwrap_or_default();
+			if let Some(new_attrs) = new_item.attrs_mut() {
+				new_attrs.retain(|attr| !attrs.contains(attr));
+				attrs.append(new_attrs);
+				*new_attrs = attrs;
+			}
+			items[position] = new_item;
+			return true;
+		}
+	}
+
+	match insertion_index(items.iter(), item, insertion) {
+		Some(index) if mode != MutationMode::Replace => {
+			let mut item = item.clone();
+			if index < items.len() && index <= leading_declarations_len(items.iter()) {
+				item.take_head_from(&mut items[index]);
+			}
+			items.insert(index, item);
+			true
+		},
+		_ => false,
+	}
+}
+
+/// Inserts `container`, created by an implementor, among `items` following `insertion`. If the
+/// anchor of [`Insertion::Before`] or [`Insertion::After`] isn't among `items`, `container` is
+/// appended to them.
+pub(super) fn insert_container(items: &mut Vec<Item>, container: Item, insertion: &Insertion) {
+	if !add_item(items, &container, MutationMode::Insert, insertion) {
+		items.push(container);
+	}
+}
+
+/// Removes from `items` every item targeted by `is_target`. Returns whether `items` has been
+/// mutated.
+///
+/// In a preserved AST, the non-preserved code preceding an item is carried by its attributes, so
+/// it's moved to the next item instead of being removed together with the item. Preserved
+/// containers always end by a marker, so there's always a next item to carry it.
+pub(super) fn remove_items<T: AttrsMut>(
+	items: &mut Vec<T>,
+	is_target: impl Fn(&T) -> bool,
+) -> bool {
+	let items_len = items.len();
+	let mut non_preserved = Vec::new();
+	let mut index = 0;
+	while index < items.len() {
+		if is_target(&items[index]) {
+			let mut item = items.remove(index);
+			if let Some(attrs) = item.attrs_mut() {
+				non_preserved.extend(preserver::take_non_preserved_code(attrs));
+			}
+			continue;
+		}
+		if let Some(attrs) = items[index].attrs_mut().filter(|_| !non_preserved.is_empty()) {
+			// The removed item was surrounded by blank lines, only one of them is kept.
+			if non_preserved.last().is_some_and(preserver::is_blank_line) &&
+				attrs.first().is_some_and(preserver::is_blank_line)
+			{
+				non_preserved.pop();
+			}
+			attrs.splice(0..0, non_preserved.drain(..));
+		}
+		index += 1;
+	}
+	items.len() < items_len
+}
+
+/// Registers a new match for `mutator` and tells whether its target item should be added to the
+/// matched container following `occurrence`.
+pub(super) fn select_occurrence<T: Debug + Clone>(
+	mutator: &mut Mutator<'_, T, 1>,
+	occurrence: Occurrence,
+) -> bool {
+	mutator.occurrences[0] += 1;
+	match occurrence {
+		Occurrence::All => true,
+		Occurrence::First | Occurrence::ExactlyOne => mutator.occurrences[0] == 1,
+		Occurrence::Nth(index) => mutator.occurrences[0] == index + 1,
+	}
+}
+
+/// Visits `file` with `mutator`, counting the matches from scratch. If `occurrence` is
+/// `ExactlyOne`, the mutation is applied to a copy of `file`, which only replaces it if there's
+/// exactly one match.
+pub(super) fn visit_file_mut<'a, T>(
+	mutator: &mut Mutator<'a, T, 1>,
+	file: &mut File,
+	occurrence: Occurrence,
+) where
+	T: Debug + Clone,
+	Mutator<'a, T, 1>: VisitMut,
+{
+	mutator.occurrences[0] = 0;
+	if occurrence != Occurrence::ExactlyOne {
+		return visit_mut::visit_file_mut(mutator, file);
+	}
+
+	let mut file_copy = file.clone();
+	visit_mut::visit_file_mut(mutator, &mut file_copy);
+	if mutator.occurrences[0] == 1 {
+		*file = file_copy;
+	} else {
+		mutator.mutated[0] = false;
+	}
+}
+
+/// The content of the inline modules found at `path` starting from `item_mod`, eg `["a", "b"]`
+/// matches the module `b` inside `item_mod` if the latter is named `a`. Several modules may be
+/// found, as `cfg` attributes may lead to modules sharing their name. The innermost modules are
+/// only kept if their attributes match `container_attrs`, see [`container_attrs_match`].
+pub(super) fn mod_contents<'a>(
+	item_mod: &'a ItemMod,
+	path: &[&str],
+	container_attrs: Option<&[Attribute]>,
+) -> Vec<&'a Vec<Item>> {
+	match (path.split_first(), &item_mod.content) {
+		(Some((name, rest)), Some((_, items))) if item_mod.ident == name => match rest {
+			[] if container_attrs_match(&item_mod.attrs, container_attrs) => vec![items],
+			[] => Vec::new(),
+			_ => items
+				.iter()
+				.filter_map(|item| match item {
+					Item::Mod(item_mod) => Some(mod_contents(item_mod, rest, container_attrs)),
+					_ => None,
+				})
+				.flatten()
+				.collect(),
+		},
+		_ => Vec::new(),
+	}
+}
+
+/// Same as [`mod_contents`], but the content is mutable.
+pub(super) fn mod_contents_mut<'a>(
+	item_mod: &'a mut ItemMod,
+	path: &[&str],
+	container_attrs: Option<&[Attribute]>,
+) -> Vec<&'a mut Vec<Item>> {
+	match (path.split_first(), &mut item_mod.content) {
+		(Some((name, rest)), Some((_, items))) if item_mod.ident == name => match rest {
+			[] if container_attrs_match(&item_mod.attrs, container_attrs) => vec![items],
+			[] => Vec::new(),
+			_ => items
+				.iter_mut()
+				.filter_map(|item| match item {
+					Item::Mod(item_mod) => Some(mod_contents_mut(item_mod, rest, container_attrs)),
+					_ => None,
+				})
+				.flatten()
+				.collect(),
+		},
+		_ => Vec::new(),
+	}
+}
+
+/// Describes an item for error messages, eg "`fn some_func`".
+pub(super) fn describe_item<T: Identity>(item: &T) -> String {
+	item.sort_key().map_or_else(
+		|| "the item".to_owned(),
+		|(kind, key)| format!("`{} {}`", kind, key.replace(" :: ", "::")),
+	)
+}
+
+/// Describes an `impl` block for error messages, eg "impl `SomeTrait for SomeType`".
+pub(super) fn describe_impl(trait_name: Option<&str>, implementor_name: &str) -> String {
+	match trait_name {
+		Some(trait_name) => format!("impl `{} for {}`", trait_name, implementor_name),
+		None => format!("impl `{}`", implementor_name),
+	}
+}
+
+/// Describes the failure of an implementor adding `element` to `container`. If the implementor
+/// didn't match any container, the container wasn't found.
+pub(super) fn describe_addition_failure(
+	container: &str,
+	element: &str,
+	occurrences: usize,
+) -> String {
+	if occurrences == 0 {
+		format!("{} not found", container)
+	} else {
+		format!("cannot add {} to {}", element, container)
+	}
+}
+
+// The visibility and qualifiers that may precede the kind of an item.
+const ITEM_QUALIFIERS: &str = concat!(
+	r"^(?:pub(?:\([^)]*\))?\s+)?",
+	r#"(?:(?:const|async|unsafe|extern(?:\s+"[^"]*")?|default)\s+)*"#
+);
+
+const ITEM_KINDS: &str = concat!(
+	r"(?:(?:fn|struct|enum|union|trait|type|const|static|mod|extern\s+crate)\s+",
+	r"|macro_rules!\s*)"
+);
+
+/// A lookup matching the lines starting a `use` declaration whose path starts by the same segment
+/// as the one of `item_use`, eg `use std` for `use std::fmt;`. The rest of the path isn't looked
+/// at, as the declaration may span several lines.
+pub(super) fn use_lookup(item_use: &ItemUse) -> Lookup<'static> {
+	let (separator, first_segment) = match item_use.tree {
+		UseTree::Path(UsePath { ref ident, .. }) |
+		UseTree::Name(UseName { ref ident }) |
+		UseTree::Rename(UseRename { ref ident, .. }) =>
+			(r"\s+", format!(r"{}\b", regex::escape(&ident.to_string()))),
+		UseTree::Glob(_) => (r"\s*", r"\*".to_owned()),
+		UseTree::Group(_) => (r"\s*", r"\{".to_owned()),
+	};
+	let separator = if item_use.leading_colon.is_some() { r"\s*::\s*" } else { separator };
+	Lookup::Regex(
+		Regex::new(&format!("{}use{}{}", ITEM_QUALIFIERS, separator, first_segment))
+			.expect("The regex is valid; qed;"),
+	)
+}
+
+/// A lookup matching the lines starting an item of the given kind, or of any kind if `kind` is
+/// `None`. If `name` is given, only the items named after it are matched.
+pub(super) fn item_lookup(kind: Option<&str>, name: Option<&Ident>) -> Lookup<'static> {
+	let kind = match kind {
+		Some("macro") => r"macro_rules!\s*".to_owned(),
+		Some("trait alias") => r"trait\s+".to_owned(),
+		Some(kind) => format!(r"{}\s+", regex::escape(kind)),
+		None => ITEM_KINDS.to_owned(),
+	};
+	let name =
+		name.map_or_else(String::new, |name| format!(r"{}\b", regex::escape(&name.to_string())));
+	Lookup::Regex(
+		Regex::new(&format!("{}{}{}", ITEM_QUALIFIERS, kind, name))
+			.expect("The regex is valid; qed;"),
+	)
+}
@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::ast::{implementors::ItemToTrait, mutator::ToMutate};
+use syn::parse_quote;
+
+fn impl_items() -> Vec<ImplItem> {
+	vec![
+		parse_quote! { type Balance = u64; },
+		parse_quote! {
+			/// Doc
+			#[some_attr]
+			fn some_func() {}
+		},
+		parse_quote! { const SOME_CONST: u8 = 1; },
+	]
+}
+
+#[test]
+fn identity_works() {
+	let item: Item = parse_quote! { fn some_func() {} };
+	let impl_item: ImplItem = parse_quote! { type Balance = u64; };
+	let trait_item: TraitItem = parse_quote! { const SOME_CONST: u8; };
+	let use_item: Item = parse_quote! { use std::path::Path; };
+
+	assert_eq!(item.identity(), Some(("fn", &parse_quote! { some_func })));
+	assert_eq!(impl_item.identity(), Some(("type", &parse_quote! { Balance })));
+	assert_eq!(trait_item.identity(), Some(("const", &parse_quote! { SOME_CONST })));
+	assert_eq!(use_item.identity(), None);
+}
+
+#[test]
+fn add_item_insert_mode_appends_item() {
+	let mut items = impl_items();
+	let item: ImplItem = parse_quote! { type Balance = u128; };
+
+	assert!(add_item(&mut items, &item, MutationMode::Insert, &Insertion::End));
+	assert_eq!(items.len(), 4);
+	assert_eq!(items[3], item);
+}
+
+#[test]
+fn add_item_replace_mode_keeps_position() {
+	let mut items = impl_items();
+	let item: ImplItem = parse_quote! { type Balance = u128; };
+
+	assert!(add_item(&mut items, &item, MutationMode::Replace, &Insertion::End));
+	assert_eq!(items.len(), 3);
+	assert_eq!(items[0], item);
+}
+
+#[test]
+fn add_item_replace_mode_keeps_attributes() {
+	let mut items = impl_items();
+	let item: ImplItem = parse_quote! {
+		#[other_attr]
+		fn some_func() -> u8 { 1 }
+	};
+
+	let expected_item: ImplItem = parse_quote! {
+		/// Doc
+		#[some_attr]
+		#[other_attr]
+		fn some_func() -> u8 { 1 }
+	};
+
+	assert!(add_item(&mut items, &item, MutationMode::Replace, &Insertion::End));
+	assert_eq!(items.len(), 3);
+	assert_eq!(items[1], expected_item);
+}
+
+#[test]
+fn add_item_replace_mode_takes_kind_into_account() {
+	let mut items = impl_items();
+	let item: ImplItem = parse_quote! { const Balance: u8 = 1; };
+
+	assert!(!add_item(&mut items, &item, MutationMode::Replace, &Insertion::End));
+	assert_eq!(items, impl_items());
+}
+
+#[test]
+fn add_item_replace_mode_fails_if_item_without_identity() {
+	let mut items: Vec<Item> = vec![parse_quote! { use std::path::Path; }];
+	let item: Item = parse_quote! { use std::path::Path; };
+
+	assert!(!add_item(&mut items, &item, MutationMode::Replace, &Insertion::End));
+	assert_eq!(items.len(), 1);
+}
+
+#[test]
+fn add_item_upsert_mode_replaces_item_if_present() {
+	let mut items = impl_items();
+	let item: ImplItem = parse_quote! { type Balance = u128; };
+
+	assert!(add_item(&mut items, &item, MutationMode::Upsert, &Insertion::End));
+	assert_eq!(items.len(), 3);
+	assert_eq!(items[0], item);
+}
+
+#[test]
+fn add_item_upsert_mode_appends_item_if_not_present() {
+	let mut items = impl_items();
+	let item: ImplItem = parse_quote! { type AccountId = u64; };
+
+	assert!(add_item(&mut items, &item, MutationMode::Upsert, &Insertion::End));
+	assert_eq!(items.len(), 4);
+	assert_eq!(items[3], item);
+}
+
+#[test]
+fn insertion_index_start_and_end_work() {
+	let items = impl_items();
+	let item: ImplItem = parse_quote! { type AccountId = u64; };
+
+	assert_eq!(insertion_index(items.iter(), &item, &Insertion::Start), Some(0));
+	assert_eq!(insertion_index(items.iter(), &item, &Insertion::End), Some(3));
+}
+
+#[test]
+fn insertion_index_before_and_after_anchor_work() {
+	let items = impl_items();
+	let item: ImplItem = parse_quote! { type AccountId = u64; };
+
+	assert_eq!(
+		insertion_index(items.iter(), &item, &Insertion::Before(parse_quote! { some_func })),
+		Some(1)
+	);
+	assert_eq!(
+		insertion_index(items.iter(), &item, &Insertion::After(parse_quote! { some_func })),
+		Some(2)
+	);
+}
+
+#[test]
+fn insertion_index_fails_if_anchor_not_found() {
+	let items = impl_items();
+	let item: ImplItem = parse_quote! { type AccountId = u64; };
+
+	assert_eq!(
+		insertion_index(items.iter(), &item, &Insertion::Before(parse_quote! { other_func })),
+		None
+	);
+	assert!(!add_item(
+		&mut impl_items(),
+		&item,
+		MutationMode::Insert,
+		&Insertion::After(parse_quote! { other_func })
+	));
+}
+
+#[test]
+fn insertion_index_sorted_works() {
+	let items: Vec<Item> = vec![
+		parse_quote! { use std::fs; },
+		parse_quote! { use std::path::PathBuf; },
+		parse_quote! { fn a() {} },
+		parse_quote! { fn c() {} },
+	];
+	let use_item: Item = parse_quote! { use std::io; };
+	let fn_item: Item = parse_quote! { fn b() {} };
+	let last_fn_item: Item = parse_quote! { fn d() {} };
+	let struct_item: Item = parse_quote! { struct A; };
+
+	assert_eq!(insertion_index(items.iter(), &use_item, &Insertion::Sorted), Some(1));
+	assert_eq!(insertion_index(items.iter(), &fn_item, &Insertion::Sorted), Some(3));
+	assert_eq!(insertion_index(items.iter(), &last_fn_item, &Insertion::Sorted), Some(4));
+	assert_eq!(insertion_index(items.iter(), &struct_item, &Insertion::Sorted), Some(4));
+}
+
+#[test]
+fn insertion_index_grouped_works() {
+	let items: Vec<Item> = vec![
+		parse_quote! { use std::path::PathBuf; },
+		parse_quote! { use std::fs; },
+		parse_quote! { fn main() {} },
+	];
+	let use_item: Item = parse_quote! { use std::io; };
+	let struct_item: Item = parse_quote! { struct A; };
+
+	assert_eq!(insertion_index(items.iter(), &use_item, &Insertion::Grouped), Some(2));
+	assert_eq!(insertion_index(items.iter(), &struct_item, &Insertion::Grouped), Some(3));
+}
+
+#[test]
+fn insertion_index_grouped_places_first_use_after_leading_declarations() {
+	let items: Vec<Item> = vec![
+		parse_quote! { extern crate alloc; },
+		parse_quote! { mod a; },
+		parse_quote! { fn main() {} },
+		parse_quote! { mod b; },
+	];
+	let use_item: Item = parse_quote! { use std::io; };
+
+	assert_eq!(insertion_index(items.iter(), &use_item, &Insertion::Grouped), Some(2));
+	assert_eq!(insertion_index(items.iter(), &use_item, &Insertion::Sorted), Some(2));
+	assert_eq!(insertion_index(items[2..].iter(), &use_item, &Insertion::Grouped), Some(0));
+	assert_eq!(insertion_index(Vec::<Item>::new().iter(), &use_item, &Insertion::Grouped), Some(0));
+}
+
+#[test]
+fn select_occurrence_works() {
+	let item_to_trait: ItemToTrait = ("MyTrait", parse_quote! { type Type1: From<u8>; }).into();
+
+	let selected = |occurrence| {
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		(0..3).map(|_| select_occurrence(&mut mutator, occurrence)).collect::<Vec<_>>()
+	};
+
+	assert_eq!(selected(Occurrence::All), vec![true, true, true]);
+	assert_eq!(selected(Occurrence::First), vec![true, false, false]);
+	assert_eq!(selected(Occurrence::ExactlyOne), vec![true, false, false]);
+	assert_eq!(selected(Occurrence::Nth(1)), vec![false, true, false]);
+}
+
+#[test]
+fn without_ignored_attrs_works() {
+	let item: ImplItem = parse_quote! {
+		/// Doc
+		#[some_attr]
+		fn some_func() {}
+	};
+
+	assert_eq!(
+		without_ignored_attrs(&item, AttrsMatching::Exact),
+		without_ignored_attrs(
+			&parse_quote! {
+				#[doc = " Doc"]
+				#[some_attr]
+				fn some_func() {}
+			},
+			AttrsMatching::Exact
+		)
+	);
+	assert_eq!(
+		without_ignored_attrs(&item, AttrsMatching::IgnoreDocs),
+		parse_quote! {
+			#[some_attr]
+			fn some_func() {}
+		}
+	);
+	assert_eq!(
+		without_ignored_attrs(&item, AttrsMatching::IgnoreAll),
+		parse_quote! { fn some_func() {} }
+	);
+}
+
+#[test]
+fn describe_item_works() {
+	let impl_item: ImplItem = parse_quote! { type Balance = u64; };
+	let use_item: Item = parse_quote! { use std::path::Path; };
+	let macro_item: Item = parse_quote! { some_macro!(); };
+
+	assert_eq!(describe_item(&impl_item), "`type Balance`");
+	assert_eq!(describe_item(&use_item), "`use std::path::Path`");
+	assert_eq!(describe_item(&macro_item), "the item");
+}
+
+#[test]
+fn describe_addition_failure_works() {
+	assert_eq!(
+		describe_addition_failure("trait `MyTrait`", "`type Balance`", 0),
+		"trait `MyTrait` not found"
+	);
+	assert_eq!(
+		describe_addition_failure("trait `MyTrait`", "`type Balance`", 1),
+		"cannot add `type Balance` to trait `MyTrait`"
+	);
+}
+
+#[test]
+fn item_lookup_works() {
+	let some_func: Ident = parse_quote! { some_func };
+
+	assert!(item_lookup(Some("fn"), Some(&some_func)).matches("pub(crate) fn some_func() {"));
+	assert!(!item_lookup(Some("fn"), Some(&some_func)).matches("struct some_func;"));
+	assert!(item_lookup(None, Some(&some_func)).matches("macro_rules! some_func {"));
+	assert!(item_lookup(Some("use"), None).matches("pub(in crate::a) use std::path::Path;"));
+	assert!(item_lookup(Some("extern crate"), None).matches("extern crate alloc;"));
+	assert!(!item_lookup(Some("use"), None).matches("fn some_func() {"));
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		implementors::helpers::{self, Identity},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use regex::Regex;
+use syn::{visit_mut::VisitMut, File, Item, Path, Type};
+
+/// This implementor removes an item from a complete AST. It's only meant to be used with
+/// [`Mutator`]: it doesn't implement [`ToFind`](crate::ast::finder::ToFind), as
+/// [`Mutator::ensure`] skips the mutation when the target is found, which is the opposite of what a
+/// removal needs. Look for the item with [`ItemToFile`](crate::ast::implementors::ItemToFile)
+/// instead.
+///
+/// It doesn't take attributes into account, this is, if the following is contained in the target
+/// AST
+///
+/// ```no_compile
+/// /// Some nice docs
+/// #[some_attr]
+/// type Type = ();
+/// ```
+///
+/// and the target item is `type Type = ();`, the item is removed together with its attributes.
+/// Every matching item is removed, and the mutation fails if none is found.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ItemFromFile {
+	pub item: Item,
+}
+
+impl From<Item> for ItemFromFile {
+	fn from(item: Item) -> Self {
+		Self { item }
+	}
+}
+
+impl<'a> ToMutate<'a, ItemFromFile, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a ItemFromFile) -> Mutator<'a, ItemFromFile, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemFromFile {
+	fn describe_failure(&self, _occurrences: usize) -> String {
+		format!("{} not found in the file", helpers::describe_item(&self.item))
+	}
+}
+
+/// `use` declarations are looked up by the first segment of their path, and macro invocations by
+/// the path of the macro. Other items without ident, such as `extern` blocks, cannot be inferred,
+/// so they aren't preserved.
+impl Preserve for ItemFromFile {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		let last_segment =
+			|path: &Path| path.segments.last().map(|segment| segment.ident.to_string());
+		let lookup = match (&self.item, self.item.identity()) {
+			(_, Some((kind, ident))) =>
+				Some(Lookup::Item { kind: kind.into(), name: ident.to_string().into() }),
+			(Item::Impl(item_impl), None) => match *item_impl.self_ty {
+				Type::Path(ref type_path) =>
+					last_segment(&type_path.path).map(|implementor_name| Lookup::Impl {
+						trait_name: item_impl
+							.trait_
+							.as_ref()
+							.and_then(|(_, path, _)| last_segment(path))
+							.map(Into::into),
+						implementor_name: implementor_name.into(),
+					}),
+				_ => None,
+			},
+			(Item::Use(item_use), None) => Some(helpers::use_lookup(item_use)),
+			(Item::Macro(item_macro), None) => {
+				let path = item_macro
+					.mac
+					.path
+					.segments
+					.iter()
+					.map(|segment| regex::escape(&segment.ident.to_string()))
+					.collect::<Vec<_>>()
+					.join(r"\s*::\s*");
+				Regex::new(&format!(r"{}\s*!", path)).ok().map(Lookup::Regex)
+			},
+			_ => None,
+		};
+		lookup.into_iter().map(Preserver::with_lookup).collect()
+	}
+}
+
+impl VisitMut for Mutator<'_, ItemFromFile, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		let self_item_no_docs =
+			rustilities::parsing::attrs_mut::tt_without_attrs(&self.mutator.item);
+		if helpers::remove_items(&mut file.items, |item| {
+			rustilities::parsing::attrs_mut::tt_without_attrs(item) == self_item_no_docs
+		}) {
+			self.mutated[0] = true;
+		}
+	}
+}
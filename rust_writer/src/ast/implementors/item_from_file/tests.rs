@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{
+	ast::{
+		finder::{Finder, ToFind},
+		implementors::ItemToFile,
+	},
+	Error, MutationFailure,
+};
+use syn::{parse_quote, Item};
+use test_builder::TestBuilder;
+
+#[test]
+fn item_from_file_mutate_works() {
+	TestBuilder::default().with_file_ast().execute(|mut builder| {
+		let item: Item = parse_quote! { use std::path::PathBuf; };
+		let item_from_file: ItemFromFile = item.clone().into();
+		let item_to_file: ItemToFile = item.into();
+
+		let ast = builder.get_mut_ast_file("file.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_file);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_file_mutate_works_despite_attrs() {
+	TestBuilder::default().with_file_ast().execute(|mut builder| {
+		let item: Item = parse_quote! { use std::fs; };
+		let item_from_file: ItemFromFile = item.clone().into();
+		let item_to_file: ItemToFile = item.into();
+
+		let ast = builder.get_mut_ast_file("file.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_file);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_file_mutate_fails_if_cannot_find_item() {
+	TestBuilder::default().with_file_ast().execute(|mut builder| {
+		let item: Item = parse_quote! { use std::io; };
+		let item_from_file: ItemFromFile = item.into();
+
+		let ast = builder.get_mut_ast_file("file.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_file);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`use std::io` not found in the file".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_file_preserves_item_to_remove() {
+	let item_from_file = ItemFromFile { item: parse_quote! { struct SomeStruct; } };
+	let item_from_impl =
+		ItemFromFile { item: parse_quote! { impl some::SomeTrait for SomeStruct {} } };
+	let item_from_use = ItemFromFile { item: parse_quote! { use std::path::Path; } };
+
+	assert_eq!(
+		item_from_file.preservers(),
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "struct".into(),
+			name: "SomeStruct".into()
+		})]
+	);
+	assert_eq!(
+		item_from_impl.preservers(),
+		vec![Preserver::with_lookup(Lookup::Impl {
+			trait_name: Some("SomeTrait".into()),
+			implementor_name: "SomeStruct".into(),
+		})]
+	);
+	let preservers = item_from_use.preservers();
+	assert_eq!(preservers.len(), 1);
+	assert!(preservers[0].lookup().matches("use std::path::Path;"));
+	assert!(preservers[0]
+		.lookup()
+		.matches("pub(crate) use std::{\n\tfmt,\n\tpath::Path,\n};"));
+	assert!(!preservers[0].lookup().matches("use core::path::Path;"));
+	assert!(!preservers[0].lookup().matches("use stdlib::Path;"));
+
+	let item_from_macro = ItemFromFile { item: parse_quote! { some::my_macro!(a, b); } };
+	let preservers = item_from_macro.preservers();
+	assert_eq!(preservers.len(), 1);
+	assert!(preservers[0].lookup().matches("some :: my_macro! {"));
+}
+
+#[test]
+fn item_from_file_mutate_removes_use_declarations_from_preserved_code() {
+	let code = "use std::fmt;\nuse std::io;\n\nfn main() {\n\n    let a = 1;\n}\n";
+
+	let item: Item = parse_quote! { use std::fmt; };
+	let item_from_file: ItemFromFile = item.into();
+	let main_preserver = Preserver::new("fn main");
+	let inferred_preservers = item_from_file.preservers();
+	let preservers: Vec<&Preserver> =
+		std::iter::once(&main_preserver).chain(&inferred_preservers).collect();
+	let mut ast = crate::preserver::preserve_and_parse_str(code, &preservers)
+		.expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_from_file);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		crate::preserver::resolve_preserved_to_string(&ast),
+		"use std::io;\n\nfn main() {\n\n    let a = 1;\n}\n"
+	);
+}
+
+#[test]
+fn item_from_file_mutate_keeps_non_preserved_code() {
+	let code =
+		"// Some header\n\nuse std::fmt;\n\n/// Some docs\n#[some_attr(\n\tsome_arg\n)]\nfn \
+	            remove() {}\n\nfn keep() {}\n";
+
+	let item: Item = parse_quote! { fn remove() {} };
+	let item_from_file: ItemFromFile = item.into();
+	let mut ast = crate::preserver::preserve_and_parse_str_for(code, &item_from_file)
+		.expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_from_file);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		crate::preserver::resolve_preserved_to_string(&ast),
+		"// Some header\n\nuse std::fmt;\n\nfn keep() {}\n"
+	);
+}
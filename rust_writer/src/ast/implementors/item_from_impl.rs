@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		implementors::{
+			helpers,
+			item_to_impl::{describe_target_impl, impl_matches, impl_preserver},
+			ImplHeader, ImplMatching,
+		},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Preserve, Preserver},
+};
+use syn::{visit_mut::VisitMut, ImplItem, ItemImpl};
+
+/// This implementor removes an element from an `impl block`. It's only meant to be used with
+/// [`Mutator`]: it doesn't implement [`ToFind`](crate::ast::finder::ToFind), as
+/// [`Mutator::ensure`] would skip the removal when the element is found. Look for the element with
+/// [`ItemToImpl`](crate::ast::implementors::ItemToImpl) instead.
+///
+/// The `impl` block is looked up exactly as
+/// [`ItemToImpl`](https://docs.rs/rust_writer/latest/rust_writer/ast/implementors/struct.ItemToImpl.html)
+/// does, and attributes aren't taken into account, this is, if the following is contained in the
+/// target impl block
+///
+/// ```no_compile
+/// /// Some nice docs
+/// #[some_attr]
+/// type Type = ();
+/// ```
+///
+/// and the target item is `type Type = ();`, the item is removed together with its attributes.
+/// Every matching item is removed, and the mutation fails if none is found.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ItemFromImpl<'a> {
+	/// The trait's name lookup. If specified, the implementor will look inside `impl` blocks
+	/// implementing this trait.
+	pub trait_name: Option<&'a str>,
+	/// The type being implemented by the `impl` block.
+	pub implementor_name: &'a str,
+	/// The target item.
+	pub impl_item: ImplItem,
+	/// How `trait_name` and `implementor_name` are matched against the `impl` blocks. Defaults to
+	/// [`ImplMatching::Exact`].
+	pub impl_matching: ImplMatching,
+	/// The exact header of the targeted `impl` blocks. If specified, it's used instead of
+	/// `trait_name` and `implementor_name` to look for the `impl` blocks. Defaults to `None`.
+	pub impl_header: Option<ImplHeader>,
+}
+
+impl<'a> From<(Option<&'a str>, &'a str, ImplItem)> for ItemFromImpl<'a> {
+	fn from(tuple: (Option<&'a str>, &'a str, ImplItem)) -> Self {
+		Self {
+			trait_name: tuple.0,
+			implementor_name: tuple.1,
+			impl_item: tuple.2,
+			impl_matching: ImplMatching::default(),
+			impl_header: None,
+		}
+	}
+}
+
+impl ItemFromImpl<'_> {
+	/// Sets the [`impl_matching`](#structfield.impl_matching) field.
+	pub fn with_impl_matching(mut self, impl_matching: ImplMatching) -> Self {
+		self.impl_matching = impl_matching;
+		self
+	}
+
+	/// Sets the [`impl_header`](#structfield.impl_header) field.
+	pub fn with_impl_header(mut self, impl_header: ImplHeader) -> Self {
+		self.impl_header = Some(impl_header);
+		self
+	}
+}
+
+impl<'a> ToMutate<'a, ItemFromImpl<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a ItemFromImpl<'a>) -> Mutator<'a, ItemFromImpl<'a>, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemFromImpl<'_> {
+	fn describe_failure(&self, _occurrences: usize) -> String {
+		format!(
+			"{} not found in {}",
+			helpers::describe_item(&self.impl_item),
+			describe_target_impl(self.trait_name, self.implementor_name, self.impl_header.as_ref())
+		)
+	}
+}
+
+impl Preserve for ItemFromImpl<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![impl_preserver(
+			self.trait_name,
+			self.implementor_name,
+			self.impl_matching,
+			self.impl_header.as_ref(),
+		)]
+	}
+}
+
+impl<'a> VisitMut for Mutator<'a, ItemFromImpl<'a>, 1> {
+	fn visit_item_impl_mut(&mut self, item_impl: &mut ItemImpl) {
+		if impl_matches(
+			self.mutator.trait_name,
+			self.mutator.implementor_name,
+			self.mutator.impl_matching,
+			self.mutator.impl_header.as_ref(),
+			item_impl,
+		) {
+			let self_impl_item_no_docs =
+				rustilities::parsing::attrs_mut::tt_without_attrs(&self.mutator.impl_item);
+			if helpers::remove_items(&mut item_impl.items, |impl_item| {
+				rustilities::parsing::attrs_mut::tt_without_attrs(impl_item) ==
+					self_impl_item_no_docs
+			}) {
+				self.mutated[0] = true;
+			}
+		}
+	}
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{
+	ast::{
+		finder::{Finder, ToFind},
+		implementors::ItemToImpl,
+	},
+	Error, MutationFailure,
+};
+use syn::parse_quote;
+use test_builder::TestBuilder;
+
+#[test]
+fn item_from_impl_mutate_works() {
+	TestBuilder::default().with_impl_block_ast().execute(|mut builder| {
+		let item_from_impl: ItemFromImpl =
+			(Some("SomeTrait"), "SomeImplementor", parse_quote! { type Type1 = u8; }).into();
+		let item_to_impl: ItemToImpl =
+			(Some("SomeTrait"), "SomeImplementor", parse_quote! { type Type1 = u8; }).into();
+
+		let ast = builder.get_mut_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_impl);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_impl_mutate_works_despite_attrs() {
+	TestBuilder::default().with_impl_block_ast().execute(|mut builder| {
+		let item_from_impl: ItemFromImpl =
+			(Some("SomeTrait"), "SomeImplementor", parse_quote! { fn func_with_attrs(){} }).into();
+		let item_to_impl: ItemToImpl =
+			(Some("SomeTrait"), "SomeImplementor", parse_quote! { fn func_with_attrs(){} }).into();
+
+		let ast = builder.get_mut_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_impl);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_impl_mutate_fails_if_cannot_find_item() {
+	TestBuilder::default().with_impl_block_ast().execute(|mut builder| {
+		let item_from_impl: ItemFromImpl =
+			(None, "SomeImplementor", parse_quote! { type Type1 = u8; }).into();
+
+		let ast = builder.get_mut_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_impl);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`type Type1` not found in impl `SomeImplementor`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_impl_mutate_fails_if_cannot_find_impl_block() {
+	TestBuilder::default().with_impl_block_ast().execute(|mut builder| {
+		let item_from_impl: ItemFromImpl =
+			(Some("SomeTrait"), "OtherImplementor", parse_quote! { type Type1 = u8; }).into();
+
+		let ast = builder.get_mut_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_impl);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`type Type1` not found in impl `SomeTrait for OtherImplementor`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_impl_mutate_with_impl_header_works() {
+	let mut ast: syn::File = parse_quote! {
+		impl frame_system::Config for Runtime {
+			type Balance = u64;
+		}
+		impl pallet_x::Config for Runtime {
+			type Balance = u64;
+		}
+	};
+
+	let item_impl: ItemImpl = parse_quote! { impl pallet_x::Config for Runtime {} };
+	let item_from_impl =
+		ItemFromImpl::from((Some("Config"), "Runtime", parse_quote! { type Balance = u64; }))
+			.with_impl_header(item_impl.into());
+	let mut mutator = Mutator::default().to_mutate(&item_from_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	let expected_ast: syn::File = parse_quote! {
+		impl frame_system::Config for Runtime {
+			type Balance = u64;
+		}
+		impl pallet_x::Config for Runtime {}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_from_impl_mutate_keeps_non_preserved_code() {
+	let code =
+		"impl SomeTrait for SomeImplementor {\n    // Some comment\n    type Type1 = u8;\n\n    \
+	            fn remove() {}\n\n    fn keep() {}\n}\n";
+
+	let item_from_impl: ItemFromImpl =
+		(Some("SomeTrait"), "SomeImplementor", parse_quote! { fn remove() {} }).into();
+	let mut preserver = Preserver::new("impl SomeTrait for SomeImplementor");
+	preserver.add_inners(&["fn remove"]);
+	let mut ast = crate::preserver::preserve_and_parse_str(code, &[&preserver])
+		.expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_from_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		crate::preserver::resolve_preserved_to_string(&ast),
+		"impl SomeTrait for SomeImplementor {\n    // Some comment\n    type Type1 = u8;\n\n    fn \
+		 keep() {}\n}\n"
+	);
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		implementors::helpers,
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use syn::{visit_mut::VisitMut, Item, ItemMod};
+
+/// This implementor removes an item from a module. It's only meant to be used with [`Mutator`]: it
+/// doesn't implement [`ToFind`](crate::ast::finder::ToFind), as [`Mutator::ensure`] would skip the
+/// removal when the item is found. Look for the item with
+/// [`ItemToMod`](crate::ast::implementors::ItemToMod) instead.
+///
+/// It doesn't take attributes into account, this is, if the following is contained in the target
+/// mod
+///
+/// ```no_compile
+/// /// Some nice docs
+/// #[some_attr]
+/// type Type = ();
+/// ```
+///
+/// and the target item is `type Type = ();`, the item is removed together with its attributes.
+/// Every matching item is removed, and the mutation fails if none is found.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ItemFromMod<'a> {
+	/// The module's name.
+	pub mod_name: &'a str,
+	/// The target item.
+	pub item: Item,
+}
+
+impl<'a> From<(&'a str, Item)> for ItemFromMod<'a> {
+	fn from(tuple: (&'a str, Item)) -> Self {
+		Self { mod_name: tuple.0, item: tuple.1 }
+	}
+}
+
+impl<'a> ToMutate<'a, ItemFromMod<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a ItemFromMod<'a>) -> Mutator<'a, ItemFromMod<'a>, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemFromMod<'_> {
+	fn describe_failure(&self, _occurrences: usize) -> String {
+		format!("{} not found in mod `{}`", helpers::describe_item(&self.item), self.mod_name)
+	}
+}
+
+impl Preserve for ItemFromMod<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "mod".into(),
+			name: self.mod_name.into(),
+		})]
+	}
+}
+
+impl<'a> VisitMut for Mutator<'a, ItemFromMod<'a>, 1> {
+	fn visit_item_mod_mut(&mut self, item_mod: &mut ItemMod) {
+		match item_mod.content {
+			Some((_, ref mut items)) if item_mod.ident == self.mutator.mod_name => {
+				let self_item_no_docs =
+					rustilities::parsing::attrs_mut::tt_without_attrs(&self.mutator.item);
+				if helpers::remove_items(items, |item| {
+					rustilities::parsing::attrs_mut::tt_without_attrs(item) == self_item_no_docs
+				}) {
+					self.mutated[0] = true;
+				}
+			},
+			_ => (),
+		}
+	}
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{
+	ast::{
+		finder::{Finder, ToFind},
+		implementors::ItemToMod,
+	},
+	Error, MutationFailure,
+};
+use syn::parse_quote;
+use test_builder::TestBuilder;
+
+#[test]
+fn item_from_mod_mutate_works() {
+	TestBuilder::default().with_mod_ast().execute(|mut builder| {
+		let item_from_mod: ItemFromMod =
+			("SomeMod", parse_quote! { fn some_super_func(&self) -> bool { true } }).into();
+		let item_to_mod: ItemToMod =
+			("SomeMod", parse_quote! { fn some_super_func(&self) -> bool { true } }).into();
+
+		let ast = builder.get_mut_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_mod);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_mod_mutate_works_despite_attrs() {
+	TestBuilder::default().with_mod_ast().execute(|mut builder| {
+		let item_from_mod: ItemFromMod = ("SomeMod", parse_quote! { trait SomeTrait{} }).into();
+		let item_to_mod: ItemToMod = ("SomeMod", parse_quote! { trait SomeTrait{} }).into();
+
+		let ast = builder.get_mut_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_mod);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_mod_mutate_fails_if_cannot_find_item() {
+	TestBuilder::default().with_mod_ast().execute(|mut builder| {
+		let item_from_mod: ItemFromMod =
+			("SomeMod", parse_quote! { fn some_super_func(&self) -> bool { false } }).into();
+
+		let ast = builder.get_mut_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_mod);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`fn some_super_func` not found in mod `SomeMod`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_mod_mutate_fails_if_cannot_find_mod() {
+	TestBuilder::default().with_mod_ast().execute(|mut builder| {
+		let item_from_mod: ItemFromMod = ("OtherMod", parse_quote! { trait SomeTrait{} }).into();
+
+		let ast = builder.get_mut_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_mod);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`trait SomeTrait` not found in mod `OtherMod`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_mod_mutate_keeps_non_preserved_code() {
+	let code =
+		"mod SomeMod {\n    // Some comment\n    use std::fmt;\n\n    /// Some docs\n    fn \
+	            remove() {}\n\n    fn keep() {}\n}\n";
+
+	let item_from_mod: ItemFromMod = ("SomeMod", parse_quote! { fn remove() {} }).into();
+	let mut preserver = Preserver::new("mod SomeMod");
+	preserver.add_inners(&["fn remove"]);
+	let mut ast = crate::preserver::preserve_and_parse_str(code, &[&preserver])
+		.expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_from_mod);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		crate::preserver::resolve_preserved_to_string(&ast),
+		"mod SomeMod {\n    // Some comment\n    use std::fmt;\n\n    fn keep() {}\n}\n"
+	);
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		implementors::helpers,
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use syn::{visit_mut::VisitMut, ItemTrait, TraitItem};
+
+/// This implementor removes an item from a trait definition. It's only meant to be used with
+/// [`Mutator`]: it doesn't implement [`ToFind`](crate::ast::finder::ToFind), as
+/// [`Mutator::ensure`] would skip the removal when the item is found. Look for the item with
+/// [`ItemToTrait`](crate::ast::implementors::ItemToTrait) instead.
+///
+/// It doesn't take attributes into account, this is, if the following is contained in the target
+/// trait
+///
+/// ```no_compile
+/// /// Some nice docs
+/// #[some_attr]
+/// type Type: From<String>;
+/// ```
+///
+/// and the target item is `type Type: From<String>;`, the item is removed together with its
+/// attributes. Every matching item is removed, and the mutation fails if none is found.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ItemFromTrait<'a> {
+	/// The trait's name.
+	pub trait_name: &'a str,
+	/// The target item.
+	pub item_trait: TraitItem,
+}
+
+impl<'a> From<(&'a str, TraitItem)> for ItemFromTrait<'a> {
+	fn from(tuple: (&'a str, TraitItem)) -> Self {
+		Self { trait_name: tuple.0, item_trait: tuple.1 }
+	}
+}
+
+impl<'a> ToMutate<'a, ItemFromTrait<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a ItemFromTrait<'a>) -> Mutator<'a, ItemFromTrait<'a>, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemFromTrait<'_> {
+	fn describe_failure(&self, _occurrences: usize) -> String {
+		format!(
+			"{} not found in trait `{}`",
+			helpers::describe_item(&self.item_trait),
+			self.trait_name
+		)
+	}
+}
+
+impl Preserve for ItemFromTrait<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "trait".into(),
+			name: self.trait_name.into(),
+		})]
+	}
+}
+
+impl<'a> VisitMut for Mutator<'a, ItemFromTrait<'a>, 1> {
+	fn visit_item_trait_mut(&mut self, item_trait: &mut ItemTrait) {
+		if item_trait.ident == self.mutator.trait_name {
+			let self_item_trait_no_docs =
+				rustilities::parsing::attrs_mut::tt_without_attrs(&self.mutator.item_trait);
+			if helpers::remove_items(&mut item_trait.items, |trait_item| {
+				rustilities::parsing::attrs_mut::tt_without_attrs(trait_item) ==
+					self_item_trait_no_docs
+			}) {
+				self.mutated[0] = true;
+			}
+		}
+	}
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{
+	ast::{
+		finder::{Finder, ToFind},
+		implementors::ItemToTrait,
+	},
+	Error, MutationFailure,
+};
+use syn::parse_quote;
+use test_builder::TestBuilder;
+
+#[test]
+fn item_from_trait_mutate_works() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_from_trait: ItemFromTrait =
+			("MyTrait", parse_quote! { type Type1: From<String>; }).into();
+		let item_to_trait: ItemToTrait =
+			("MyTrait", parse_quote! { type Type1: From<String>; }).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_trait);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_trait_mutate_works_despite_attrs() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_from_trait: ItemFromTrait =
+			("MyTrait", parse_quote! { type CommentedType: From<String>; }).into();
+		let item_to_trait: ItemToTrait =
+			("MyTrait", parse_quote! { type CommentedType: From<String>; }).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_trait);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_from_trait_mutate_fails_if_cannot_find_item() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_from_trait: ItemFromTrait = ("MyTrait", parse_quote! { type Type3; }).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_trait);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`type Type3` not found in trait `MyTrait`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_trait_mutate_fails_if_cannot_find_trait() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_from_trait: ItemFromTrait =
+			("OtherTrait", parse_quote! { type Type1: From<String>; }).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_from_trait);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "`type Type1` not found in trait `OtherTrait`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_from_trait_mutate_keeps_non_preserved_code() {
+	let code =
+		"trait MyTrait {\n    // Some comment\n    type Type1;\n\n    fn remove();\n\n    fn \
+	            keep();\n}\n";
+
+	let item_from_trait: ItemFromTrait = ("MyTrait", parse_quote! { fn remove(); }).into();
+	let mut preserver = Preserver::new("trait MyTrait");
+	preserver.add_inners(&["fn remove"]);
+	let mut ast = crate::preserver::preserve_and_parse_str(code, &[&preserver])
+		.expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_from_trait);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		crate::preserver::resolve_preserved_to_string(&ast),
+		"trait MyTrait {\n    // Some comment\n    type Type1;\n\n    fn keep();\n}\n"
+	);
+}
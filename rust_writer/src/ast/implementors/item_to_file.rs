@@ -3,9 +3,16 @@
 #[cfg(test)]
 mod tests;
 
-use crate::ast::{
-	finder::{EmptyFinder, Finder, ToFind},
-	mutator::{EmptyMutator, Mutator, ToMutate},
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{
+			helpers::{self, Identity},
+			AttrsMatching, Insertion, MutationMode,
+		},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
 };
 use syn::{visit::Visit, visit_mut::VisitMut, File, Item};
 
@@ -20,46 +27,126 @@ use syn::{visit::Visit, visit_mut::VisitMut, File, Item};
 /// type Type = ();
 /// ```
 ///
-/// and the target item is `type Type = ();`, the [`find`] method will return true. A major update
-/// will change this in the future, allowing to include attributes in the lookup if needed.
+/// and the target item is `type Type = ();`, the [`find`] method will return true. The
+/// [`attrs_matching`](#structfield.attrs_matching) field allows to include attributes in the
+/// lookup if needed. See [`AttrsMatching`] for further details.
+///
+/// When it's used with [`Mutator`], the [`mode`](#structfield.mode) field defines whether the
+/// target item is appended, or if it replaces an existing item with the same ident and kind. See
+/// [`MutationMode`] for further details.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ItemToFile {
 	pub item: Item,
+	/// How attributes are taken into account when looking for the target item. Defaults to
+	/// [`AttrsMatching::IgnoreAll`].
+	pub attrs_matching: AttrsMatching,
+	/// How the target item is added to the AST. Defaults to [`MutationMode::Insert`].
+	pub mode: MutationMode,
+	/// Where the target item is inserted if it's added as a new item. Defaults to
+	/// [`Insertion::Grouped`] for `use` declarations, so they're placed together with the existing
+	/// imports, and to [`Insertion::End`] otherwise.
+	pub insertion: Insertion,
 }
 
 impl From<Item> for ItemToFile {
 	fn from(item: Item) -> Self {
-		Self { item }
+		let insertion = match item {
+			Item::Use(_) => Insertion::Grouped,
+			_ => Insertion::default(),
+		};
+		Self {
+			item,
+			attrs_matching: AttrsMatching::default(),
+			mode: MutationMode::default(),
+			insertion,
+		}
+	}
+}
+
+impl ItemToFile {
+	/// Sets the [`attrs_matching`](#structfield.attrs_matching) field.
+	pub fn with_attrs_matching(mut self, attrs_matching: AttrsMatching) -> Self {
+		self.attrs_matching = attrs_matching;
+		self
+	}
+
+	/// Sets the [`mode`](#structfield.mode) field.
+	pub fn with_mode(mut self, mode: MutationMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: Insertion) -> Self {
+		self.insertion = insertion;
+		self
 	}
 }
 
 impl<'a> ToFind<'a, ItemToFile, 1> for Finder<'a, EmptyFinder, 1> {
 	fn to_find(self, finder: &'a ItemToFile) -> Finder<'a, ItemToFile, 1> {
-		Finder { found: self.found, finder }
+		Finder::new(finder)
 	}
 }
 
-impl<'a> Visit<'a> for Finder<'a, ItemToFile, 1> {
-	fn visit_file(&mut self, file: &'a File) {
-		let self_item_no_docs =
-			rustilities::parsing::attrs_mut::tt_without_attrs(&self.finder.item);
-		if file.items.iter().any(|item| {
-			rustilities::parsing::attrs_mut::tt_without_attrs(item) == self_item_no_docs
-		}) {
-			self.found[0] = true;
+impl<'ast> Visit<'ast> for Finder<'_, ItemToFile, 1> {
+	fn visit_file(&mut self, file: &'ast File) {
+		let self_item =
+			helpers::without_ignored_attrs(&self.finder.item, self.finder.attrs_matching);
+		for item in &file.items {
+			if helpers::without_ignored_attrs(item, self.finder.attrs_matching) == self_item {
+				self.found[0] = true;
+				self.record_match(item);
+			}
 		}
 	}
 }
 
 impl<'a> ToMutate<'a, ItemToFile, 1> for Mutator<'_, EmptyMutator, 1> {
 	fn to_mutate(self, mutator: &'a ItemToFile) -> Mutator<'a, ItemToFile, 1> {
-		Mutator { mutated: self.mutated, mutator }
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemToFile {
+	fn describe_failure(&self, _occurrences: usize) -> String {
+		format!("cannot add {} to the file", helpers::describe_item(&self.item))
+	}
+}
+
+impl Preserve for ItemToFile {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		let mut lookups = Vec::new();
+		// The item to replace must be in the AST.
+		if let (true, Some((kind, ident))) =
+			(self.mode != MutationMode::Insert, self.item.identity())
+		{
+			lookups.push(Lookup::Item { kind: kind.into(), name: ident.to_string().into() });
+		}
+		// So must the items defining the insertion point.
+		match self.insertion {
+			Insertion::Before(ref anchor) | Insertion::After(ref anchor) =>
+				lookups.push(helpers::item_lookup(None, Some(anchor))),
+			Insertion::Sorted | Insertion::Grouped =>
+				if let Some((kind, _)) = self.item.sort_key() {
+					lookups.push(helpers::item_lookup(Some(kind), None));
+				},
+			Insertion::Start | Insertion::End => (),
+		}
+		lookups.into_iter().map(Preserver::with_lookup).collect()
 	}
 }
 
 impl VisitMut for Mutator<'_, ItemToFile, 1> {
 	fn visit_file_mut(&mut self, file: &mut File) {
-		file.items.push(self.mutator.item.clone());
-		self.mutated[0] = true;
+		if helpers::add_item(
+			&mut file.items,
+			&self.mutator.item,
+			self.mutator.mode,
+			&self.mutator.insertion,
+		) {
+			self.mutated[0] = true;
+		}
 	}
 }
@@ -66,3 +66,133 @@ fn item_to_file_mutate_works_at_end() {
 		assert_eq!(*last_item, new_item);
 	});
 }
+
+#[test]
+fn item_to_file_mutate_with_replace_mode_works() {
+	TestBuilder::default().with_file_ast().execute(|mut builder| {
+		let item: Item = parse_quote! { trait A { fn other_func(&self); } };
+		let item_to_file = ItemToFile::from(item).with_mode(MutationMode::Replace);
+
+		let ast = builder.get_mut_ast_file("file.rs").expect("This exists; qed;");
+		let items_len = ast.items.len();
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_file);
+		assert!(mutator.mutate(ast).is_ok());
+
+		assert_eq!(ast.items.len(), items_len);
+		assert_eq!(ast.items[4], item_to_file.item);
+	});
+}
+
+#[test]
+fn item_to_file_mutate_places_use_items_with_existing_imports() {
+	TestBuilder::default().with_file_ast().execute(|mut builder| {
+		let item: Item = parse_quote! { use std::io; };
+		let item_to_file: ItemToFile = item.into();
+
+		let ast = builder.get_mut_ast_file("file.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_file);
+		assert!(mutator.mutate(ast).is_ok());
+
+		assert_eq!(ast.items[2], item_to_file.item);
+	});
+}
+
+#[test]
+fn item_to_file_finder_with_exact_attrs_matching_works() {
+	TestBuilder::default().with_file_ast().execute(|builder| {
+		let item_with_attrs: Item = parse_quote! {
+			/// Doc
+			#[some_attr]
+			use std::fs;
+		};
+		let item_without_attrs: Item = parse_quote! { use std::fs; };
+
+		let ast = builder.get_ref_ast_file("file.rs").expect("This exists; qed;");
+
+		let item_to_file =
+			ItemToFile::from(item_with_attrs).with_attrs_matching(AttrsMatching::Exact);
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(finder.find(ast));
+
+		let item_to_file =
+			ItemToFile::from(item_without_attrs).with_attrs_matching(AttrsMatching::Exact);
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_to_file_finder_with_ignore_docs_attrs_matching_works() {
+	TestBuilder::default().with_file_ast().execute(|builder| {
+		let item_with_attr: Item = parse_quote! {
+			#[some_attr]
+			use std::fs;
+		};
+		let item_with_other_attr: Item = parse_quote! {
+			/// Doc
+			#[other_attr]
+			use std::fs;
+		};
+
+		let ast = builder.get_ref_ast_file("file.rs").expect("This exists; qed;");
+
+		let item_to_file =
+			ItemToFile::from(item_with_attr).with_attrs_matching(AttrsMatching::IgnoreDocs);
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(finder.find(ast));
+
+		let item_to_file =
+			ItemToFile::from(item_with_other_attr).with_attrs_matching(AttrsMatching::IgnoreDocs);
+		let mut finder = Finder::default().to_find(&item_to_file);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_to_file_preserves_items_needed_by_mutation() {
+	let item: Item = parse_quote! { fn some_func() {} };
+	let item_to_file: ItemToFile = item.clone().into();
+	assert!(item_to_file.preservers().is_empty());
+
+	let item_to_file = ItemToFile::from(item.clone()).with_mode(MutationMode::Upsert);
+	assert_eq!(
+		item_to_file.preservers(),
+		vec![Preserver::with_lookup(Lookup::Item { kind: "fn".into(), name: "some_func".into() })]
+	);
+
+	let item_to_file = ItemToFile::from(item.clone())
+		.with_insertion(Insertion::After(parse_quote! { other_func }));
+	let preservers = item_to_file.preservers();
+	assert_eq!(preservers.len(), 1);
+	assert!(preservers[0].lookup().matches("pub(crate) async fn other_func() {"));
+	assert!(preservers[0].lookup().matches("struct other_func;"));
+	assert!(!preservers[0].lookup().matches("fn other_func_2() {"));
+
+	let item_to_file = ItemToFile::from(item).with_insertion(Insertion::Sorted);
+	let preservers = item_to_file.preservers();
+	assert_eq!(preservers.len(), 1);
+	assert!(preservers[0].lookup().matches("pub const unsafe fn other_func() {"));
+	assert!(!preservers[0].lookup().matches("struct other_func;"));
+}
+
+#[test]
+fn item_to_file_mutate_places_first_use_after_file_head() {
+	let code =
+		"// SPDX-License-Identifier: GPL-3.0\n\n#![no_std]\n\nextern crate alloc;\n\n// Entry \
+	            point\nfn main() {}\n";
+
+	let item: Item = parse_quote! { use alloc::vec::Vec; };
+	let item_to_file: ItemToFile = item.into();
+	let mut ast = crate::preserver::preserve_and_parse_str_for(code, &item_to_file)
+		.expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_to_file);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		crate::preserver::resolve_preserved_to_string(&ast),
+		code.replace("alloc;\n", "alloc;\n\nuse alloc::vec::Vec;\n")
+	);
+}
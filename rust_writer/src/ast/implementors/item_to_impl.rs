@@ -3,11 +3,23 @@
 #[cfg(test)]
 mod tests;
 
-use crate::ast::{
-	finder::{EmptyFinder, Finder, ToFind},
-	mutator::{EmptyMutator, Mutator, ToMutate},
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{
+			helpers::{self, Identity},
+			AttrsMatching, ImplHeader, ImplMatching, Insertion, MutationMode, Occurrence,
+		},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use quote::ToTokens;
+use regex::Regex;
+use syn::{
+	visit::Visit, visit_mut::VisitMut, Attribute, File, ImplItem, Item, ItemImpl, Path,
+	PathSegment, Token, Type,
 };
-use syn::{visit::Visit, visit_mut::VisitMut, ImplItem, ItemImpl, PathSegment};
 
 /// This implementor targets an element inside an `impl block`
 /// When it's used with [`Finder`], it doesn't take attributes into account, this is, if the
@@ -19,9 +31,31 @@ use syn::{visit::Visit, visit_mut::VisitMut, ImplItem, ItemImpl, PathSegment};
 /// type Type = ();
 /// ```
 ///
-/// and the target item is `type Type = ();`, the [`find`] method will return true. A major update
-/// will change this in the future, allowing to include attributes in the lookup if needed.
+/// and the target item is `type Type = ();`, the [`find`] method will return true. The
+/// [`attrs_matching`](#structfield.attrs_matching) field allows to include attributes in the
+/// lookup if needed. See [`AttrsMatching`] for further details.
+///
+/// When it's used with [`Mutator`], the [`mode`](#structfield.mode) field defines whether the
+/// target item is appended, or if it replaces an existing item with the same ident and kind. See
+/// [`MutationMode`] for further details. If several `impl` blocks match the trait and the
+/// implementor, the [`occurrence`](#structfield.occurrence) field defines which of them are
+/// mutated.
+///
+/// By default, an `impl` block is targeted if its trait and its type are named after
+/// [`trait_name`](#structfield.trait_name) and [`implementor_name`](#structfield.implementor_name).
+/// The [`impl_matching`](#structfield.impl_matching) field allows to match any segment of their
+/// paths instead. See [`ImplMatching`] for further details. The
+/// [`impl_header`](#structfield.impl_header) field allows to target the `impl` blocks by their
+/// exact header instead. See [`ImplHeader`] for further details. The
+/// [`container_attrs`](#structfield.container_attrs) field allows to tell apart `impl` blocks by
+/// their attributes, eg a `#[cfg(feature = "std")]` one from the plain one.
+///
+/// If no `impl` block is matched, the mutation fails unless the
+/// [`create_if_missing`](#structfield.create_if_missing) field is specified. In that case, that
+/// `impl` block is created with the target item inside, and placed among the items of the file
+/// following [`container_insertion`](#structfield.container_insertion).
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ItemToImpl<'a> {
 	/// The trait's name lookup. If specified, the implementor will look inside `impl` blocks
 	/// implementing this trait.  
@@ -30,22 +64,165 @@ pub struct ItemToImpl<'a> {
 	pub implementor_name: &'a str,
 	/// The target item.
 	pub impl_item: ImplItem,
+	/// How attributes are taken into account when looking for the target item. Defaults to
+	/// [`AttrsMatching::IgnoreAll`].
+	pub attrs_matching: AttrsMatching,
+	/// How the target item is added to the AST. Defaults to [`MutationMode::Insert`].
+	pub mode: MutationMode,
+	/// Where the target item is inserted if it's added as a new item. Defaults to
+	/// [`Insertion::End`].
+	pub insertion: Insertion,
+	/// Which of the matched `impl` blocks receive the target item. Defaults to
+	/// [`Occurrence::All`].
+	pub occurrence: Occurrence,
+	/// How `trait_name` and `implementor_name` are matched against the `impl` blocks. Defaults to
+	/// [`ImplMatching::Exact`].
+	pub impl_matching: ImplMatching,
+	/// The exact header of the targeted `impl` blocks. If specified, it's used instead of
+	/// `trait_name` and `implementor_name` to look for the `impl` blocks. Defaults to `None`.
+	pub impl_header: Option<ImplHeader>,
+	/// The attributes of the targeted `impl` blocks. If specified, only the `impl` blocks with
+	/// exactly these attributes, doc comments aside, are targeted. Defaults to `None`.
+	pub container_attrs: Option<Vec<Attribute>>,
+	/// The `impl` block created if no `impl` block is matched, eg `impl SomeType {}`. Its header
+	/// is replaced by `impl_header`, or by `trait_name` and `implementor_name` if it doesn't
+	/// match them, and it's given `container_attrs` if they're specified, so it's matched by
+	/// later mutations. The mutation fails if those names aren't a valid type and trait path.
+	/// Defaults to `None`.
+	pub create_if_missing: Option<ItemImpl>,
+	/// Where the `impl` block created from `create_if_missing` is inserted among the items of the
+	/// file. The target item is appended to it. With [`Insertion::End`], the default, it's placed
+	/// just after the definition of its type or the last `impl` block for that type found at the
+	/// root of the file, or at the end of the file if there's none. It's appended to the file if
+	/// the insertion anchor isn't found.
+	pub container_insertion: Insertion,
 }
 
 impl<'a> From<(Option<&'a str>, &'a str, ImplItem)> for ItemToImpl<'a> {
 	fn from(tuple: (Option<&'a str>, &'a str, ImplItem)) -> Self {
-		Self { trait_name: tuple.0, implementor_name: tuple.1, impl_item: tuple.2 }
+		Self {
+			trait_name: tuple.0,
+			implementor_name: tuple.1,
+			impl_item: tuple.2,
+			attrs_matching: AttrsMatching::default(),
+			mode: MutationMode::default(),
+			insertion: Insertion::default(),
+			occurrence: Occurrence::default(),
+			impl_matching: ImplMatching::default(),
+			impl_header: None,
+			container_attrs: None,
+			create_if_missing: None,
+			container_insertion: Insertion::default(),
+		}
 	}
 }
 
-struct PathSegmentFinder<'a> {
-	found: [bool; 2],
-	trait_name: Option<&'a str>,
-	implementor_name: &'a str,
+impl ItemToImpl<'_> {
+	/// Sets the [`attrs_matching`](#structfield.attrs_matching) field.
+	pub fn with_attrs_matching(mut self, attrs_matching: AttrsMatching) -> Self {
+		self.attrs_matching = attrs_matching;
+		self
+	}
+
+	/// Sets the [`mode`](#structfield.mode) field.
+	pub fn with_mode(mut self, mode: MutationMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: Insertion) -> Self {
+		self.insertion = insertion;
+		self
+	}
+
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
+	}
+
+	/// Sets the [`impl_matching`](#structfield.impl_matching) field.
+	pub fn with_impl_matching(mut self, impl_matching: ImplMatching) -> Self {
+		self.impl_matching = impl_matching;
+		self
+	}
+
+	/// Sets the [`impl_header`](#structfield.impl_header) field.
+	pub fn with_impl_header(mut self, impl_header: ImplHeader) -> Self {
+		self.impl_header = Some(impl_header);
+		self
+	}
+
+	/// Sets the [`container_attrs`](#structfield.container_attrs) field.
+	pub fn with_container_attrs(mut self, container_attrs: Vec<Attribute>) -> Self {
+		self.container_attrs = Some(container_attrs);
+		self
+	}
+
+	/// Sets the [`create_if_missing`](#structfield.create_if_missing) field.
+	pub fn with_create_if_missing(mut self, create_if_missing: ItemImpl) -> Self {
+		self.create_if_missing = Some(create_if_missing);
+		self
+	}
+
+	/// Sets the [`container_insertion`](#structfield.container_insertion) field.
+	pub fn with_container_insertion(mut self, container_insertion: Insertion) -> Self {
+		self.container_insertion = container_insertion;
+		self
+	}
+}
+
+impl ItemToImpl<'_> {
+	// The `impl` block created from `create_if_missing`, which is targeted by the implementor. It's
+	// `None` if there's nothing to create or if the names it needs don't parse.
+	fn created_impl(&self) -> Option<ItemImpl> {
+		let mut item_impl = self.create_if_missing.clone()?;
+		let span = item_impl.impl_token.span;
+		if let Some(ref impl_header) = self.impl_header {
+			item_impl.trait_ =
+				impl_header.trait_path.clone().map(|path| (None, path, Token![for](span)));
+			item_impl.self_ty = Box::new(impl_header.self_ty.clone());
+			if let Some(ref generics) = impl_header.generics {
+				item_impl.generics = generics.clone();
+			}
+		} else if !impl_matches(
+			self.trait_name,
+			self.implementor_name,
+			ImplMatching::Exact,
+			None,
+			&item_impl,
+		) {
+			item_impl.self_ty = Box::new(syn::parse_str(self.implementor_name).ok()?);
+			item_impl.trait_ = match self.trait_name {
+				Some(trait_name) =>
+					Some((None, syn::parse_str(trait_name).ok()?, Token![for](span))),
+				None => None,
+			};
+		}
+		helpers::set_container_attrs(&mut item_impl.attrs, self.container_attrs.as_deref());
+		Some(item_impl)
+	}
+
+	fn targets(&self, item_impl: &ItemImpl) -> bool {
+		impl_matches(
+			self.trait_name,
+			self.implementor_name,
+			self.impl_matching,
+			self.impl_header.as_ref(),
+			item_impl,
+		) && helpers::container_attrs_match(&item_impl.attrs, self.container_attrs.as_deref())
+	}
 }
 
-impl<'a> PathSegmentFinder<'a> {
-	fn find_impl_paths(&mut self, item_impl: &'a ItemImpl) {
+pub(super) struct PathSegmentFinder<'a> {
+	pub(super) found: [bool; 2],
+	pub(super) trait_name: Option<&'a str>,
+	pub(super) implementor_name: &'a str,
+}
+
+impl PathSegmentFinder<'_> {
+	pub(super) fn find_impl_paths(&mut self, item_impl: &ItemImpl) {
 		match item_impl.trait_ {
 			Some((_, ref path, _)) => self.visit_path(path),
 			None if self.trait_name.is_none() => self.found[0] = true,
@@ -55,8 +232,8 @@ impl<'a> PathSegmentFinder<'a> {
 	}
 }
 
-impl<'a> Visit<'a> for PathSegmentFinder<'a> {
-	fn visit_path_segment(&mut self, path_segment: &'a PathSegment) {
+impl<'ast> Visit<'ast> for PathSegmentFinder<'_> {
+	fn visit_path_segment(&mut self, path_segment: &'ast PathSegment) {
 		match self.trait_name {
 			Some(trait_name) if path_segment.ident == trait_name => self.found[0] = true,
 			_ => (),
@@ -67,49 +244,220 @@ impl<'a> Visit<'a> for PathSegmentFinder<'a> {
 	}
 }
 
+/// Tells whether `item_impl` is targeted, by its header if `impl_header` is given or by the names
+/// of its trait and type otherwise.
+pub(super) fn impl_matches(
+	trait_name: Option<&str>,
+	implementor_name: &str,
+	impl_matching: ImplMatching,
+	impl_header: Option<&ImplHeader>,
+	item_impl: &ItemImpl,
+) -> bool {
+	match impl_header {
+		Some(impl_header) =>
+			impl_header.trait_path.as_ref() == item_impl.trait_.as_ref().map(|(_, path, _)| path) &&
+				impl_header.self_ty == *item_impl.self_ty &&
+				impl_header
+					.generics
+					.as_ref()
+					.is_none_or(|generics| *generics == item_impl.generics),
+		None if impl_matching == ImplMatching::AnySegment => {
+			let mut path_segment_finder =
+				PathSegmentFinder { found: [false, false], trait_name, implementor_name };
+			path_segment_finder.find_impl_paths(item_impl);
+			path_segment_finder.found.iter().all(|&x| x)
+		},
+		None => {
+			let last_segment_is = |path: &Path, name: &str| {
+				path.segments.last().is_some_and(|segment| segment.ident == name)
+			};
+			let trait_matches = match (trait_name, item_impl.trait_.as_ref()) {
+				(Some(trait_name), Some((_, path, _))) => last_segment_is(path, trait_name),
+				(None, None) => true,
+				_ => false,
+			};
+			trait_matches &&
+				matches!(*item_impl.self_ty, Type::Path(ref type_path)
+					if last_segment_is(&type_path.path, implementor_name))
+		},
+	}
+}
+
+/// Describes the targeted `impl` blocks for error messages, by their header if `impl_header` is
+/// given.
+pub(super) fn describe_target_impl(
+	trait_name: Option<&str>,
+	implementor_name: &str,
+	impl_header: Option<&ImplHeader>,
+) -> String {
+	let Some(impl_header) = impl_header else {
+		return helpers::describe_impl(trait_name, implementor_name);
+	};
+	let to_string = |tokens: &dyn ToTokens| {
+		tokens
+			.to_token_stream()
+			.to_string()
+			.replace(" :: ", "::")
+			.replace(" < ", "<")
+			.replace(" >", ">")
+	};
+	helpers::describe_impl(
+		impl_header.trait_path.as_ref().map(|path| to_string(path)).as_deref(),
+		&to_string(&impl_header.self_ty),
+	)
+}
+
+/// The preserver of the targeted `impl` blocks. If `impl_header` is given, its trait and type are
+/// looked up by the last segment of their paths, so every `impl` block is preserved if its type
+/// isn't a path. The same happens if any segment of the paths may match.
+pub(super) fn impl_preserver<'a>(
+	trait_name: Option<&'a str>,
+	implementor_name: &'a str,
+	impl_matching: ImplMatching,
+	impl_header: Option<&ImplHeader>,
+) -> Preserver<'a> {
+	let any_impl = || {
+		Preserver::with_lookup(Lookup::Regex(
+			Regex::new(r"(?:unsafe\s+)?impl\b").expect("The regex is valid; qed;"),
+		))
+	};
+	let Some(impl_header) = impl_header else {
+		return match impl_matching {
+			ImplMatching::Exact => Preserver::with_lookup(Lookup::Impl {
+				trait_name: trait_name.map(Into::into),
+				implementor_name: implementor_name.into(),
+			}),
+			ImplMatching::AnySegment => any_impl(),
+		};
+	};
+	let last_segment = |path: &Path| path.segments.last().map(|segment| segment.ident.to_string());
+	match impl_header.self_ty {
+		Type::Path(ref type_path) => Preserver::with_lookup(Lookup::Impl {
+			trait_name: impl_header.trait_path.as_ref().and_then(last_segment).map(Into::into),
+			implementor_name: last_segment(&type_path.path).unwrap_or_default().into(),
+		}),
+		_ => any_impl(),
+	}
+}
+
+// Where an `impl` block for `self_ty` is created: just after the last item among the definition
+// of its type and its `impl` blocks, or at the end of `items` if there's none.
+fn impl_creation_index(items: &[Item], self_ty: &Type) -> usize {
+	let Type::Path(ref type_path) = self_ty else {
+		return items.len();
+	};
+	let Some(name) = type_path.path.segments.last().map(|segment| &segment.ident) else {
+		return items.len();
+	};
+	items
+		.iter()
+		.rposition(|item| match item {
+			Item::Impl(item_impl) => matches!(*item_impl.self_ty, Type::Path(ref type_path)
+				if type_path.path.segments.last().is_some_and(|segment| segment.ident == *name)),
+			item => item.identity().is_some_and(|(kind, ident)| {
+				matches!(kind, "struct" | "enum" | "union" | "type") && ident == name
+			}),
+		})
+		.map_or(items.len(), |index| index + 1)
+}
+
 impl<'a> ToFind<'a, ItemToImpl<'a>, 1> for Finder<'a, EmptyFinder, 1> {
 	fn to_find(self, finder: &'a ItemToImpl<'a>) -> Finder<'a, ItemToImpl<'a>, 1> {
-		Finder { found: self.found, finder }
+		Finder::new(finder)
 	}
 }
 
-impl<'a> Visit<'a> for Finder<'a, ItemToImpl<'a>, 1> {
-	fn visit_item_impl(&mut self, item_impl: &'a ItemImpl) {
-		let mut path_segment_finder = PathSegmentFinder {
-			found: [false, false],
-			trait_name: self.finder.trait_name,
-			implementor_name: self.finder.implementor_name,
-		};
-		path_segment_finder.find_impl_paths(item_impl);
-		let self_item_impl_no_docs =
-			rustilities::parsing::attrs_mut::tt_without_attrs(&self.finder.impl_item);
-		if path_segment_finder.found.iter().all(|&x| x) &&
-			item_impl.items.iter().any(|item_impl| {
-				rustilities::parsing::attrs_mut::tt_without_attrs(item_impl) ==
-					self_item_impl_no_docs
-			}) {
-			self.found[0] = true;
+impl<'a, 'ast> Visit<'ast> for Finder<'a, ItemToImpl<'a>, 1> {
+	fn visit_item_impl(&mut self, item_impl: &'ast ItemImpl) {
+		let self_impl_item =
+			helpers::without_ignored_attrs(&self.finder.impl_item, self.finder.attrs_matching);
+		if !self.finder.targets(item_impl) {
+			return;
+		}
+		for impl_item in &item_impl.items {
+			if helpers::without_ignored_attrs(impl_item, self.finder.attrs_matching) ==
+				self_impl_item
+			{
+				self.found[0] = true;
+				self.record_match(impl_item);
+			}
 		}
 	}
 }
 
 impl<'a> ToMutate<'a, ItemToImpl<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
 	fn to_mutate(self, mutator: &'a ItemToImpl<'a>) -> Mutator<'a, ItemToImpl<'a>, 1> {
-		Mutator { mutated: self.mutated, mutator }
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemToImpl<'_> {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		let target_impl =
+			describe_target_impl(self.trait_name, self.implementor_name, self.impl_header.as_ref());
+		if occurrences == 0 && self.create_if_missing.is_some() && self.created_impl().is_none() {
+			return format!(
+				"cannot create {}, its names aren't a valid type and trait",
+				target_impl
+			);
+		}
+		helpers::describe_addition_failure(
+			&target_impl,
+			&helpers::describe_item(&self.impl_item),
+			occurrences,
+		)
+	}
+}
+
+impl Preserve for ItemToImpl<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![impl_preserver(
+			self.trait_name,
+			self.implementor_name,
+			self.impl_matching,
+			self.impl_header.as_ref(),
+		)]
 	}
 }
 
 impl<'a> VisitMut for Mutator<'a, ItemToImpl<'a>, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+		match self.mutator.created_impl() {
+			Some(mut item_impl) if self.occurrences[0] == 0 => {
+				if helpers::add_item(
+					&mut item_impl.items,
+					&self.mutator.impl_item,
+					self.mutator.mode,
+					&Insertion::End,
+				) {
+					if self.mutator.container_insertion == Insertion::End {
+						let index = impl_creation_index(&file.items, &item_impl.self_ty);
+						file.items.insert(index, Item::Impl(item_impl));
+					} else {
+						helpers::insert_container(
+							&mut file.items,
+							Item::Impl(item_impl),
+							&self.mutator.container_insertion,
+						);
+					}
+					self.mutated[0] = true;
+				}
+			},
+			_ => (),
+		}
+	}
+
 	fn visit_item_impl_mut(&mut self, item_impl: &mut ItemImpl) {
-		let mut path_segment_finder = PathSegmentFinder {
-			found: [false, false],
-			trait_name: self.mutator.trait_name,
-			implementor_name: self.mutator.implementor_name,
-		};
-		path_segment_finder.find_impl_paths(item_impl);
-		if path_segment_finder.found.iter().all(|&x| x) {
+		if self.mutator.targets(item_impl) &&
+			helpers::select_occurrence(self, self.mutator.occurrence) &&
+			helpers::add_item(
+				&mut item_impl.items,
+				&self.mutator.impl_item,
+				self.mutator.mode,
+				&self.mutator.insertion,
+			) {
 			self.mutated[0] = true;
-			item_impl.items.push(self.mutator.impl_item.clone());
 		}
 	}
 }
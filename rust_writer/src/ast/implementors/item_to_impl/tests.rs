@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use super::*;
-use crate::Error;
+use crate::{Error, MutationFailure};
 use syn::{parse_quote, Item};
 use test_builder::TestBuilder;
 
@@ -250,8 +250,12 @@ fn item_to_impl_with_trait_mutate_fails_if_cannot_find_impl_block() {
 		let mut mutator = Mutator::default().to_mutate(&item_to_impl_with_trait);
 		assert!(matches!(
 			mutator.mutate(ast),
-			Err(Error::Descriptive(msg))
-			if msg == format!("Cannot mutate using Mutator: {:?}", item_to_impl_with_trait)
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "impl `SomTrait for SomeImplementor` not found".to_owned()
+			}]
 		));
 
 		let mut finder = Finder::default().to_find(&item_to_impl_with_trait);
@@ -357,11 +361,575 @@ fn item_to_impl_without_trait_mutate_fails_if_cannot_find_impl_block() {
 		let mut mutator = Mutator::default().to_mutate(&item_to_impl_without_trait);
 		assert!(matches!(
 			mutator.mutate(ast),
-			Err(Error::Descriptive(msg))
-			if msg == format!("Cannot mutate using Mutator: {:?}", item_to_impl_without_trait)
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "impl `SoeImplementor` not found".to_owned()
+			}]
 		));
 
 		let mut finder = Finder::default().to_find(&item_to_impl_without_trait);
 		assert!(!finder.find(ast));
 	});
 }
+
+#[test]
+fn item_to_impl_mutate_with_upsert_mode_works() {
+	TestBuilder::default().with_impl_block_ast().execute(|mut builder| {
+		let replaced_item = ItemToImpl::from((
+			Some("SomeTrait"),
+			"SomeImplementor",
+			parse_quote! { type Type1 = u128; },
+		))
+		.with_mode(MutationMode::Upsert);
+		let inserted_item = ItemToImpl::from((
+			Some("SomeTrait"),
+			"SomeImplementor",
+			parse_quote! { type Type2 = u128; },
+		))
+		.with_mode(MutationMode::Upsert);
+
+		let ast = builder.get_mut_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&replaced_item);
+		assert!(mutator.mutate(ast).is_ok());
+		let mut mutator = Mutator::default().to_mutate(&inserted_item);
+		assert!(mutator.mutate(ast).is_ok());
+
+		match &ast.items[0] {
+			Item::Impl(item_impl) => {
+				assert_eq!(item_impl.items.len(), 4);
+				assert_eq!(item_impl.items[0], replaced_item.impl_item);
+				assert_eq!(item_impl.items[3], inserted_item.impl_item);
+			},
+			_ => unreachable!("By construction this is an impl block; qed;"),
+		}
+	});
+}
+
+fn some_implementor_items(ast: &syn::File) -> Vec<usize> {
+	ast.items
+		.iter()
+		.filter_map(|item| match item {
+			Item::Impl(item_impl) => Some(item_impl.items.len()),
+			_ => None,
+		})
+		.collect()
+}
+
+#[test]
+fn item_to_impl_mutate_with_occurrence_all_works() {
+	TestBuilder::default().with_multiple_matches_ast().execute(|mut builder| {
+		let item_to_impl: ItemToImpl =
+			(None, "SomeImplementor", parse_quote! { type Type1 = u8; }).into();
+
+		let ast = builder.get_mut_ast_file("multiple_matches.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(mutator.mutate(ast).is_ok());
+		assert_eq!(mutator.occurrences(), [2]);
+
+		assert_eq!(some_implementor_items(ast), vec![2, 2]);
+	});
+}
+
+#[test]
+fn item_to_impl_mutate_with_occurrence_first_works() {
+	TestBuilder::default().with_multiple_matches_ast().execute(|mut builder| {
+		let item_to_impl =
+			ItemToImpl::from((None, "SomeImplementor", parse_quote! { type Type1 = u8; }))
+				.with_occurrence(Occurrence::First);
+
+		let ast = builder.get_mut_ast_file("multiple_matches.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(mutator.mutate(ast).is_ok());
+		assert_eq!(mutator.occurrences(), [2]);
+
+		assert_eq!(some_implementor_items(ast), vec![2, 1]);
+	});
+}
+
+#[test]
+fn item_to_impl_mutate_with_occurrence_nth_works() {
+	TestBuilder::default().with_multiple_matches_ast().execute(|mut builder| {
+		let item_to_impl =
+			ItemToImpl::from((None, "SomeImplementor", parse_quote! { type Type1 = u8; }))
+				.with_occurrence(Occurrence::Nth(1));
+
+		let ast = builder.get_mut_ast_file("multiple_matches.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(mutator.mutate(ast).is_ok());
+
+		assert_eq!(some_implementor_items(ast), vec![1, 2]);
+	});
+}
+
+#[test]
+fn item_to_impl_mutate_with_occurrence_nth_fails_if_not_enough_matches() {
+	TestBuilder::default().with_multiple_matches_ast().execute(|mut builder| {
+		let item_to_impl =
+			ItemToImpl::from((None, "SomeImplementor", parse_quote! { type Type1 = u8; }))
+				.with_occurrence(Occurrence::Nth(2));
+
+		let ast = builder.get_mut_ast_file("multiple_matches.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "cannot add `type Type1` to impl `SomeImplementor`".to_owned()
+			}]
+		));
+		assert_eq!(mutator.occurrences(), [2]);
+
+		assert_eq!(some_implementor_items(ast), vec![1, 1]);
+	});
+}
+
+#[test]
+fn item_to_impl_mutate_with_occurrence_exactly_one_works() {
+	TestBuilder::default().with_impl_block_ast().execute(|mut builder| {
+		let item_to_impl =
+			ItemToImpl::from((None, "SomeImplementor", parse_quote! { type Type1 = u8; }))
+				.with_occurrence(Occurrence::ExactlyOne);
+
+		let ast = builder.get_mut_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(mutator.mutate(ast).is_ok());
+		assert_eq!(mutator.occurrences(), [1]);
+
+		assert_eq!(some_implementor_items(ast), vec![3, 2]);
+	});
+}
+
+#[test]
+fn item_to_impl_mutate_with_occurrence_exactly_one_fails_if_ambiguous() {
+	TestBuilder::default().with_multiple_matches_ast().execute(|mut builder| {
+		let item_to_impl =
+			ItemToImpl::from((None, "SomeImplementor", parse_quote! { type Type1 = u8; }))
+				.with_occurrence(Occurrence::ExactlyOne);
+
+		let ast = builder.get_mut_ast_file("multiple_matches.rs").expect("This exists; qed;");
+		let original_ast = ast.clone();
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "cannot add `type Type1` to impl `SomeImplementor`".to_owned()
+			}]
+		));
+		assert_eq!(mutator.occurrences(), [2]);
+
+		assert_eq!(*ast, original_ast);
+	});
+}
+
+#[test]
+fn item_to_impl_finder_with_exact_attrs_matching_works() {
+	TestBuilder::default().with_impl_block_ast().execute(|builder| {
+		let ast = builder.get_ref_ast_file("impl_block.rs").expect("This exists; qed;");
+
+		let item_to_impl = ItemToImpl::from((
+			Some("SomeTrait"),
+			"SomeImplementor",
+			parse_quote! {
+				/// Doc
+				#[some_attr]
+				fn func_with_attrs(){}
+			},
+		))
+		.with_attrs_matching(AttrsMatching::Exact);
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert!(finder.find(ast));
+
+		let item_to_impl = ItemToImpl::from((
+			Some("SomeTrait"),
+			"SomeImplementor",
+			parse_quote! {
+				#[some_attr]
+				fn func_with_attrs(){}
+			},
+		))
+		.with_attrs_matching(AttrsMatching::Exact);
+		let mut finder = Finder::default().to_find(&item_to_impl);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_to_impl_preserves_impl_block() {
+	let item_to_impl: ItemToImpl =
+		(Some("SomeTrait"), "SomeImplementor", parse_quote! { fn some_func() {} }).into();
+
+	assert_eq!(
+		item_to_impl.preservers(),
+		vec![Preserver::with_lookup(Lookup::Impl {
+			trait_name: Some("SomeTrait".into()),
+			implementor_name: "SomeImplementor".into(),
+		})]
+	);
+}
+
+fn impl_blocks() -> File {
+	parse_quote! {
+		impl From<MyStruct> for Other {}
+		impl frame_system::Config for Runtime {}
+		impl pallet_x::Config for Runtime {}
+		impl<T> SomeTrait for Wrapper<T> where T: Clone {}
+	}
+}
+
+fn impl_header(item_impl: ItemImpl) -> ImplHeader {
+	item_impl.into()
+}
+
+#[test]
+fn impl_matches_compares_header_exactly() {
+	let ast = impl_blocks();
+	let matches = |impl_header: Option<ImplHeader>, trait_name, implementor_name| {
+		ast.items
+			.iter()
+			.map(|item| match item {
+				Item::Impl(item_impl) => impl_matches(
+					trait_name,
+					implementor_name,
+					ImplMatching::Exact,
+					impl_header.as_ref(),
+					item_impl,
+				),
+				_ => unreachable!("By construction these are impl blocks; qed;"),
+			})
+			.collect::<Vec<_>>()
+	};
+
+	assert_eq!(
+		matches(Some(impl_header(parse_quote! { impl From<MyStruct> for Other {} })), None, ""),
+		[true, false, false, false]
+	);
+	assert_eq!(
+		matches(Some(impl_header(parse_quote! { impl From<OtherStruct> for Other {} })), None, ""),
+		[false; 4]
+	);
+	assert_eq!(
+		matches(Some(impl_header(parse_quote! { impl pallet_x::Config for Runtime {} })), None, ""),
+		[false, false, true, false]
+	);
+	assert_eq!(
+		matches(Some(impl_header(parse_quote! { impl Config for Runtime {} })), None, ""),
+		[false; 4]
+	);
+	assert_eq!(
+		matches(
+			Some(impl_header(parse_quote! { impl<T> SomeTrait for Wrapper<T> where T: Clone {} })),
+			None,
+			""
+		),
+		[false, false, false, true]
+	);
+	assert_eq!(
+		matches(
+			Some(impl_header(parse_quote! { impl<T> SomeTrait for Wrapper<T> where T: Copy {} })),
+			None,
+			""
+		),
+		[false; 4]
+	);
+
+	// Generics are ignored if not given.
+	let impl_header = ImplHeader {
+		trait_path: Some(parse_quote! { SomeTrait }),
+		self_ty: parse_quote! { Wrapper<T> },
+		generics: None,
+	};
+	assert_eq!(matches(Some(impl_header), None, ""), [false, false, false, true]);
+}
+
+#[test]
+fn impl_matches_compares_names_exactly_by_default() {
+	let ast: File = parse_quote! {
+		impl Config for Runtime {}
+		impl Runtime for Config {}
+		impl Config for Runtime::Inner {}
+		impl From<Runtime> for Config {}
+		impl<T> Config for Wrapper<T> {}
+	};
+	let matches = |impl_matching, trait_name, implementor_name| {
+		ast.items
+			.iter()
+			.map(|item| match item {
+				Item::Impl(item_impl) =>
+					impl_matches(trait_name, implementor_name, impl_matching, None, item_impl),
+				_ => unreachable!("By construction these are impl blocks; qed;"),
+			})
+			.collect::<Vec<_>>()
+	};
+
+	assert_eq!(
+		matches(ImplMatching::Exact, Some("Config"), "Runtime"),
+		[true, false, false, false, false]
+	);
+	assert_eq!(matches(ImplMatching::Exact, Some("From"), "Runtime"), [false; 5]);
+	assert_eq!(
+		matches(ImplMatching::Exact, Some("Config"), "Wrapper"),
+		[false, false, false, false, true]
+	);
+	assert_eq!(matches(ImplMatching::Exact, Some("Config"), "T"), [false; 5]);
+	assert_eq!(matches(ImplMatching::Exact, None, "Runtime"), [false; 5]);
+
+	// Any segment of the paths matches if opted in.
+	assert_eq!(
+		matches(ImplMatching::AnySegment, Some("Config"), "Runtime"),
+		[true, true, true, false, false]
+	);
+}
+
+#[test]
+fn item_to_impl_mutate_ignores_impl_blocks_only_mentioning_the_names() {
+	let ast: File = parse_quote! {
+		impl Runtime for Config {}
+	};
+	let mut mutated_ast = ast.clone();
+
+	let item_to_impl =
+		ItemToImpl::from((Some("Config"), "Runtime", parse_quote! { type Block = (); }));
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut mutated_ast).is_err());
+	assert_eq!(mutated_ast, ast);
+
+	let item_to_impl = item_to_impl.with_impl_matching(ImplMatching::AnySegment);
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut mutated_ast).is_ok());
+	assert_eq!(
+		mutated_ast,
+		parse_quote! {
+			impl Runtime for Config {
+				type Block = ();
+			}
+		}
+	);
+}
+
+#[test]
+fn item_to_impl_mutate_with_impl_header_works() {
+	let mut ast = impl_blocks();
+
+	let item_to_impl =
+		ItemToImpl::from((Some("Config"), "Runtime", parse_quote! { type Balance = u64; }))
+			.with_impl_header(impl_header(parse_quote! { impl pallet_x::Config for Runtime {} }));
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	let expected_ast: File = parse_quote! {
+		impl From<MyStruct> for Other {}
+		impl frame_system::Config for Runtime {}
+		impl pallet_x::Config for Runtime {
+			type Balance = u64;
+		}
+		impl<T> SomeTrait for Wrapper<T> where T: Clone {}
+	};
+	assert_eq!(ast, expected_ast);
+
+	let mut finder = Finder::default().to_find(&item_to_impl);
+	assert!(finder.find(&ast));
+}
+
+#[test]
+fn item_to_impl_mutate_with_impl_header_fails_if_cannot_find_impl_block() {
+	let mut ast = impl_blocks();
+
+	let item_to_impl =
+		ItemToImpl::from((Some("Config"), "Runtime", parse_quote! { type Balance = u64; }))
+			.with_impl_header(impl_header(parse_quote! { impl pallet_y::Config for Runtime {} }));
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "impl `pallet_y::Config for Runtime` not found"
+	));
+}
+
+#[test]
+fn item_to_impl_preserves_impl_block_by_header() {
+	let item_to_impl = ItemToImpl::from((None, "", parse_quote! { type Balance = u64; }))
+		.with_impl_header(impl_header(parse_quote! { impl<T> pallet_x::Config for Runtime<T> {} }));
+	assert_eq!(
+		item_to_impl.preservers(),
+		vec![Preserver::with_lookup(Lookup::Impl {
+			trait_name: Some("Config".into()),
+			implementor_name: "Runtime".into(),
+		})]
+	);
+
+	let item_to_impl = ItemToImpl::from((None, "", parse_quote! { type Balance = u64; }))
+		.with_impl_header(impl_header(parse_quote! { impl SomeTrait for &str {} }));
+	let preservers = item_to_impl.preservers();
+	assert_eq!(preservers.len(), 1);
+	assert!(preservers[0].lookup().matches("unsafe impl<T> SomeTrait for [T] {"));
+}
+
+#[test]
+fn item_to_impl_mutate_creates_impl_block_if_missing() {
+	let mut ast: File = parse_quote! {
+		struct Foo;
+		impl Display for Foo {}
+		struct Bar;
+	};
+
+	let create = |implementor_name, create_if_missing| {
+		ItemToImpl::from((None, implementor_name, parse_quote! { fn new() -> Self { Self } }))
+			.with_create_if_missing(create_if_missing)
+	};
+
+	let item_to_impl = create("Foo", parse_quote! { impl Foo {} });
+	assert!(Mutator::default().to_mutate(&item_to_impl).mutate(&mut ast).is_ok());
+	let item_to_impl = create("Baz", parse_quote! { impl<T> Baz<T> {} });
+	assert!(Mutator::default().to_mutate(&item_to_impl).mutate(&mut ast).is_ok());
+
+	let expected_ast: File = parse_quote! {
+		struct Foo;
+		impl Display for Foo {}
+		impl Foo {
+			fn new() -> Self { Self }
+		}
+		struct Bar;
+		impl<T> Baz<T> {
+			fn new() -> Self { Self }
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_impl_mutate_doesnt_create_impl_block_if_present() {
+	let mut ast: File = parse_quote! {
+		struct Foo;
+		impl Foo {}
+	};
+
+	let item_to_impl = ItemToImpl::from((None, "Foo", parse_quote! { fn new() -> Self { Self } }))
+		.with_create_if_missing(parse_quote! { impl Foo {} });
+	assert!(Mutator::default().to_mutate(&item_to_impl).mutate(&mut ast).is_ok());
+
+	let expected_ast: File = parse_quote! {
+		struct Foo;
+		impl Foo {
+			fn new() -> Self { Self }
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_impl_ensure_creates_impl_block_with_its_attrs_once() {
+	let mut ast: File = parse_quote! {
+		struct Foo;
+		struct Bar;
+	};
+
+	let item_to_impl = ItemToImpl::from((None, "Foo", parse_quote! { fn new() -> Self { Self } }))
+		.with_container_attrs(vec![parse_quote! { #[cfg(feature = "std")] }])
+		.with_container_insertion(Insertion::Before(parse_quote! { Bar }))
+		.with_create_if_missing(parse_quote! { impl Foo {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.ensure(&mut ast).is_ok());
+	// The created `impl` block has the configured attributes, so it's matched this time.
+	let ensured = mutator.ensure(&mut ast).expect("The impl block is found; qed;");
+	assert!(ensured.applied.is_empty());
+
+	let expected_ast: File = parse_quote! {
+		struct Foo;
+		#[cfg(feature = "std")]
+		impl Foo {
+			fn new() -> Self { Self }
+		}
+		struct Bar;
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_impl_mutate_creates_impl_block_matching_its_header() {
+	let mut ast: File = parse_quote! {
+		struct Foo;
+	};
+
+	let item_to_impl =
+		ItemToImpl::from((Some("Default"), "Foo", parse_quote! { fn default() -> Self { Self } }))
+			.with_create_if_missing(parse_quote! { impl Bar {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.ensure(&mut ast).is_ok());
+	assert!(mutator.ensure(&mut ast).is_ok());
+
+	let item_to_impl = ItemToImpl::from((None, "", parse_quote! { fn len(&self) -> usize { 0 } }))
+		.with_impl_header(impl_header(parse_quote! { impl<T> Len for Vec<T> {} }))
+		.with_create_if_missing(parse_quote! { impl Foo {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.ensure(&mut ast).is_ok());
+	assert!(mutator.ensure(&mut ast).is_ok());
+
+	let expected_ast: File = parse_quote! {
+		struct Foo;
+		impl Default for Foo {
+			fn default() -> Self { Self }
+		}
+		impl<T> Len for Vec<T> {
+			fn len(&self) -> usize { 0 }
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_impl_mutate_fails_if_created_impl_names_dont_parse() {
+	let mut ast: File = parse_quote! {
+		struct Foo;
+	};
+
+	let item_to_impl = ItemToImpl::from((None, "Foo<", parse_quote! { fn new() -> Self { Self } }))
+		.with_create_if_missing(parse_quote! { impl Foo {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description ==
+			"cannot create impl `Foo<`, its names aren't a valid type and trait"
+	));
+	assert_eq!(ast.items.len(), 1);
+}
+
+#[test]
+fn item_to_impl_with_container_attrs_works() {
+	let mut ast: syn::File = parse_quote! {
+		/// Std only.
+		#[cfg(feature = "std")]
+		impl SomeImplementor {
+			fn some_func() {}
+		}
+
+		impl SomeImplementor {}
+	};
+
+	let item_to_impl =
+		ItemToImpl::from((None, "SomeImplementor", parse_quote! { fn some_func() {} }))
+			.with_container_attrs(vec![parse_quote! { #[cfg(feature = "std")] }]);
+	assert!(Finder::default().to_find(&item_to_impl).find(&ast));
+
+	let item_to_impl = item_to_impl.with_container_attrs(Vec::new());
+	assert!(!Finder::default().to_find(&item_to_impl).find(&ast));
+
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+	assert_eq!(mutator.occurrences(), [1]);
+	assert_eq!(some_implementor_items(&ast), vec![1, 1]);
+}
@@ -1,13 +1,17 @@
-// SPDX-License-Identiier: GPL-3.0
+// SPDX-License-Identifier: GPL-3.0
 
 #[cfg(test)]
 mod tests;
 
-use crate::ast::{
-	finder::{EmptyFinder, Finder, ToFind},
-	mutator::{EmptyMutator, Mutator, ToMutate},
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{helpers, AttrsMatching, Insertion, MutationMode, Occurrence},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
 };
-use syn::{visit::Visit, visit_mut::VisitMut, Item, ItemMod};
+use syn::{visit::Visit, visit_mut::VisitMut, Attribute, File, Item, ItemMod};
 
 /// This implementor targets any item inside a module.
 ///
@@ -20,58 +24,255 @@ use syn::{visit::Visit, visit_mut::VisitMut, Item, ItemMod};
 /// type Type = ();
 /// ```
 ///
-/// and the target item is `type Type = ();`, the [`find`] method will return true. A major update
-/// will change this in the future, allowing to include attributes in the lookup if needed.
+/// and the target item is `type Type = ();`, the [`find`] method will return true. The
+/// [`attrs_matching`](#structfield.attrs_matching) field allows to include attributes in the
+/// lookup if needed. See [`AttrsMatching`] for further details.
+///
+/// When it's used with [`Mutator`], the [`mode`](#structfield.mode) field defines whether the
+/// target item is appended, or if it replaces an existing item with the same ident and kind. See
+/// [`MutationMode`] for further details. The [`container_attrs`](#structfield.container_attrs)
+/// field allows to tell apart modules by their attributes, eg a `#[cfg(test)]` one from the
+/// plain one. If the module isn't found, the mutation fails unless the
+/// [`create_if_missing`](#structfield.create_if_missing) field is specified. In that case, that
+/// module is created with the target item inside, and placed in its parent module, or in the file
+/// if the module is at its root, following
+/// [`container_insertion`](#structfield.container_insertion). The mutation still fails if the
+/// parent module isn't found either.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ItemToMod<'a> {
-	/// The module's name.
+	/// The module's path relative to the root of the file, eg `a::b::c` targets the module `c`
+	/// declared inside `b`, itself declared inside `a` at the root of the file. Only the module at
+	/// that exact nesting is targeted, so a plain `c` only targets a module `c` declared at the
+	/// root of the file.
 	pub mod_name: &'a str,
 	/// The target item.
 	pub item: Item,
+	/// How attributes are taken into account when looking for the target item. Defaults to
+	/// [`AttrsMatching::IgnoreAll`].
+	pub attrs_matching: AttrsMatching,
+	/// How the target item is added to the AST. Defaults to [`MutationMode::Insert`].
+	pub mode: MutationMode,
+	/// Where the target item is inserted if it's added as a new item. Defaults to
+	/// [`Insertion::End`].
+	pub insertion: Insertion,
+	/// Which of the modules found at `mod_name` receive the target item. Defaults to
+	/// [`Occurrence::All`].
+	pub occurrence: Occurrence,
+	/// The attributes of the targeted modules. If specified, only the modules with exactly these
+	/// attributes, doc comments aside, are targeted. Defaults to `None`.
+	pub container_attrs: Option<Vec<Attribute>>,
+	/// The module created if no module is found at `mod_name`, eg `mod tests {}`. It's renamed
+	/// after the last segment of `mod_name` and given `container_attrs` if they're specified, so
+	/// it's matched by later mutations. If several parent modules are found, it's only created in
+	/// the first of them. The mutation fails if the last segment of `mod_name` isn't a valid
+	/// ident. Defaults to `None`.
+	pub create_if_missing: Option<ItemMod>,
+	/// Where the module created from `create_if_missing` is inserted among the items of its
+	/// parent. The target item is appended to it. It's appended to its parent if the insertion
+	/// anchor isn't found. Defaults to [`Insertion::End`].
+	pub container_insertion: Insertion,
 }
 
 impl<'a> From<(&'a str, Item)> for ItemToMod<'a> {
 	fn from(tuple: (&'a str, Item)) -> Self {
-		Self { mod_name: tuple.0, item: tuple.1 }
+		Self {
+			mod_name: tuple.0,
+			item: tuple.1,
+			attrs_matching: AttrsMatching::default(),
+			mode: MutationMode::default(),
+			insertion: Insertion::default(),
+			occurrence: Occurrence::default(),
+			container_attrs: None,
+			create_if_missing: None,
+			container_insertion: Insertion::default(),
+		}
+	}
+}
+
+impl ItemToMod<'_> {
+	/// Sets the [`attrs_matching`](#structfield.attrs_matching) field.
+	pub fn with_attrs_matching(mut self, attrs_matching: AttrsMatching) -> Self {
+		self.attrs_matching = attrs_matching;
+		self
+	}
+
+	/// Sets the [`mode`](#structfield.mode) field.
+	pub fn with_mode(mut self, mode: MutationMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: Insertion) -> Self {
+		self.insertion = insertion;
+		self
+	}
+
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
+	}
+
+	/// Sets the [`container_attrs`](#structfield.container_attrs) field.
+	pub fn with_container_attrs(mut self, container_attrs: Vec<Attribute>) -> Self {
+		self.container_attrs = Some(container_attrs);
+		self
+	}
+
+	/// Sets the [`create_if_missing`](#structfield.create_if_missing) field.
+	pub fn with_create_if_missing(mut self, create_if_missing: ItemMod) -> Self {
+		self.create_if_missing = Some(create_if_missing);
+		self
+	}
+
+	/// Sets the [`container_insertion`](#structfield.container_insertion) field.
+	pub fn with_container_insertion(mut self, container_insertion: Insertion) -> Self {
+		self.container_insertion = container_insertion;
+		self
+	}
+}
+
+impl ItemToMod<'_> {
+	fn mod_path(&self) -> Vec<&str> {
+		self.mod_name.split("::").map(str::trim).collect()
+	}
+
+	// The module created from `create_if_missing`, which is targeted by the implementor. It's
+	// `None` if there's nothing to create or if the last segment of `mod_name` isn't a valid ident.
+	fn created_mod(&self) -> Option<ItemMod> {
+		let mut item_mod = self.create_if_missing.clone()?;
+		item_mod.ident = syn::parse_str(self.mod_path().last()?).ok()?;
+		helpers::set_container_attrs(&mut item_mod.attrs, self.container_attrs.as_deref());
+		item_mod.semi = None;
+		Some(item_mod)
 	}
 }
 
 impl<'a> ToFind<'a, ItemToMod<'a>, 1> for Finder<'a, EmptyFinder, 1> {
 	fn to_find(self, finder: &'a ItemToMod<'a>) -> Finder<'a, ItemToMod<'a>, 1> {
-		Finder { found: self.found, finder }
+		Finder::new(finder)
 	}
 }
 
-impl<'a> Visit<'a> for Finder<'a, ItemToMod<'a>, 1> {
-	fn visit_item_mod(&mut self, item_mod: &'a ItemMod) {
-		let self_item_no_docs =
-			rustilities::parsing::attrs_mut::tt_without_attrs(&self.finder.item);
-		match item_mod.content {
-			Some((_, ref items))
-				if item_mod.ident == self.finder.mod_name &&
-					items.iter().any(|item| {
-						rustilities::parsing::attrs_mut::tt_without_attrs(item) == self_item_no_docs
-					}) =>
-				self.found[0] = true,
-			_ => (),
+impl<'a, 'ast> Visit<'ast> for Finder<'a, ItemToMod<'a>, 1> {
+	fn visit_item(&mut self, item: &'ast Item) {
+		// The module path is relative to the root of the file, so only the modules at the root are
+		// visited.
+		if let Item::Mod(item_mod) = item {
+			self.visit_item_mod(item_mod);
+		}
+	}
+
+	fn visit_item_mod(&mut self, item_mod: &'ast ItemMod) {
+		let self_item =
+			helpers::without_ignored_attrs(&self.finder.item, self.finder.attrs_matching);
+		for item in helpers::mod_contents(
+			item_mod,
+			&self.finder.mod_path(),
+			self.finder.container_attrs.as_deref(),
+		)
+		.into_iter()
+		.flatten()
+		{
+			if helpers::without_ignored_attrs(item, self.finder.attrs_matching) == self_item {
+				self.found[0] = true;
+				self.record_match(item);
+			}
 		}
 	}
 }
 
 impl<'a> ToMutate<'a, ItemToMod<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
 	fn to_mutate(self, mutator: &'a ItemToMod<'a>) -> Mutator<'a, ItemToMod<'a>, 1> {
-		Mutator { mutated: self.mutated, mutator }
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemToMod<'_> {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		if occurrences == 0 && self.create_if_missing.is_some() && self.created_mod().is_none() {
+			return format!("cannot create mod `{}`, its name isn't a valid ident", self.mod_name);
+		}
+		helpers::describe_addition_failure(
+			&format!("mod `{}`", self.mod_name),
+			&helpers::describe_item(&self.item),
+			occurrences,
+		)
+	}
+}
+
+impl Preserve for ItemToMod<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		// The module at the root of the file contains the target module.
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "mod".into(),
+			name: self.mod_path()[0].into(),
+		})]
 	}
 }
 
 impl<'a> VisitMut for Mutator<'a, ItemToMod<'a>, 1> {
-	fn visit_item_mod_mut(&mut self, item_mod: &mut ItemMod) {
-		match item_mod.content {
-			Some((_, ref mut items)) if item_mod.ident == self.mutator.mod_name => {
-				self.mutated[0] = true;
-				items.push(self.mutator.item.clone());
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+		match self.mutator.created_mod() {
+			Some(mut item_mod) if self.occurrences[0] == 0 => {
+				let mod_path = self.mutator.mod_path();
+				let parent = match mod_path.split_last() {
+					Some((_, [])) | None => Some(&mut file.items),
+					Some((_, parent_path)) => file.items.iter_mut().find_map(|item| match item {
+						Item::Mod(parent_mod) =>
+							helpers::mod_contents_mut(parent_mod, parent_path, None)
+								.into_iter()
+								.next(),
+						_ => None,
+					}),
+				};
+				let (_, items) = item_mod.content.get_or_insert_with(Default::default);
+				if let Some(parent) = parent {
+					if helpers::add_item(
+						items,
+						&self.mutator.item,
+						self.mutator.mode,
+						&Insertion::End,
+					) {
+						helpers::insert_container(
+							parent,
+							Item::Mod(item_mod),
+							&self.mutator.container_insertion,
+						);
+						self.mutated[0] = true;
+					}
+				}
 			},
 			_ => (),
 		}
 	}
+
+	fn visit_item_mut(&mut self, item: &mut Item) {
+		// The module path is relative to the root of the file, so only the modules at the root are
+		// visited.
+		if let Item::Mod(item_mod) = item {
+			self.visit_item_mod_mut(item_mod);
+		}
+	}
+
+	fn visit_item_mod_mut(&mut self, item_mod: &mut ItemMod) {
+		for items in helpers::mod_contents_mut(
+			item_mod,
+			&self.mutator.mod_path(),
+			self.mutator.container_attrs.as_deref(),
+		) {
+			if helpers::select_occurrence(self, self.mutator.occurrence) &&
+				helpers::add_item(
+					items,
+					&self.mutator.item,
+					self.mutator.mode,
+					&self.mutator.insertion,
+				) {
+				self.mutated[0] = true;
+			}
+		}
+	}
 }
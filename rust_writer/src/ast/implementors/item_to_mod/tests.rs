@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use super::*;
-use crate::Error;
+use crate::{Error, MutationFailure};
 use syn::parse_quote;
 use test_builder::TestBuilder;
 
@@ -89,11 +89,331 @@ fn item_to_mod_mutate_fails_if_cannot_find_mod() {
 		let mut mutator = Mutator::default().to_mutate(&item_to_mod);
 		assert!(matches!(
 			mutator.mutate(ast),
-			Err(Error::Descriptive(msg))
-			if msg == format!("Cannot mutate using Mutator: {:?}", item_to_mod)
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "mod `NonExistingMod` not found".to_owned()
+			}]
 		));
 
 		let mut finder = Finder::default().to_find(&item_to_mod);
 		assert!(!finder.find(ast));
 	});
 }
+
+#[test]
+fn item_to_mod_mutate_with_replace_mode_works() {
+	TestBuilder::default().with_mod_ast().execute(|mut builder| {
+		let item_to_mod = ItemToMod::from(("SomeMod", parse_quote! { enum A { A, B } }))
+			.with_mode(MutationMode::Replace);
+
+		let ast = builder.get_mut_ast_file("mod.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+		assert!(mutator.mutate(ast).is_ok());
+
+		match &ast.items[0] {
+			Item::Mod(ItemMod { content: Some((_, items)), .. }) => {
+				assert_eq!(items.len(), 3);
+				assert_eq!(items[0], item_to_mod.item);
+			},
+			_ => unreachable!("By construction this is an inline mod; qed;"),
+		}
+	});
+}
+
+#[test]
+fn item_to_mod_finder_with_exact_attrs_matching_works() {
+	TestBuilder::default().with_mod_ast().execute(|builder| {
+		let ast = builder.get_ref_ast_file("mod.rs").expect("This exists; qed;");
+
+		let item_to_mod = ItemToMod::from((
+			"SomeMod",
+			parse_quote! { #[doc = " Doc"] #[some_attr] trait SomeTrait{} },
+		))
+		.with_attrs_matching(AttrsMatching::Exact);
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(finder.find(ast));
+
+		let item_to_mod = ItemToMod::from((
+			"SomeMod",
+			parse_quote! { #[some_attr] #[doc = " Doc"] trait SomeTrait{} },
+		))
+		.with_attrs_matching(AttrsMatching::Exact);
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_to_mod_finder_matches_module_path_exactly() {
+	let ast: File = parse_quote! {
+		mod a {
+			mod tests {
+				fn some_func() {}
+			}
+		}
+		mod b {
+			mod tests {}
+		}
+		mod tests {}
+	};
+
+	let finds = |mod_name| {
+		let item_to_mod: ItemToMod = (mod_name, parse_quote! { fn some_func() {} }).into();
+		Finder::default().to_find(&item_to_mod).find(&ast)
+	};
+
+	assert!(finds("a::tests"));
+	assert!(!finds("b::tests"));
+	assert!(!finds("tests"));
+	assert!(!finds("a"));
+}
+
+#[test]
+fn item_to_mod_mutate_targets_module_path() {
+	let mut ast: File = parse_quote! {
+		mod runtime {
+			mod configs {
+				mod xcm {}
+			}
+			mod xcm {}
+		}
+		mod xcm {}
+		fn some_func() {
+			mod runtime {
+				mod configs {
+					mod xcm {}
+				}
+			}
+		}
+	};
+
+	let item_to_mod: ItemToMod = ("runtime::configs::xcm", parse_quote! { type Xcm = (); }).into();
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	let expected_ast: File = parse_quote! {
+		mod runtime {
+			mod configs {
+				mod xcm {
+					type Xcm = ();
+				}
+			}
+			mod xcm {}
+		}
+		mod xcm {}
+		fn some_func() {
+			mod runtime {
+				mod configs {
+					mod xcm {}
+				}
+			}
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_mod_with_container_attrs_works() {
+	let mut ast: File = parse_quote! {
+		mod runtime {
+			mod tests {}
+
+			#[cfg(test)]
+			mod tests {}
+		}
+	};
+
+	let item_to_mod = ItemToMod::from(("runtime::tests", parse_quote! { fn some_test() {} }))
+		.with_container_attrs(vec![parse_quote! { #[cfg(test)] }]);
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	assert!(mutator.mutate(&mut ast).is_ok());
+	assert_eq!(mutator.occurrences(), [1]);
+
+	let expected_ast: File = parse_quote! {
+		mod runtime {
+			mod tests {}
+
+			#[cfg(test)]
+			mod tests {
+				fn some_test() {}
+			}
+		}
+	};
+	assert_eq!(ast, expected_ast);
+	assert!(Finder::default().to_find(&item_to_mod).find(&ast));
+}
+
+#[test]
+fn item_to_mod_mutate_fails_if_cannot_find_module_path() {
+	let mut ast: File = parse_quote! {
+		mod runtime {
+			mod configs {}
+		}
+	};
+
+	let item_to_mod: ItemToMod = ("runtime::xcm", parse_quote! { type Xcm = (); }).into();
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "mod `runtime::xcm` not found"
+	));
+}
+
+#[test]
+fn item_to_mod_mutate_fails_if_module_only_nested() {
+	let mut ast: File = parse_quote! {
+		mod runtime {
+			mod configs {}
+		}
+	};
+
+	// `configs` isn't declared at the root of the file, so its full path is needed.
+	let item_to_mod: ItemToMod = ("configs", parse_quote! { type Xcm = (); }).into();
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "mod `configs` not found"
+	));
+
+	let item_to_mod: ItemToMod = ("runtime::configs", parse_quote! { type Xcm = (); }).into();
+	assert!(Mutator::default().to_mutate(&item_to_mod).mutate(&mut ast).is_ok());
+}
+
+#[test]
+fn item_to_mod_preserves_module_at_file_root() {
+	let item_to_mod: ItemToMod = ("runtime::configs", parse_quote! { type Xcm = (); }).into();
+
+	assert_eq!(
+		item_to_mod.preservers(),
+		vec![Preserver::with_lookup(Lookup::Item { kind: "mod".into(), name: "runtime".into() })]
+	);
+}
+
+#[test]
+fn item_to_mod_mutate_creates_module_if_missing() {
+	let mut ast: File = parse_quote! {
+		mod a {
+			fn some_func() {}
+		}
+	};
+
+	let create = |mod_name| {
+		ItemToMod::from((mod_name, parse_quote! { fn some_test() {} })).with_create_if_missing(
+			parse_quote! {
+				#[cfg(test)]
+				mod tests;
+			},
+		)
+	};
+
+	let item_to_mod = create("tests");
+	assert!(Mutator::default().to_mutate(&item_to_mod).mutate(&mut ast).is_ok());
+	let item_to_mod = create("a::tests");
+	assert!(Mutator::default().to_mutate(&item_to_mod).mutate(&mut ast).is_ok());
+	// The module exists now, so it isn't created again.
+	assert!(Mutator::default().to_mutate(&item_to_mod).mutate(&mut ast).is_ok());
+
+	let expected_ast: File = parse_quote! {
+		mod a {
+			fn some_func() {}
+			#[cfg(test)]
+			mod tests {
+				fn some_test() {}
+				fn some_test() {}
+			}
+		}
+		#[cfg(test)]
+		mod tests {
+			fn some_test() {}
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_mod_ensure_creates_module_with_its_attrs_once() {
+	let mut ast: File = parse_quote! {
+		mod a {
+			fn some_func() {}
+		}
+		mod tests {}
+	};
+
+	let mut ensure = |insertion| {
+		let item_to_mod = ItemToMod::from(("a::tests", parse_quote! { fn some_test() {} }))
+			.with_container_attrs(vec![parse_quote! { #[cfg(test)] }])
+			.with_container_insertion(insertion)
+			.with_create_if_missing(parse_quote! { mod some_mod; });
+		let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+		mutator.ensure(&mut ast).expect("The parent module is found; qed;")
+	};
+
+	assert_eq!(ensure(Insertion::Start).applied, vec![0]);
+	// The created module is named after the last segment of the path and has the configured
+	// attributes, so it's matched this time.
+	assert!(ensure(Insertion::Start).applied.is_empty());
+
+	let item_to_mod = ItemToMod::from(("tests", parse_quote! { fn some_test() {} }))
+		.with_container_attrs(vec![parse_quote! { #[cfg(test)] }])
+		.with_container_insertion(Insertion::Before(parse_quote! { missing }))
+		.with_create_if_missing(parse_quote! { mod tests {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	// The anchor isn't found, so the module is appended.
+	assert!(mutator.ensure(&mut ast).is_ok());
+	assert!(mutator.ensure(&mut ast).expect("The module is found; qed;").applied.is_empty());
+
+	let expected_ast: File = parse_quote! {
+		mod a {
+			#[cfg(test)]
+			mod tests {
+				fn some_test() {}
+			}
+			fn some_func() {}
+		}
+		mod tests {}
+		#[cfg(test)]
+		mod tests {
+			fn some_test() {}
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_mod_mutate_fails_if_created_module_name_isnt_ident() {
+	let mut ast: File = parse_quote! {
+		mod a {}
+	};
+
+	let item_to_mod = ItemToMod::from(("a::type", parse_quote! { fn some_test() {} }))
+		.with_create_if_missing(parse_quote! { mod tests {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "cannot create mod `a::type`, its name isn't a valid ident"
+	));
+	assert_eq!(ast, parse_quote! { mod a {} });
+}
+
+#[test]
+fn item_to_mod_mutate_fails_if_parent_of_missing_module_not_found() {
+	let mut ast: File = parse_quote! {
+		mod a {}
+	};
+
+	let item_to_mod = ItemToMod::from(("b::tests", parse_quote! { fn some_test() {} }))
+		.with_create_if_missing(parse_quote! { mod tests {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "mod `b::tests` not found"
+	));
+	assert_eq!(ast, parse_quote! { mod a {} });
+}
@@ -3,11 +3,15 @@
 #[cfg(test)]
 mod tests;
 
-use crate::ast::{
-	finder::{EmptyFinder, Finder, ToFind},
-	mutator::{EmptyMutator, Mutator, ToMutate},
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{helpers, AttrsMatching, Insertion, MutationMode, Occurrence},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
 };
-use syn::{visit::Visit, visit_mut::VisitMut, ItemTrait, TraitItem};
+use syn::{visit::Visit, visit_mut::VisitMut, Attribute, File, Item, ItemTrait, TraitItem};
 
 /// This implementor target any item inside a trait definition.
 ///
@@ -20,53 +24,213 @@ use syn::{visit::Visit, visit_mut::VisitMut, ItemTrait, TraitItem};
 /// type Type = ();
 /// ```
 ///
-/// and the target item is `type Type = ();`, the [`find`] method will return true. A major update
-/// will change this in the future, allowing to include attributes in the lookup if needed.
+/// and the target item is `type Type = ();`, the [`find`] method will return true. The
+/// [`attrs_matching`](#structfield.attrs_matching) field allows to include attributes in the
+/// lookup if needed. See [`AttrsMatching`] for further details.
+///
+/// When it's used with [`Mutator`], the [`mode`](#structfield.mode) field defines whether the
+/// target item is appended, or if it replaces an existing item with the same ident and kind. See
+/// [`MutationMode`] for further details. The [`container_attrs`](#structfield.container_attrs)
+/// field allows to tell apart traits by their attributes. If the trait isn't found, the mutation
+/// fails unless the [`create_if_missing`](#structfield.create_if_missing) field is specified. In
+/// that case, that trait is created with the target item inside, and placed among the items of the
+/// file following [`container_insertion`](#structfield.container_insertion).
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ItemToTrait<'a> {
 	/// The trait's name.
 	pub trait_name: &'a str,
 	/// The target item.
 	pub item_trait: TraitItem,
+	/// How attributes are taken into account when looking for the target item. Defaults to
+	/// [`AttrsMatching::IgnoreAll`].
+	pub attrs_matching: AttrsMatching,
+	/// How the target item is added to the AST. Defaults to [`MutationMode::Insert`].
+	pub mode: MutationMode,
+	/// Where the target item is inserted if it's added as a new item. Defaults to
+	/// [`Insertion::End`].
+	pub insertion: Insertion,
+	/// Which of the traits named after `trait_name` receive the target item, as several modules
+	/// may define a trait with the same name. Defaults to [`Occurrence::All`].
+	pub occurrence: Occurrence,
+	/// The attributes of the targeted traits. If specified, only the traits with exactly these
+	/// attributes, doc comments aside, are targeted. Defaults to `None`.
+	pub container_attrs: Option<Vec<Attribute>>,
+	/// The trait created if no trait named after `trait_name` is found, eg `pub trait SomeTrait
+	/// {}`. It's renamed after `trait_name` and given `container_attrs` if they're specified, so
+	/// it's matched by later mutations. The mutation fails if `trait_name` isn't a valid ident.
+	/// Defaults to `None`.
+	pub create_if_missing: Option<ItemTrait>,
+	/// Where the trait created from `create_if_missing` is inserted among the items of the file.
+	/// The target item is appended to it. It's appended to the file if the insertion anchor isn't
+	/// found. Defaults to [`Insertion::End`].
+	pub container_insertion: Insertion,
 }
 
 impl<'a> From<(&'a str, TraitItem)> for ItemToTrait<'a> {
 	fn from(tuple: (&'a str, TraitItem)) -> Self {
-		Self { trait_name: tuple.0, item_trait: tuple.1 }
+		Self {
+			trait_name: tuple.0,
+			item_trait: tuple.1,
+			attrs_matching: AttrsMatching::default(),
+			mode: MutationMode::default(),
+			insertion: Insertion::default(),
+			occurrence: Occurrence::default(),
+			container_attrs: None,
+			create_if_missing: None,
+			container_insertion: Insertion::default(),
+		}
+	}
+}
+
+impl ItemToTrait<'_> {
+	/// Sets the [`attrs_matching`](#structfield.attrs_matching) field.
+	pub fn with_attrs_matching(mut self, attrs_matching: AttrsMatching) -> Self {
+		self.attrs_matching = attrs_matching;
+		self
+	}
+
+	/// Sets the [`mode`](#structfield.mode) field.
+	pub fn with_mode(mut self, mode: MutationMode) -> Self {
+		self.mode = mode;
+		self
+	}
+
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: Insertion) -> Self {
+		self.insertion = insertion;
+		self
+	}
+
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
+	}
+
+	/// Sets the [`container_attrs`](#structfield.container_attrs) field.
+	pub fn with_container_attrs(mut self, container_attrs: Vec<Attribute>) -> Self {
+		self.container_attrs = Some(container_attrs);
+		self
+	}
+
+	/// Sets the [`create_if_missing`](#structfield.create_if_missing) field.
+	pub fn with_create_if_missing(mut self, create_if_missing: ItemTrait) -> Self {
+		self.create_if_missing = Some(create_if_missing);
+		self
+	}
+
+	/// Sets the [`container_insertion`](#structfield.container_insertion) field.
+	pub fn with_container_insertion(mut self, container_insertion: Insertion) -> Self {
+		self.container_insertion = container_insertion;
+		self
+	}
+}
+
+impl ItemToTrait<'_> {
+	fn targets(&self, item_trait: &ItemTrait) -> bool {
+		item_trait.ident == self.trait_name &&
+			helpers::container_attrs_match(&item_trait.attrs, self.container_attrs.as_deref())
+	}
+
+	// The trait created from `create_if_missing`, which is targeted by the implementor. It's `None`
+	// if there's nothing to create or if `trait_name` isn't a valid ident.
+	fn created_trait(&self) -> Option<ItemTrait> {
+		let mut item_trait = self.create_if_missing.clone()?;
+		item_trait.ident = syn::parse_str(self.trait_name).ok()?;
+		helpers::set_container_attrs(&mut item_trait.attrs, self.container_attrs.as_deref());
+		Some(item_trait)
 	}
 }
 
 impl<'a> ToFind<'a, ItemToTrait<'a>, 1> for Finder<'a, EmptyFinder, 1> {
 	fn to_find(self, finder: &'a ItemToTrait<'a>) -> Finder<'a, ItemToTrait<'a>, 1> {
-		Finder { found: self.found, finder }
+		Finder::new(finder)
 	}
 }
 
-impl<'a> Visit<'a> for Finder<'a, ItemToTrait<'a>, 1> {
-	fn visit_item_trait(&mut self, item_trait: &'a ItemTrait) {
-		let self_item_trait_no_docs =
-			rustilities::parsing::attrs_mut::tt_without_attrs(&self.finder.item_trait);
-		if item_trait.ident == self.finder.trait_name &&
-			item_trait.items.iter().any(|trait_item| {
-				rustilities::parsing::attrs_mut::tt_without_attrs(trait_item) ==
-					self_item_trait_no_docs
-			}) {
-			self.found[0] = true;
+impl<'a, 'ast> Visit<'ast> for Finder<'a, ItemToTrait<'a>, 1> {
+	fn visit_item_trait(&mut self, item_trait: &'ast ItemTrait) {
+		let self_item_trait =
+			helpers::without_ignored_attrs(&self.finder.item_trait, self.finder.attrs_matching);
+		if !self.finder.targets(item_trait) {
+			return;
+		}
+		for trait_item in &item_trait.items {
+			if helpers::without_ignored_attrs(trait_item, self.finder.attrs_matching) ==
+				self_item_trait
+			{
+				self.found[0] = true;
+				self.record_match(trait_item);
+			}
 		}
 	}
 }
 
 impl<'a> ToMutate<'a, ItemToTrait<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
 	fn to_mutate(self, mutator: &'a ItemToTrait<'a>) -> Mutator<'a, ItemToTrait<'a>, 1> {
-		Mutator { mutated: self.mutated, mutator }
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for ItemToTrait<'_> {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		if occurrences == 0 && self.create_if_missing.is_some() && self.created_trait().is_none() {
+			return format!(
+				"cannot create trait `{}`, its name isn't a valid ident",
+				self.trait_name
+			);
+		}
+		helpers::describe_addition_failure(
+			&format!("trait `{}`", self.trait_name),
+			&helpers::describe_item(&self.item_trait),
+			occurrences,
+		)
+	}
+}
+
+impl Preserve for ItemToTrait<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "trait".into(),
+			name: self.trait_name.into(),
+		})]
 	}
 }
 
 impl<'a> VisitMut for Mutator<'a, ItemToTrait<'a>, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+		match self.mutator.created_trait() {
+			Some(mut item_trait) if self.occurrences[0] == 0 => {
+				if helpers::add_item(
+					&mut item_trait.items,
+					&self.mutator.item_trait,
+					self.mutator.mode,
+					&Insertion::End,
+				) {
+					helpers::insert_container(
+						&mut file.items,
+						Item::Trait(item_trait),
+						&self.mutator.container_insertion,
+					);
+					self.mutated[0] = true;
+				}
+			},
+			_ => (),
+		}
+	}
+
 	fn visit_item_trait_mut(&mut self, item_trait: &mut ItemTrait) {
-		if item_trait.ident == self.mutator.trait_name {
+		if self.mutator.targets(item_trait) &&
+			helpers::select_occurrence(self, self.mutator.occurrence) &&
+			helpers::add_item(
+				&mut item_trait.items,
+				&self.mutator.item_trait,
+				self.mutator.mode,
+				&self.mutator.insertion,
+			) {
 			self.mutated[0] = true;
-			item_trait.items.push(self.mutator.item_trait.clone());
 		}
 	}
 }
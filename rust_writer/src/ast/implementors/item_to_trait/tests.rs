@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use super::*;
-use crate::Error;
+use crate::{Error, MutationFailure};
 use syn::parse_quote;
 use test_builder::TestBuilder;
 
@@ -91,11 +91,221 @@ fn item_to_trait_mutate_fails_if_cannot_find_trait() {
 		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
 		assert!(matches!(
 			mutator.mutate(ast),
-			Err(Error::Descriptive(msg))
-			if msg == format!("Cannot mutate using Mutator: {:?}", item_to_trait)
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "trait `UnexistingTrait` not found".to_owned()
+			}]
 		));
 
 		let mut finder = Finder::default().to_find(&item_to_trait);
 		assert!(!finder.find(ast));
 	});
 }
+
+#[test]
+fn item_to_trait_mutate_with_replace_mode_works() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_to_trait = ItemToTrait::from((
+			"MyTrait",
+			TraitItem::Type(parse_quote! {type CommentedType: From<u8>;}),
+		))
+		.with_mode(MutationMode::Replace);
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(finder.find(ast));
+
+		let expected_item: TraitItem = parse_quote! {
+			#[doc = " Doc"]
+			#[some_attr]
+			type CommentedType: From<u8>;
+		};
+		match &ast.items[1] {
+			syn::Item::Trait(item_trait) => {
+				assert_eq!(item_trait.items.len(), 4);
+				assert_eq!(item_trait.items[2], expected_item);
+			},
+			_ => unreachable!("By construction this is a trait; qed;"),
+		}
+	});
+}
+
+#[test]
+fn item_to_trait_mutate_with_replace_mode_fails_if_cannot_find_item() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_to_trait =
+			ItemToTrait::from(("MyTrait", TraitItem::Type(parse_quote! {type Type3: From<u8>;})))
+				.with_mode(MutationMode::Replace);
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "cannot add `type Type3` to trait `MyTrait`".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn item_to_trait_finder_with_ignore_docs_attrs_matching_works() {
+	TestBuilder::default().with_trait_ast().execute(|builder| {
+		let ast = builder.get_ref_ast_file("trait.rs").expect("This exists; qed;");
+
+		let item_to_trait = ItemToTrait::from((
+			"MyTrait",
+			parse_quote! { #[some_attr] type CommentedType: From<String>; },
+		))
+		.with_attrs_matching(AttrsMatching::IgnoreDocs);
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(finder.find(ast));
+
+		let item_to_trait =
+			ItemToTrait::from(("MyTrait", parse_quote! { type CommentedType: From<String>; }))
+				.with_attrs_matching(AttrsMatching::IgnoreDocs);
+		let mut finder = Finder::default().to_find(&item_to_trait);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn item_to_trait_mutate_creates_trait_if_missing() {
+	let mut ast: syn::File = parse_quote! {
+		fn some_func() {}
+	};
+
+	let item_to_trait = ItemToTrait::from(("MyTrait", parse_quote! { fn some_func(&self); }))
+		.with_create_if_missing(parse_quote! { pub trait MyTrait: Clone {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	let expected_ast: syn::File = parse_quote! {
+		fn some_func() {}
+		pub trait MyTrait: Clone {
+			fn some_func(&self);
+		}
+	};
+	assert_eq!(ast, expected_ast);
+
+	// The trait exists now, so it isn't created again.
+	let mut finder = Finder::default().to_find(&item_to_trait);
+	assert!(finder.find(&ast));
+	assert!(mutator.mutate(&mut ast).is_ok());
+	assert_eq!(ast.items.len(), 2);
+}
+
+#[test]
+fn item_to_trait_ensure_creates_trait_with_its_attrs_once() {
+	let mut ast: syn::File = parse_quote! {
+		fn some_func() {}
+		fn other_func() {}
+	};
+
+	let item_to_trait = ItemToTrait::from(("MyTrait", parse_quote! { fn some_func(&self); }))
+		.with_container_attrs(vec![parse_quote! { #[cfg(feature = "std")] }])
+		.with_container_insertion(Insertion::After(parse_quote! { some_func }))
+		.with_create_if_missing(parse_quote! {
+			/// Some docs.
+			pub trait SomeTrait {}
+		});
+	let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+	assert!(mutator.ensure(&mut ast).is_ok());
+	// The created trait is named after `trait_name` and has the configured attributes, so it's
+	// matched this time.
+	let ensured = mutator.ensure(&mut ast).expect("The trait is found; qed;");
+	assert!(ensured.applied.is_empty());
+
+	let expected_ast: syn::File = parse_quote! {
+		fn some_func() {}
+		/// Some docs.
+		#[cfg(feature = "std")]
+		pub trait MyTrait {
+			fn some_func(&self);
+		}
+		fn other_func() {}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_trait_mutate_places_item_and_created_trait_apart() {
+	let mut ast: syn::File = parse_quote! {
+		fn some_func() {}
+	};
+
+	// The anchor of `insertion` is looked for inside the trait, while the anchor of
+	// `container_insertion` is looked for among the items of the file.
+	let item_to_trait = ItemToTrait::from(("MyTrait", parse_quote! { fn first(&self); }))
+		.with_insertion(Insertion::Before(parse_quote! { second }))
+		.with_container_insertion(Insertion::Start)
+		.with_create_if_missing(parse_quote! { trait MyTrait {} });
+	assert!(Mutator::default().to_mutate(&item_to_trait).mutate(&mut ast).is_ok());
+	let item_to_trait = ItemToTrait::from(("MyTrait", parse_quote! { fn zeroth(&self); }))
+		.with_insertion(Insertion::Before(parse_quote! { first }));
+	assert!(Mutator::default().to_mutate(&item_to_trait).mutate(&mut ast).is_ok());
+
+	let expected_ast: syn::File = parse_quote! {
+		trait MyTrait {
+			fn zeroth(&self);
+			fn first(&self);
+		}
+		fn some_func() {}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn item_to_trait_mutate_fails_if_created_trait_name_isnt_ident() {
+	let mut ast: syn::File = parse_quote! {
+		fn some_func() {}
+	};
+
+	let item_to_trait = ItemToTrait::from(("My Trait", parse_quote! { fn some_func(&self); }))
+		.with_create_if_missing(parse_quote! { trait MyTrait {} });
+	let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "cannot create trait `My Trait`, its name isn't a valid ident"
+	));
+	assert_eq!(ast.items.len(), 1);
+}
+
+#[test]
+fn item_to_trait_with_container_attrs_works() {
+	let mut ast: syn::File = parse_quote! {
+		#[cfg(feature = "std")]
+		trait MyTrait {}
+
+		#[cfg(not(feature = "std"))]
+		trait MyTrait {}
+	};
+
+	let item_to_trait = ItemToTrait::from(("MyTrait", parse_quote! { fn some_func(&self); }))
+		.with_container_attrs(vec![parse_quote! { #[cfg(not(feature = "std"))] }]);
+	let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	let expected_ast: syn::File = parse_quote! {
+		#[cfg(feature = "std")]
+		trait MyTrait {}
+
+		#[cfg(not(feature = "std"))]
+		trait MyTrait {
+			fn some_func(&self);
+		}
+	};
+	assert_eq!(ast, expected_ast);
+	assert!(Finder::default().to_find(&item_to_trait).find(&ast));
+}
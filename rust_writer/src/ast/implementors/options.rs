@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use syn::{Generics, Ident, ItemImpl, Path, Stmt, Type};
+
+/// Defines how an implementor adds its target item to the AST when used with
+/// [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html).
+///
+/// The `Replace` and `Upsert` modes look for an item sharing the target's identity, this is, its
+/// ident and its kind (`fn`, `type`, `const`,...). Items without an ident, such as `use`
+/// declarations or `impl` blocks, have no identity, so they're never replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MutationMode {
+	/// The target item is appended to its container.
+	#[default]
+	Insert,
+	/// The item sharing the target's identity is swapped in place by the target item, keeping its
+	/// attributes and position. The mutation fails if there's no such item.
+	Replace,
+	/// Same as `Replace`, but the target item is appended if there's no item to replace.
+	Upsert,
+}
+
+/// Defines where an implementor inserts its target item inside its container when used with
+/// [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html).
+///
+/// Items replaced in place by [`MutationMode::Replace`] or [`MutationMode::Upsert`] keep their
+/// position, so the insertion policy only applies to new items.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Insertion {
+	/// The target item is appended to its container.
+	#[default]
+	End,
+	/// The target item is inserted as the first item of its container.
+	Start,
+	/// The target item is inserted just before the first item named after the given ident. The
+	/// mutation fails if there's no such item.
+	Before(Ident),
+	/// The target item is inserted just after the first item named after the given ident. The
+	/// mutation fails if there's no such item.
+	After(Ident),
+	/// The target item is inserted among the items of its same kind keeping their alphabetical
+	/// order. `use` declarations are sorted by their path. If there isn't any item of the same
+	/// kind, the target item is placed as in `Grouped`.
+	Sorted,
+	/// The target item is inserted just after the last item of its same kind. This is useful to
+	/// keep `use` declarations together. If there isn't any item of the same kind, `use`
+	/// declarations are inserted after the leading `extern crate` and `mod` declarations of the
+	/// container, while any other item is appended to it.
+	Grouped,
+}
+
+/// Defines where [`StmtToFn`](crate::ast::implementors::StmtToFn) inserts its target statement
+/// inside the body of the function when used with
+/// [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StmtInsertion {
+	/// The target statement is appended to the body, just before its tail expression if any, so
+	/// the value returned by the function doesn't change. A trailing `for` or `while` loop or `if`
+	/// without `else` is never a tail expression, as its value is always `()`. In functions
+	/// returning `()`, neither are other block-like expressions such as `match` or `if`-`else`.
+	#[default]
+	End,
+	/// The target statement is inserted as the first statement of the body.
+	Start,
+	/// The target statement is inserted just before the first statement equal to the given one.
+	/// The mutation fails if there's no such statement.
+	Before(Stmt),
+	/// The target statement is inserted just after the first statement equal to the given one.
+	/// The mutation fails if there's no such statement.
+	After(Stmt),
+}
+
+/// Defines which of the containers matched by an implementor receive its target item when used
+/// with [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html).
+/// For instance, a file may contain several `impl Foo` blocks or several invocations of the same
+/// macro.
+///
+/// The number of matched containers is reported back in the `occurrences` array of the `Mutator`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Occurrence {
+	/// The target item is added to every matched container.
+	#[default]
+	All,
+	/// The target item is only added to the first matched container.
+	First,
+	/// The target item is only added to the matched container at the given 0-based index. The
+	/// mutation fails if there are fewer matches.
+	Nth(usize),
+	/// The target item is added to the matched container if it's the only one. The mutation fails
+	/// leaving the AST untouched if there are several matches.
+	ExactlyOne,
+}
+
+/// Defines how attributes are taken into account when an implementor looks for its target item
+/// using [`Finder`](https://docs.rs/rust_writer/latest/rust_writer/ast/finder/struct.Finder.html).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttrsMatching {
+	/// The attributes of the target item must be exactly the attributes of the found item, in the
+	/// same order. Doc comments are compared by their content, so `/// Doc` and `#[doc = " Doc"]`
+	/// are deemed equal.
+	Exact,
+	/// Doc comments are ignored, but any other attribute must be exactly the same.
+	IgnoreDocs,
+	/// Attributes are ignored.
+	#[default]
+	IgnoreAll,
+}
+
+/// Defines how an implementor matches the `impl` blocks by the names of their trait and type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImplMatching {
+	/// The implemented trait and the type must be named after the given names, this is, the last
+	/// segment of their paths is compared, so `MyStruct` matches `impl MyStruct`,
+	/// `impl<T> MyStruct<T>` and `impl crate::MyStruct`, but neither `impl From<MyStruct> for
+	/// Other` nor `impl Trait for Box<MyStruct>`.
+	#[default]
+	Exact,
+	/// Both names are looked up among the segments of the trait path and of the type path, so
+	/// eg `Config` and `Runtime` also match `impl Runtime for Config` and
+	/// `impl Config for Runtime::Inner`. Generic arguments aren't taken into account.
+	AnySegment,
+}
+
+/// Defines the header of the `impl` blocks targeted by an implementor, which is compared
+/// structurally against the header of the `impl` blocks found in the AST. Unlike looking them up
+/// by the trait and implementor names, this tells apart eg `frame_system::Config` from
+/// `pallet_x::Config`, or `From<u8>` from `From<u16>`.
+///
+/// It can be built out of an `impl` block, whose items are ignored:
+///
+/// ```rust
+/// use rust_writer::ast::implementors::ImplHeader;
+/// use syn::{parse_quote, ItemImpl};
+///
+/// let item_impl: ItemImpl = parse_quote! {
+///     impl<T> pallet_x::Config for Runtime<T> where T: Get<u32> {}
+/// };
+/// let impl_header = ImplHeader::from(item_impl);
+///
+/// assert_eq!(impl_header.trait_path, Some(parse_quote! { pallet_x::Config }));
+/// assert_eq!(impl_header.self_ty, parse_quote! { Runtime<T> });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplHeader {
+	/// The full path of the implemented trait, or `None` to target inherent `impl` blocks.
+	pub trait_path: Option<Path>,
+	/// The type being implemented.
+	pub self_ty: Type,
+	/// The generics of the `impl` block, including its where clause. If `None`, the generics of
+	/// the `impl` blocks aren't taken into account.
+	pub generics: Option<Generics>,
+}
+
+impl From<ItemImpl> for ImplHeader {
+	fn from(item_impl: ItemImpl) -> Self {
+		Self {
+			trait_path: item_impl.trait_.map(|(_, path, _)| path),
+			self_ty: *item_impl.self_ty,
+			generics: Some(item_impl.generics),
+		}
+	}
+}
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{helpers, Occurrence, StmtInsertion},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use regex::Regex;
+use syn::{
+	visit::Visit, visit_mut::VisitMut, Block, Expr, ExprIf, File, Ident, ImplItemFn, ItemFn,
+	ReturnType, Signature, Stmt, TraitItemFn,
+};
+
+/// This implementor targets a statement inside the body of a function, which may be a free
+/// function, a method inside an `impl` block or a default method inside a trait definition.
+///
+/// When it's used with [`Finder`], only the statements at the top level of the body are taken
+/// into account, this is, if the target function is
+///
+/// ```no_compile
+/// fn main() {
+///     let app = App::new();
+///     if cfg!(debug_assertions) {
+///         app.add_plugin(DebugPlugin);
+///     }
+///     app.run();
+/// }
+/// ```
+///
+/// the [`find`](Finder::find) method will return true for `app.run();`, but false for
+/// `app.add_plugin(DebugPlugin);`.
+///
+/// When it's used with [`Mutator`], the [`insertion`](#structfield.insertion) field defines where
+/// the target statement is inserted inside the body. See [`StmtInsertion`] for further details.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct StmtToFn<'a> {
+	/// The function's name.
+	pub fn_name: &'a str,
+	/// The target statement.
+	pub stmt: Stmt,
+	/// Where the target statement is inserted. Defaults to [`StmtInsertion::End`].
+	pub insertion: StmtInsertion,
+	/// Which of the functions named after `fn_name` receive the target statement. Defaults to
+	/// [`Occurrence::All`].
+	pub occurrence: Occurrence,
+}
+
+impl<'a> From<(&'a str, Stmt)> for StmtToFn<'a> {
+	fn from(tuple: (&'a str, Stmt)) -> Self {
+		Self {
+			fn_name: tuple.0,
+			stmt: tuple.1,
+			insertion: StmtInsertion::default(),
+			occurrence: Occurrence::default(),
+		}
+	}
+}
+
+impl StmtToFn<'_> {
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: StmtInsertion) -> Self {
+		self.insertion = insertion;
+		self
+	}
+
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
+	}
+}
+
+// The index where a new statement is inserted following `insertion`, if any.
+fn insertion_index(sig: &Signature, stmts: &[Stmt], insertion: &StmtInsertion) -> Option<usize> {
+	match insertion {
+		StmtInsertion::Start => Some(0),
+		StmtInsertion::End => match stmts.last() {
+			Some(Stmt::Expr(expr, None)) if is_tail(sig, expr) => Some(stmts.len() - 1),
+			_ => Some(stmts.len()),
+		},
+		StmtInsertion::Before(anchor) => stmts.iter().position(|stmt| stmt == anchor),
+		StmtInsertion::After(anchor) =>
+			stmts.iter().position(|stmt| stmt == anchor).map(|index| index + 1),
+	}
+}
+
+// Whether the last expression of a body, which isn't followed by a semicolon, is its tail. This is
+// the case if the function returns its value, unless it's a `for` or `while` loop or an `if`
+// without `else`, which always evaluate to `()`. If the function returns `()`, only expressions
+// that cannot be followed by another statement without a semicolon are tails, eg `app.run()`,
+// while block-like expressions such as `if` or `match` are just the last statement of the body.
+fn is_tail(sig: &Signature, expr: &Expr) -> bool {
+	match expr {
+		Expr::ForLoop(_) | Expr::While(_) | Expr::If(ExprIf { else_branch: None, .. }) => false,
+		_ if !matches!(sig.output, ReturnType::Default) => true,
+		Expr::Block(_) |
+		Expr::Const(_) |
+		Expr::If(_) |
+		Expr::Loop(_) |
+		Expr::Match(_) |
+		Expr::TryBlock(_) |
+		Expr::Unsafe(_) => false,
+		_ => true,
+	}
+}
+
+impl<'a> ToFind<'a, StmtToFn<'a>, 1> for Finder<'a, EmptyFinder, 1> {
+	fn to_find(self, finder: &'a StmtToFn<'a>) -> Finder<'a, StmtToFn<'a>, 1> {
+		Finder::new(finder)
+	}
+}
+
+impl Finder<'_, StmtToFn<'_>, 1> {
+	fn find_stmt(&mut self, ident: &Ident, block: &Block) {
+		if ident != self.finder.fn_name {
+			return;
+		}
+		for stmt in &block.stmts {
+			if *stmt == self.finder.stmt {
+				self.found[0] = true;
+				self.record_match(stmt);
+			}
+		}
+	}
+}
+
+impl<'a, 'ast> Visit<'ast> for Finder<'a, StmtToFn<'a>, 1> {
+	fn visit_item_fn(&mut self, item_fn: &'ast ItemFn) {
+		self.find_stmt(&item_fn.sig.ident, &item_fn.block);
+	}
+
+	fn visit_impl_item_fn(&mut self, impl_item_fn: &'ast ImplItemFn) {
+		self.find_stmt(&impl_item_fn.sig.ident, &impl_item_fn.block);
+	}
+
+	fn visit_trait_item_fn(&mut self, trait_item_fn: &'ast TraitItemFn) {
+		if let Some(ref block) = trait_item_fn.default {
+			self.find_stmt(&trait_item_fn.sig.ident, block);
+		}
+	}
+}
+
+impl<'a> ToMutate<'a, StmtToFn<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a StmtToFn<'a>) -> Mutator<'a, StmtToFn<'a>, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for StmtToFn<'_> {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		helpers::describe_addition_failure(
+			&format!("fn `{}`", self.fn_name),
+			"the statement",
+			occurrences,
+		)
+	}
+}
+
+impl Preserve for StmtToFn<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		let fn_lookup = Lookup::Item { kind: "fn".into(), name: self.fn_name.into() };
+		// Methods may be inside any `impl` block or trait definition.
+		let mut impl_preserver = Preserver::with_lookup(Lookup::Regex(
+			Regex::new(r"(?:unsafe\s+)?impl\b").expect("The regex is valid; qed;"),
+		));
+		impl_preserver.add_inner_lookups(vec![fn_lookup.clone()]);
+		let mut trait_preserver = Preserver::with_lookup(helpers::item_lookup(Some("trait"), None));
+		trait_preserver.add_inner_lookups(vec![fn_lookup.clone()]);
+		vec![Preserver::with_lookup(fn_lookup), impl_preserver, trait_preserver]
+	}
+}
+
+impl Mutator<'_, StmtToFn<'_>, 1> {
+	fn add_stmt(&mut self, sig: &Signature, block: &mut Block) {
+		if sig.ident != self.mutator.fn_name ||
+			!helpers::select_occurrence(self, self.mutator.occurrence)
+		{
+			return;
+		}
+		if let Some(index) = insertion_index(sig, &block.stmts, &self.mutator.insertion) {
+			block.stmts.insert(index, self.mutator.stmt.clone());
+			self.mutated[0] = true;
+		}
+	}
+}
+
+impl<'a> VisitMut for Mutator<'a, StmtToFn<'a>, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+	}
+
+	fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+		self.add_stmt(&item_fn.sig, &mut item_fn.block);
+	}
+
+	fn visit_impl_item_fn_mut(&mut self, impl_item_fn: &mut ImplItemFn) {
+		self.add_stmt(&impl_item_fn.sig, &mut impl_item_fn.block);
+	}
+
+	fn visit_trait_item_fn_mut(&mut self, trait_item_fn: &mut TraitItemFn) {
+		if let Some(ref mut block) = trait_item_fn.default {
+			self.add_stmt(&trait_item_fn.sig, block);
+		}
+	}
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{
+	preserver::{preserve_and_parse_str_for, resolve_preserved_to_string},
+	Error,
+};
+use syn::{parse_quote, Item};
+
+fn ast() -> File {
+	parse_quote! {
+		fn main() {
+			let app = App::new();
+			if cfg!(debug_assertions) {
+				app.add_plugin(DebugPlugin);
+			}
+			app.run();
+		}
+
+		impl Builder {
+			fn build(self) -> App {
+				let app = App::new();
+				app
+			}
+		}
+
+		trait Plugin {
+			fn name(&self) -> &str;
+			fn build(&self, app: &mut App) {
+				app.add_system(system);
+			}
+		}
+	}
+}
+
+fn mutated(stmt_to_fn: &StmtToFn) -> File {
+	let mut ast = ast();
+	let mut mutator = Mutator::default().to_mutate(stmt_to_fn);
+	assert!(mutator.mutate(&mut ast).is_ok());
+	ast
+}
+
+#[test]
+fn stmt_to_fn_finder_finds_stmt_in_free_fn() {
+	let ast = ast();
+
+	let stmt_to_fn: StmtToFn = ("main", parse_quote! { app.run(); }).into();
+	assert!(Finder::default().to_find(&stmt_to_fn).find(&ast));
+
+	// Only top level statements are found.
+	let stmt_to_fn: StmtToFn = ("main", parse_quote! { app.add_plugin(DebugPlugin); }).into();
+	assert!(!Finder::default().to_find(&stmt_to_fn).find(&ast));
+
+	let stmt_to_fn: StmtToFn = ("other", parse_quote! { app.run(); }).into();
+	assert!(!Finder::default().to_find(&stmt_to_fn).find(&ast));
+}
+
+#[test]
+fn stmt_to_fn_finder_finds_stmt_in_methods() {
+	let ast = ast();
+
+	let stmt_to_fn: StmtToFn = ("build", parse_quote! { let app = App::new(); }).into();
+	assert!(Finder::default().to_find(&stmt_to_fn).find(&ast));
+
+	let stmt_to_fn: StmtToFn = ("build", parse_quote! { app.add_system(system); }).into();
+	assert!(Finder::default().to_find(&stmt_to_fn).find(&ast));
+}
+
+#[test]
+fn stmt_to_fn_mutate_inserts_stmt_before_tail_expr() {
+	let stmt_to_fn: StmtToFn = ("build", parse_quote! { app.add_plugin(SomePlugin); }).into();
+
+	let expected_ast: File = parse_quote! {
+		fn main() {
+			let app = App::new();
+			if cfg!(debug_assertions) {
+				app.add_plugin(DebugPlugin);
+			}
+			app.run();
+		}
+
+		impl Builder {
+			fn build(self) -> App {
+				let app = App::new();
+				app.add_plugin(SomePlugin);
+				app
+			}
+		}
+
+		trait Plugin {
+			fn name(&self) -> &str;
+			fn build(&self, app: &mut App) {
+				app.add_system(system);
+				app.add_plugin(SomePlugin);
+			}
+		}
+	};
+	assert_eq!(mutated(&stmt_to_fn), expected_ast);
+}
+
+#[test]
+fn stmt_to_fn_mutate_appends_stmt_after_unit_exprs() {
+	let mut ast: File = parse_quote! {
+		fn main() {
+			let app = App::new();
+			for plugin in plugins() {
+				app.add_plugin(plugin);
+			}
+		}
+
+		fn run(app: App) {
+			if app.is_ready() {
+				app.run();
+			}
+		}
+
+		fn stop(app: App) {
+			match app.state() {
+				State::Running => app.stop(),
+				_ => (),
+			}
+		}
+
+		fn state(app: &App) -> State {
+			match app.state() {
+				State::Running => State::Stopping,
+				state => state,
+			}
+		}
+	};
+	for fn_name in ["main", "run", "stop", "state"] {
+		let stmt_to_fn: StmtToFn = (fn_name, parse_quote! { log(); }).into();
+		let mut mutator = Mutator::default().to_mutate(&stmt_to_fn);
+		assert!(mutator.mutate(&mut ast).is_ok());
+	}
+
+	let expected_ast: File = parse_quote! {
+		fn main() {
+			let app = App::new();
+			for plugin in plugins() {
+				app.add_plugin(plugin);
+			}
+			log();
+		}
+
+		fn run(app: App) {
+			if app.is_ready() {
+				app.run();
+			}
+			log();
+		}
+
+		fn stop(app: App) {
+			match app.state() {
+				State::Running => app.stop(),
+				_ => (),
+			}
+			log();
+		}
+
+		fn state(app: &App) -> State {
+			log();
+			match app.state() {
+				State::Running => State::Stopping,
+				state => state,
+			}
+		}
+	};
+	assert_eq!(ast, expected_ast);
+}
+
+#[test]
+fn stmt_to_fn_mutate_with_insertion_works() {
+	let stmt: Stmt = parse_quote! { app.add_plugin(SomePlugin); };
+	let mutated_main = |insertion| {
+		let stmt_to_fn = StmtToFn { insertion, ..("main", stmt.clone()).into() };
+		match mutated(&stmt_to_fn).items[0] {
+			Item::Fn(ref item_fn) => item_fn.block.stmts.clone(),
+			_ => unreachable!("By construction this is fn main; qed;"),
+		}
+	};
+
+	let stmts = mutated_main(StmtInsertion::Start);
+	assert_eq!(stmts[0], stmt);
+	let stmts = mutated_main(StmtInsertion::End);
+	assert_eq!(stmts[3], stmt);
+	let stmts = mutated_main(StmtInsertion::Before(parse_quote! { app.run(); }));
+	assert_eq!(stmts[2], stmt);
+	let stmts = mutated_main(StmtInsertion::After(parse_quote! { let app = App::new(); }));
+	assert_eq!(stmts[1], stmt);
+}
+
+#[test]
+fn stmt_to_fn_mutate_fails_if_anchor_not_found() {
+	let mut ast = ast();
+
+	let stmt_to_fn = StmtToFn::from(("main", parse_quote! { app.add_plugin(SomePlugin); }))
+		.with_insertion(StmtInsertion::Before(parse_quote! { app.stop(); }));
+	let mut mutator = Mutator::default().to_mutate(&stmt_to_fn);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "cannot add the statement to fn `main`"
+	));
+	assert_eq!(ast, self::ast());
+}
+
+#[test]
+fn stmt_to_fn_mutate_fails_if_fn_not_found() {
+	let mut ast = ast();
+
+	let stmt_to_fn: StmtToFn = ("name", parse_quote! { app.add_plugin(SomePlugin); }).into();
+	let mut mutator = Mutator::default().to_mutate(&stmt_to_fn);
+	assert!(matches!(
+		mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures)) if failures[0].description == "fn `name` not found"
+	));
+}
+
+#[test]
+fn stmt_to_fn_preserves_fn_bodies() {
+	let code =
+		"// Some comment\nfn main() {\n    // Create the app\n    let app = App::new();\n\n    \
+	            app.run();\n}\n\nimpl Builder {\n    // Builds the app\n    fn build(self) -> App \
+	            {\n        // Create the app\n        App::new()\n    }\n}\n";
+
+	let stmt_to_fn: StmtToFn = ("build", parse_quote! { let app = App::new(); }).into();
+	let mut ast = preserve_and_parse_str_for(code, &stmt_to_fn).expect("This should be Ok; qed;");
+
+	let stmt_to_fn =
+		StmtToFn::from(("build", parse_quote! { init(); })).with_insertion(StmtInsertion::Start);
+	let mut mutator = Mutator::default().to_mutate(&stmt_to_fn);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		resolve_preserved_to_string(&ast),
+		code.replace("-> App {\n", "-> App {\n        init();\n")
+	);
+}
@@ -3,12 +3,18 @@
 #[cfg(test)]
 mod tests;
 
-use crate::ast::{
-	finder::{EmptyFinder, Finder, ToFind},
-	mutator::{EmptyMutator, Mutator, ToMutate},
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{helpers, Occurrence},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
 };
 use proc_macro2::{Group, TokenStream, TokenTree};
-use syn::{visit::Visit, visit_mut::VisitMut, Ident, Macro, Path};
+use quote::ToTokens;
+use regex::Regex;
+use syn::{visit::Visit, visit_mut::VisitMut, File, Ident, Macro, Path};
 
 /// This implementor targets any [`TokenStream`](https://docs.rs/proc-macro2/latest/proc_macro2/struct.TokenStream.html)
 /// inside a declarative macro.
@@ -19,7 +25,18 @@ use syn::{visit::Visit, visit_mut::VisitMut, Ident, Macro, Path};
 /// details such as spans or spacing are ignored by this implementor. Have a look at
 /// [this function](https://docs.rs/rustilities/latest/rustilities/parsing/fn.syntactic_token_stream_contains.html)
 /// for further details, as it's used internally.
+///
+/// When used inside a [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html),
+/// every invocation of the macro is considered a match, as long as it contains the container
+/// group if `container_ident` is specified. The [`occurrence`](#structfield.occurrence) field
+/// defines which of these invocations are extended.
+///
+/// Unlike the implementors targeting items, fields or variants, this one has no
+/// [`Insertion`](crate::ast::implementors::Insertion) policy: the content of a macro invocation
+/// is an opaque token stream, without items to anchor to or sort, so the target `TokenStream` is
+/// always appended to the invocation or to its container group.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct TokenStreamToMacro {
 	/// The path used to invoke the macro in the AST. Eg, the `println` in `println!("hello")`.
 	pub macro_path: Path,
@@ -46,22 +63,38 @@ pub struct TokenStreamToMacro {
 	pub container_ident: Option<Ident>,
 	/// The target `TokenStream`.
 	pub token_stream: TokenStream,
+	/// Which of the matched macro invocations receive the target `TokenStream`. Defaults to
+	/// [`Occurrence::All`].
+	pub occurrence: Occurrence,
 }
 
 impl From<(Path, Option<Ident>, TokenStream)> for TokenStreamToMacro {
 	fn from(tuple: (Path, Option<Ident>, TokenStream)) -> Self {
-		Self { macro_path: tuple.0, container_ident: tuple.1, token_stream: tuple.2 }
+		Self {
+			macro_path: tuple.0,
+			container_ident: tuple.1,
+			token_stream: tuple.2,
+			occurrence: Occurrence::default(),
+		}
+	}
+}
+
+impl TokenStreamToMacro {
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
 	}
 }
 
 impl<'a> ToFind<'a, TokenStreamToMacro, 1> for Finder<'a, EmptyFinder, 1> {
 	fn to_find(self, finder: &'a TokenStreamToMacro) -> Finder<'a, TokenStreamToMacro, 1> {
-		Finder { found: self.found, finder }
+		Finder::new(finder)
 	}
 }
 
-impl<'a> Visit<'a> for Finder<'a, TokenStreamToMacro, 1> {
-	fn visit_macro(&mut self, macro_: &'a Macro) {
+impl<'ast> Visit<'ast> for Finder<'_, TokenStreamToMacro, 1> {
+	fn visit_macro(&mut self, macro_: &'ast Macro) {
 		if self.finder.token_stream.is_empty() {
 			return;
 		}
@@ -84,6 +117,7 @@ impl<'a> Visit<'a> for Finder<'a, TokenStreamToMacro, 1> {
 									) =>
 							{
 								self.found[0] = true;
+								self.record_match(macro_);
 								break;
 							},
 							_ if ident_found => ident_found = false,
@@ -95,7 +129,10 @@ impl<'a> Visit<'a> for Finder<'a, TokenStreamToMacro, 1> {
 					self.finder.token_stream.clone(),
 					macro_.tokens.clone(),
 				) =>
-					self.found[0] = true,
+				{
+					self.found[0] = true;
+					self.record_match(macro_);
+				},
 				_ => (),
 			}
 		}
@@ -104,17 +141,50 @@ impl<'a> Visit<'a> for Finder<'a, TokenStreamToMacro, 1> {
 
 impl<'a> ToMutate<'a, TokenStreamToMacro, 1> for Mutator<'_, EmptyMutator, 1> {
 	fn to_mutate(self, mutator: &'a TokenStreamToMacro) -> Mutator<'a, TokenStreamToMacro, 1> {
-		Mutator { mutated: self.mutated, mutator }
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for TokenStreamToMacro {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		let macro_path = self.macro_path.to_token_stream().to_string().replace(' ', "");
+		let container = match self.container_ident {
+			Some(ref ident) => format!("group `{}` in macro `{}!`", ident, macro_path),
+			None => format!("macro `{}!`", macro_path),
+		};
+		helpers::describe_addition_failure(&container, "the token stream", occurrences)
+	}
+}
+
+/// Only the top level invocations of the macro are preserved.
+impl Preserve for TokenStreamToMacro {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		let segments: Vec<String> = self
+			.macro_path
+			.segments
+			.iter()
+			.map(|segment| regex::escape(&segment.ident.to_string()))
+			.collect();
+		let leading_colon = if self.macro_path.leading_colon.is_some() { r"::\s*" } else { "" };
+		vec![Preserver::with_lookup(Lookup::Regex(
+			Regex::new(&format!(r"{}{}\s*!", leading_colon, segments.join(r"\s*::\s*")))
+				.expect("The regex is valid; qed;"),
+		))]
 	}
 }
 
 impl VisitMut for Mutator<'_, TokenStreamToMacro, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+	}
+
 	fn visit_macro_mut(&mut self, macro_: &mut Macro) {
 		if macro_.path == self.mutator.macro_path {
 			match self.mutator.container_ident.clone() {
 				Some(ident) => {
 					let mut new_tokens = TokenStream::new();
 					let mut ident_found = false;
+					let mut group_found = false;
 					for token in macro_.tokens.clone().into_iter() {
 						match token {
 							TokenTree::Ident(macro_ident)
@@ -130,7 +200,7 @@ impl VisitMut for Mutator<'_, TokenStreamToMacro, 1> {
 									group.delimiter(),
 									group_stream,
 								))));
-								self.mutated[0] = true;
+								group_found = true;
 								ident_found = false;
 							},
 							_ if ident_found => {
@@ -142,12 +212,16 @@ impl VisitMut for Mutator<'_, TokenStreamToMacro, 1> {
 							},
 						}
 					}
-					macro_.tokens = new_tokens;
-				},
-				None => {
-					macro_.tokens.extend(self.mutator.token_stream.clone());
-					self.mutated[0] = true;
+					if group_found && helpers::select_occurrence(self, self.mutator.occurrence) {
+						macro_.tokens = new_tokens;
+						self.mutated[0] = true;
+					}
 				},
+				None =>
+					if helpers::select_occurrence(self, self.mutator.occurrence) {
+						macro_.tokens.extend(self.mutator.token_stream.clone());
+						self.mutated[0] = true;
+					},
 			}
 		}
 	}
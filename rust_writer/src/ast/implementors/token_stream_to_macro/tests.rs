@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: GPL-3.0
 
 use super::*;
-use crate::Error;
+use crate::{Error, MutationFailure};
 use syn::parse_quote;
 use test_builder::TestBuilder;
 
@@ -148,8 +148,12 @@ fn token_stream_to_macro_mutate_fails_if_cannot_find_container() {
 		let mut mutator = Mutator::default().to_mutate(&token_to_macro);
 		assert!(matches!(
 			mutator.mutate(ast),
-			Err(Error::Descriptive(msg))
-				if msg == format!("Cannot mutate using Mutator: {:?}", token_to_macro)
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "group `Type` in macro `my_macro!` not found".to_owned()
+			}]
 		));
 
 		let mut finder = Finder::default().to_find(&token_to_macro);
@@ -175,11 +179,50 @@ fn token_stream_to_macro_mutate_fails_if_macro_not_found() {
 		let mut mutator = Mutator::default().to_mutate(&token_to_macro);
 		assert!(matches!(
 			mutator.mutate(ast),
-			Err(Error::Descriptive(msg))
-				if msg == format!("Cannot mutate using Mutator: {:?}", token_to_macro)
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "group `SomeEnum` in macro `unexisting_macro!` not found".to_owned()
+			}]
 		));
 
 		let mut finder = Finder::default().to_find(&token_to_macro);
 		assert!(!finder.find(ast));
 	});
 }
+
+#[test]
+fn token_stream_to_macro_mutate_with_occurrence_first_works() {
+	TestBuilder::default().with_multiple_matches_ast().execute(|mut builder| {
+		let token_stream_to_macro =
+			TokenStreamToMacro::from((parse_quote! { my_macro }, None, parse_quote! { , D }))
+				.with_occurrence(Occurrence::First);
+
+		let ast = builder.get_mut_ast_file("multiple_matches.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&token_stream_to_macro);
+		assert!(mutator.mutate(ast).is_ok());
+		assert_eq!(mutator.occurrences(), [2]);
+
+		let mut finder = Finder::default().to_find(&token_stream_to_macro);
+		assert!(finder.find(ast));
+
+		let token_stream_to_macro: TokenStreamToMacro =
+			(parse_quote! { my_macro }, None, parse_quote! { C, D }).into();
+		let mut finder = Finder::default().to_find(&token_stream_to_macro);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn token_stream_to_macro_preserves_macro_invocations() {
+	let token_to_macro: TokenStreamToMacro =
+		(parse_quote! { frame::construct_runtime }, None, parse_quote! { Balances }).into();
+
+	let preservers = token_to_macro.preservers();
+	assert_eq!(preservers.len(), 1);
+	assert!(preservers[0].lookup().matches("frame::construct_runtime!("));
+	assert!(preservers[0].lookup().matches("frame :: construct_runtime ! {"));
+	assert!(!preservers[0].lookup().matches("construct_runtime!("));
+}
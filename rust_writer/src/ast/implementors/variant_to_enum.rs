@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: GPL-3.0
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+	ast::{
+		finder::{EmptyFinder, Finder, ToFind},
+		implementors::{helpers, Insertion, Occurrence},
+		mutator::{Describe, EmptyMutator, Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use syn::{visit::Visit, visit_mut::VisitMut, File, ItemEnum, Variant};
+
+/// This implementor targets a variant inside an enum definition.
+///
+/// When it's used with [`Finder`], it doesn't take attributes into account, this is, if the
+/// following is contained in the target enum
+///
+/// ```no_compile
+/// /// Some nice docs
+/// #[some_attr]
+/// Variant(u8),
+/// ```
+///
+/// and the target variant is `Variant(u8)`, the [`find`] method will return true.
+///
+/// Explicit discriminants are only taken into account if the target variant defines one. So if
+/// the target enum contains `Variant = 3`, both `Variant` and `Variant = 3` are found, while
+/// `Variant = 4` isn't.
+///
+/// When it's used with [`Mutator`], the [`insertion`](#structfield.insertion) field defines where
+/// the target variant is inserted among the existing ones. See [`Insertion`] for further details.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct VariantToEnum<'a> {
+	/// The enum's name.
+	pub enum_name: &'a str,
+	/// The target variant.
+	pub variant: Variant,
+	/// Where the target variant is inserted. Defaults to [`Insertion::End`].
+	pub insertion: Insertion,
+	/// Which of the enums named after `enum_name` receive the target variant. Defaults to
+	/// [`Occurrence::All`].
+	pub occurrence: Occurrence,
+}
+
+impl<'a> From<(&'a str, Variant)> for VariantToEnum<'a> {
+	fn from(tuple: (&'a str, Variant)) -> Self {
+		Self {
+			enum_name: tuple.0,
+			variant: tuple.1,
+			insertion: Insertion::default(),
+			occurrence: Occurrence::default(),
+		}
+	}
+}
+
+impl VariantToEnum<'_> {
+	/// Sets the [`insertion`](#structfield.insertion) field.
+	pub fn with_insertion(mut self, insertion: Insertion) -> Self {
+		self.insertion = insertion;
+		self
+	}
+
+	/// Sets the [`occurrence`](#structfield.occurrence) field.
+	pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+		self.occurrence = occurrence;
+		self
+	}
+}
+
+impl VariantToEnum<'_> {
+	fn matches(&self, variant: &Variant) -> bool {
+		let discriminant = match self.variant.discriminant {
+			Some(_) => variant.discriminant.clone(),
+			None => None,
+		};
+		Variant { attrs: Vec::new(), discriminant, ..variant.clone() } ==
+			Variant { attrs: Vec::new(), ..self.variant.clone() }
+	}
+}
+
+impl<'a> ToFind<'a, VariantToEnum<'a>, 1> for Finder<'a, EmptyFinder, 1> {
+	fn to_find(self, finder: &'a VariantToEnum<'a>) -> Finder<'a, VariantToEnum<'a>, 1> {
+		Finder::new(finder)
+	}
+}
+
+impl<'a, 'ast> Visit<'ast> for Finder<'a, VariantToEnum<'a>, 1> {
+	fn visit_item_enum(&mut self, item_enum: &'ast ItemEnum) {
+		if item_enum.ident != self.finder.enum_name {
+			return;
+		}
+		for variant in &item_enum.variants {
+			if self.finder.matches(variant) {
+				self.found[0] = true;
+				self.record_match(variant);
+			}
+		}
+	}
+}
+
+impl<'a> ToMutate<'a, VariantToEnum<'a>, 1> for Mutator<'_, EmptyMutator, 1> {
+	fn to_mutate(self, mutator: &'a VariantToEnum<'a>) -> Mutator<'a, VariantToEnum<'a>, 1> {
+		Mutator::new(mutator)
+	}
+}
+
+impl Describe for VariantToEnum<'_> {
+	fn describe_failure(&self, occurrences: usize) -> String {
+		helpers::describe_addition_failure(
+			&format!("enum `{}`", self.enum_name),
+			&format!("variant `{}`", self.variant.ident),
+			occurrences,
+		)
+	}
+}
+
+impl Preserve for VariantToEnum<'_> {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		vec![Preserver::with_lookup(Lookup::Item {
+			kind: "enum".into(),
+			name: self.enum_name.into(),
+		})]
+	}
+}
+
+impl<'a> VisitMut for Mutator<'a, VariantToEnum<'a>, 1> {
+	fn visit_file_mut(&mut self, file: &mut File) {
+		helpers::visit_file_mut(self, file, self.mutator.occurrence);
+	}
+
+	fn visit_item_enum_mut(&mut self, item_enum: &mut ItemEnum) {
+		if item_enum.ident != self.mutator.enum_name ||
+			!helpers::select_occurrence(self, self.mutator.occurrence)
+		{
+			return;
+		}
+		if let Some(index) = helpers::insertion_index(
+			item_enum.variants.iter(),
+			&self.mutator.variant,
+			&self.mutator.insertion,
+		) {
+			self.mutated[0] = true;
+			item_enum.variants.insert(index, self.mutator.variant.clone());
+		}
+	}
+}
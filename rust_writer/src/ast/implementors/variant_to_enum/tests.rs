@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::{Error, MutationFailure};
+use syn::{parse_quote, Item};
+use test_builder::TestBuilder;
+
+#[test]
+fn variant_to_enum_finder_find_variant_if_present() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let variant_to_enum: VariantToEnum = ("MyEnum", parse_quote! { C { field: u8 } }).into();
+
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_finder_find_variant_if_present_despite_attrs() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let variant_to_enum: VariantToEnum = ("MyEnum", parse_quote! { D }).into();
+
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_finder_find_variant_with_or_without_discriminant() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let variant_to_enum: VariantToEnum = ("Discriminants", parse_quote! { B }).into();
+		let variant_to_enum_with_discriminant: VariantToEnum =
+			("Discriminants", parse_quote! { B = 2 }).into();
+
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(finder.find(ast));
+
+		let mut finder = Finder::default().to_find(&variant_to_enum_with_discriminant);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_finder_cannot_find_variant_if_discriminant_incorrect() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let variant_to_enum: VariantToEnum = ("Discriminants", parse_quote! { B = 3 }).into();
+
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_finder_cannot_find_variant_if_enum_name_incorrect() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let variant_to_enum: VariantToEnum = ("OtherEnum", parse_quote! { A }).into();
+
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_finder_cannot_find_variant_if_variant_incorrect() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let variant_to_enum: VariantToEnum = ("MyEnum", parse_quote! { B(u8) }).into();
+
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(!finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_mutate_works() {
+	TestBuilder::default().with_enum_ast().execute(|mut builder| {
+		let variant_to_enum: VariantToEnum = ("Discriminants", parse_quote! { C = 5 }).into();
+
+		let ast = builder.get_mut_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(!finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&variant_to_enum);
+		assert!(mutator.mutate(ast).is_ok());
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(finder.find(ast));
+	});
+}
+
+#[test]
+fn variant_to_enum_mutate_with_insertion_works() {
+	TestBuilder::default().with_enum_ast().execute(|builder| {
+		let ast = builder.get_ref_ast_file("enum.rs").expect("This exists; qed;");
+		let mutated_variant_names = |insertion| {
+			let variant_to_enum =
+				VariantToEnum::from(("MyEnum", parse_quote! { AB })).with_insertion(insertion);
+			let mut ast = ast.clone();
+			let mut mutator = Mutator::default().to_mutate(&variant_to_enum);
+			assert!(mutator.mutate(&mut ast).is_ok());
+			match ast.items[0] {
+				Item::Enum(ref item_enum) => item_enum
+					.variants
+					.iter()
+					.map(|variant| variant.ident.to_string())
+					.collect::<Vec<_>>(),
+				_ => unreachable!("By construction this is MyEnum; qed;"),
+			}
+		};
+
+		assert_eq!(mutated_variant_names(Insertion::Start), ["AB", "A", "B", "C", "D"]);
+		assert_eq!(mutated_variant_names(Insertion::End), ["A", "B", "C", "D", "AB"]);
+		assert_eq!(
+			mutated_variant_names(Insertion::Before(parse_quote! { C })),
+			["A", "B", "AB", "C", "D"]
+		);
+		assert_eq!(mutated_variant_names(Insertion::Sorted), ["A", "AB", "B", "C", "D"]);
+	});
+}
+
+#[test]
+fn variant_to_enum_mutate_fails_if_cannot_find_enum() {
+	TestBuilder::default().with_enum_ast().execute(|mut builder| {
+		let variant_to_enum: VariantToEnum = ("UnexistingEnum", parse_quote! { E }).into();
+
+		let ast = builder.get_mut_ast_file("enum.rs").expect("This exists; qed;");
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(!finder.find(ast));
+
+		let mut mutator = Mutator::default().to_mutate(&variant_to_enum);
+		assert!(matches!(
+			mutator.mutate(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "enum `UnexistingEnum` not found".to_owned()
+			}]
+		));
+
+		let mut finder = Finder::default().to_find(&variant_to_enum);
+		assert!(!finder.find(ast));
+	});
+}
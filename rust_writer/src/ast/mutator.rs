@@ -42,9 +42,13 @@
 #[cfg(test)]
 mod tests;
 
-use crate::Error;
+use crate::{
+	ast::finder::Finder,
+	preserver::{Preserve, Preserver},
+	Error, MutationFailure,
+};
 use std::fmt::Debug;
-use syn::{visit_mut::VisitMut, File};
+use syn::{visit::Visit, visit_mut::VisitMut, File};
 
 /// A placeholder finder which does not perform any specific search.
 /// This is used as the default for a [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html) .
@@ -100,10 +104,24 @@ pub struct Mutator<'a, T: Debug + Clone, const N: usize> {
 	/// A `Mutator` can act in different parts of the AST at the same time. This array keeps track
 	/// of which of these mutations succeeded.  
 	pub mutated: [bool; N],
+	// The number of containers matched by each of these mutations in the last run. See the
+	// `occurrences` method.
+	pub(crate) occurrences: [usize; N],
 	/// Placeholder to load an implementor.
 	pub mutator: &'a T,
 }
 
+/// The outcome of an `ensure` call, either on a [`Mutator`] or on a wrapper generated by the
+/// [`#[mutator]`](https://docs.rs/rust_writer/latest/rust_writer/ast/attr.mutator.html) macro.
+/// Each entry is the index of an implementor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ensured {
+	/// The implementors whose mutation has been applied.
+	pub applied: Vec<u32>,
+	/// The implementors whose target was already in the AST, so they've been skipped.
+	pub satisfied: Vec<u32>,
+}
+
 /// This trait is used to create new `Mutator` variables. It's typically implemented for
 /// `Mutator<'_, EmptyMutator, 1>`, enabling the flow:
 /// 1. Create a `Mutator<'_, EmptyMutator, 1>` using `Mutator::default()`.
@@ -112,35 +130,148 @@ pub trait ToMutate<'a, T: Debug + Clone, const N: usize> {
 	fn to_mutate(self, mutator: &'a T) -> Mutator<'a, T, N>;
 }
 
+/// This trait describes why an implementor failed to mutate an AST, so the failure can be reported
+/// in an [`Error::MutationFailed`]. It's implemented by every implementor usable with `Mutator`.
+pub trait Describe {
+	/// Describes the failure, given the number of containers matched by the implementor in the
+	/// last run (see [`Mutator::occurrences`]), eg "trait `MyTrait` not found".
+	fn describe_failure(&self, occurrences: usize) -> String;
+}
+
+// Used by the code generated by the `#[mutator]` and `#[local_mutator]` macros to describe the
+// failures of local implementors. Implementing `Describe` is optional for them, so the description
+// is picked by autoref: `(&LocalFailure(..)).describe_local()` resolves to `DescribeLocal` if the
+// implementor is `Describe`, and falls back to the default description otherwise. Local
+// implementors don't keep track of their matches, so they're described with 0 occurrences.
+#[doc(hidden)]
+pub struct LocalFailure<'a, T>(pub &'a T, pub &'a str);
+
+#[doc(hidden)]
+pub trait DescribeLocal {
+	fn describe_local(&self) -> String;
+}
+
+impl<T: Describe> DescribeLocal for LocalFailure<'_, T> {
+	fn describe_local(&self) -> String {
+		self.0.describe_failure(0)
+	}
+}
+
+#[doc(hidden)]
+pub trait DescribeLocalDefault {
+	fn describe_local(&self) -> String;
+}
+
+impl<T> DescribeLocalDefault for &LocalFailure<'_, T> {
+	fn describe_local(&self) -> String {
+		self.1.to_owned()
+	}
+}
+
 impl Default for Mutator<'_, EmptyMutator, 1> {
 	fn default() -> Self {
-		Self { mutated: [false], mutator: &EmptyMutator }
+		Self::new(&EmptyMutator)
 	}
 }
 
 impl<'a, T, const N: usize> Mutator<'a, T, N>
 where
 	T: Debug + Clone,
+{
+	/// Create a new `Mutator` loading the given implementor, with none of its mutations applied
+	/// yet. This is the building block of the
+	/// [`ToMutate`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/trait.ToMutate.html)
+	/// implementations.
+	pub fn new(mutator: &'a T) -> Self {
+		Self { mutated: [false; N], occurrences: [0; N], mutator }
+	}
+
+	/// The number of containers matched by each of the mutations in the last run, eg the number
+	/// of `impl` blocks matching an [`ItemToImpl`](https://docs.rs/rust_writer/latest/rust_writer/ast/implementors/struct.ItemToImpl.html)
+	/// implementor. Implementors that don't keep track of their matches report 0.
+	pub fn occurrences(&self) -> [usize; N] {
+		self.occurrences
+	}
+
+	// Used by the code generated by the `#[mutator]` macro to report the occurrences of the
+	// implementors it merges.
+	#[doc(hidden)]
+	pub fn set_occurrences(&mut self, index: usize, occurrences: usize) {
+		self.occurrences[index] = occurrences;
+	}
+}
+
+impl<'a, T, const N: usize> Mutator<'a, T, N>
+where
+	T: Debug + Clone + Describe,
 	Mutator<'a, T, N>: VisitMut,
 {
-	/// Apply all the mutations defined by the implementor.
+	/// Apply all the mutations defined by the implementor. If some of them fail, the returned
+	/// [`Error::MutationFailed`] describes each of the failures.
 	pub fn mutate(&mut self, ast: &mut File) -> Result<(), Error> {
 		self.visit_file_mut(ast);
 
-		if self.mutated.iter().all(|&x| x) {
+		let failures: Vec<MutationFailure> = self
+			.mutated
+			.iter()
+			.zip(self.occurrences)
+			.enumerate()
+			.filter(|(_, (&mutated, _))| !mutated)
+			.map(|(index, (_, occurrences))| MutationFailure {
+				index: index as u32,
+				field: None,
+				description: self.mutator.describe_failure(occurrences),
+			})
+			.collect();
+
+		if failures.is_empty() {
 			Ok(())
 		} else {
-			Err(Error::Descriptive(format!("Cannot mutate using Mutator: {:?}", self.mutator)))
+			Err(Error::MutationFailed(failures))
 		}
 	}
 }
 
+impl<'a, T> Mutator<'a, T, 1>
+where
+	T: Debug + Clone + Describe,
+	Mutator<'a, T, 1>: VisitMut,
+{
+	/// Apply the mutation defined by the implementor only if its target cannot be found in the
+	/// AST yet, so calling this method several times over the same AST doesn't duplicate
+	/// elements. The returned [`Ensured`] reports if the mutation has been applied or if it was
+	/// already satisfied.
+	pub fn ensure(&mut self, ast: &mut File) -> Result<Ensured, Error>
+	where
+		for<'ast> Finder<'a, T, 1>: Visit<'ast>,
+	{
+		let mut finder = Finder::new(self.mutator);
+		if finder.find(ast) {
+			Ok(Ensured { applied: Vec::new(), satisfied: vec![0] })
+		} else {
+			self.mutate(ast)?;
+			Ok(Ensured { applied: vec![0], satisfied: Vec::new() })
+		}
+	}
+}
+
+impl<T, const N: usize> Preserve for Mutator<'_, T, N>
+where
+	T: Debug + Clone + Preserve,
+{
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		self.mutator.preservers()
+	}
+}
+
 impl<T, const N: usize> Mutator<'_, T, N>
 where
 	T: Debug + Clone,
 {
-	/// Reset the `mutated` array to all `false` values.
+	/// Reset the `mutated` array to all `false` values and the `occurrences` array to all `0`
+	/// values.
 	pub fn reset(&mut self) {
 		self.mutated = [false; N];
+		self.occurrences = [0; N];
 	}
 }
@@ -17,9 +17,74 @@ fn mutator_works_for_a_implementor() {
 		assert!(mutator.mutate(ast).is_ok());
 
 		assert!(mutator.mutated[0]);
+		assert_eq!(mutator.occurrences, [1]);
 
 		mutator.reset();
 
 		assert!(!mutator.mutated[0]);
+		assert_eq!(mutator.occurrences, [0]);
+	});
+}
+
+#[test]
+fn mutator_ensure_only_mutates_once() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_to_trait: ItemToTrait =
+			("MyTrait", TraitItem::Type(parse_quote! {type Type3: From<String>;})).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		assert_eq!(
+			mutator.ensure(ast).expect("This should be Ok; qed;"),
+			Ensured { applied: vec![0], satisfied: Vec::new() }
+		);
+
+		let ast_after_first_ensure = ast.clone();
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		assert_eq!(
+			mutator.ensure(ast).expect("This should be Ok; qed;"),
+			Ensured { applied: Vec::new(), satisfied: vec![0] }
+		);
+
+		assert_eq!(*ast, ast_after_first_ensure);
+	});
+}
+
+#[test]
+fn mutator_ensure_fails_if_mutation_fails() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_to_trait: ItemToTrait =
+			("OtherTrait", TraitItem::Type(parse_quote! {type Type3: From<String>;})).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		assert!(matches!(
+			mutator.ensure(ast),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 0,
+				field: None,
+				description: "trait `OtherTrait` not found".to_owned()
+			}]
+		));
+	});
+}
+
+#[test]
+fn mutator_error_describes_failures() {
+	TestBuilder::default().with_trait_ast().execute(|mut builder| {
+		let item_to_trait: ItemToTrait =
+			("OtherTrait", TraitItem::Type(parse_quote! {type Type3: From<String>;})).into();
+
+		let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+
+		let mut mutator = Mutator::default().to_mutate(&item_to_trait);
+		assert_eq!(
+			mutator.mutate(ast).expect_err("This should fail; qed;").to_string(),
+			"Cannot mutate using Mutator: implementor 0: trait `OtherTrait` not found"
+		);
 	});
 }
@@ -0,0 +1,300 @@
+// SPDX-License-Identifier: GPL-3.0
+
+//! This module provides a crate-level layer on top of the [`ast`](crate::ast) and
+//! [`splicer`](crate::splicer) modules, so a whole crate may be edited at once instead of a single
+//! file.
+//!
+//! A [`CrateTree`] is loaded from the crate root (typically `lib.rs` or `main.rs`). Every
+//! out-of-line module declaration (`mod foo;`) is resolved to its file, following the same rules as
+//! the compiler: `foo.rs`, `foo/mod.rs` or the file given by a `#[path = "..."]` attribute. The
+//! items of that file are then inlined into the declaration, so the whole crate becomes a single
+//! AST where out-of-line modules look like inline ones.
+//!
+//! That AST can be used with any [`Finder`](crate::ast::finder::Finder) or
+//! [`Mutator`](crate::ast::mutator::Mutator), so eg an
+//! [`ItemToMod`](crate::ast::implementors::ItemToMod) targeting `foo` mutates the content of
+//! `foo.rs`. Once mutated, [`CrateTree::write`] splits the AST back into its files and only writes
+//! those that changed, splicing the changes with [`splice`](crate::splicer::splice), so the rest
+//! of the files is kept byte by byte. As the splicer, this module is only available with the
+//! `span-locations` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use rust_writer::{
+//!     ast::{
+//!         implementors::ItemToMod,
+//!         mutator::{Mutator, ToMutate},
+//!     },
+//!     crate_tree::CrateTree,
+//! };
+//! use syn::parse_quote;
+//! use test_builder::TestBuilder;
+//!
+//! TestBuilder::default().execute(|builder| {
+//!     let src = builder.tempdir_path();
+//!     std::fs::write(src.join("lib.rs"), "mod foo;\n").expect("This should be writable; qed;");
+//!     std::fs::write(src.join("foo.rs"), "// Foo\nfn foo() {}\n")
+//!         .expect("This should be writable; qed;");
+//!
+//!     let mut crate_tree = CrateTree::load(&src.join("lib.rs")).expect("This should be Ok; qed;");
+//!
+//!     let item_to_mod: ItemToMod = ("foo", parse_quote! { fn bar() {} }).into();
+//!     let mut mutator = Mutator::default().to_mutate(&item_to_mod);
+//!     assert!(mutator.mutate(crate_tree.ast_mut()).is_ok());
+//!
+//!     assert_eq!(crate_tree.write().expect("This should be Ok; qed;"), [src.join("foo.rs")]);
+//!     assert_eq!(
+//!         std::fs::read_to_string(src.join("foo.rs")).expect("This should be readable; qed;"),
+//!         "// Foo\nfn foo() {}\nfn bar() {}\n"
+//!     );
+//! });
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::Error;
+use std::path::{Path, PathBuf};
+use syn::{token::Brace, Expr, ExprLit, File, Item, ItemMod, Lit, Meta};
+
+/// A crate loaded from its root file, with all its out-of-line modules inlined into a single AST.
+/// See the [module documentation](self) for further details.
+#[derive(Debug, Clone)]
+pub struct CrateTree {
+	ast: File,
+	files: Vec<ModuleFile>,
+}
+
+// A file of the crate, as it was read. The module loaded from it is found back in the crate AST
+// by resolving the paths of the modules again, so the file doesn't depend on where its module is
+// among the items of its parent.
+#[derive(Debug, Clone)]
+struct ModuleFile {
+	path: PathBuf,
+	source: String,
+	ast: File,
+}
+
+// Where the modules declared in a file or in an inline module are looked for.
+struct ModuleDirs<'a> {
+	// The directory `#[path]` attributes are relative to.
+	path_attr_dir: &'a Path,
+	// The directory containing the files of the modules without `#[path]` attribute.
+	children_dir: &'a Path,
+}
+
+impl CrateTree {
+	/// Loads the crate whose root file is `root`, eg `src/lib.rs`, resolving all its out-of-line
+	/// modules.
+	///
+	/// # Errors
+	///
+	/// This function fails if any of the files cannot be read or parsed, including the files of the
+	/// modules declared in the crate.
+	pub fn load(root: &Path) -> Result<Self, Error> {
+		let mut files = Vec::new();
+		let root_dir = root.parent().unwrap_or(Path::new(""));
+		let ast = load_file(root, root_dir, &mut files)?;
+		Ok(Self { ast, files })
+	}
+
+	/// The AST of the whole crate.
+	pub fn ast(&self) -> &File {
+		&self.ast
+	}
+
+	/// The AST of the whole crate, ready to be mutated.
+	pub fn ast_mut(&mut self) -> &mut File {
+		&mut self.ast
+	}
+
+	/// The paths of the files composing the crate, starting by its root.
+	pub fn files(&self) -> Vec<&Path> {
+		self.files.iter().map(|file| file.path.as_path()).collect()
+	}
+
+	/// Splits the crate AST back into its files and writes the files whose AST changed, returning
+	/// their paths. The changes are spliced into the files, so the rest of their code is kept as
+	/// it was.
+	///
+	/// The modules are matched with their files by resolving their paths as they were loaded, ie
+	/// from their names and `#[path]` attributes, so moving a module among the items of its parent
+	/// or declaring new modules beside it doesn't change its file. If the module loaded from a file
+	/// isn't in the AST anymore, eg because it's been removed, that file is left untouched. Modules
+	/// added to the AST are written inline into their parent file.
+	///
+	/// # Errors
+	///
+	/// This function fails if the changes cannot be spliced (see
+	/// [`splice`](crate::splicer::splice)) or if a file cannot be written. The files written
+	/// before the failure keep their changes.
+	pub fn write(&mut self) -> Result<Vec<PathBuf>, Error> {
+		let mut ast = self.ast.clone();
+		let mut asts: Vec<Option<File>> = vec![None; self.files.len()];
+
+		let root_dir = self.files[0].path.parent().unwrap_or(Path::new(""));
+		split_modules(
+			&mut ast.items,
+			ModuleDirs { path_attr_dir: root_dir, children_dir: root_dir },
+			&self.files,
+			&mut asts,
+		);
+		asts[0] = Some(ast);
+
+		let mut written = Vec::new();
+		for (file, ast) in self.files.iter_mut().zip(asts) {
+			match ast {
+				Some(ast) if ast != file.ast => {
+					let source = crate::splicer::splice(&file.source, &file.ast, &ast)?;
+					std::fs::write(&file.path, &source)?;
+					file.ast =
+						syn::parse_file(&source).map_err(|err| parse_error(&file.path, err))?;
+					file.source = source;
+					written.push(file.path.clone());
+				},
+				_ => (),
+			}
+		}
+		Ok(written)
+	}
+}
+
+// Reads and parses a file, inlining its out-of-line modules. The file is recorded in `files`.
+fn load_file(path: &Path, children_dir: &Path, files: &mut Vec<ModuleFile>) -> Result<File, Error> {
+	let source = std::fs::read_to_string(path)?;
+	let mut ast = syn::parse_file(&source).map_err(|err| parse_error(path, err))?;
+	files.push(ModuleFile { path: path.to_path_buf(), source, ast: ast.clone() });
+
+	let file_dir = path.parent().unwrap_or(Path::new(""));
+	load_modules(&mut ast.items, ModuleDirs { path_attr_dir: file_dir, children_dir }, files)?;
+	Ok(ast)
+}
+
+fn load_modules(
+	items: &mut [Item],
+	dirs: ModuleDirs,
+	files: &mut Vec<ModuleFile>,
+) -> Result<(), Error> {
+	for item in items {
+		let Item::Mod(item_mod) = item else {
+			continue;
+		};
+		if item_mod.content.is_none() {
+			let (path, children_dir) = module_file(item_mod, &dirs, Path::exists);
+			let ast = load_file(&path, &children_dir, files)?;
+			item_mod.content = Some((Brace::default(), ast.items));
+			item_mod.semi = None;
+		} else {
+			let dir = inline_module_dir(item_mod, &dirs);
+			if let Some((_, ref mut items)) = item_mod.content {
+				load_modules(items, ModuleDirs { path_attr_dir: &dir, children_dir: &dir }, files)?;
+			}
+		}
+	}
+	Ok(())
+}
+
+// Takes out of `items` the content of the modules loaded from `files`, turning them back into
+// out-of-line modules. The ASTs of their files are stored in `asts`, at the index of their file.
+fn split_modules(
+	items: &mut [Item],
+	dirs: ModuleDirs,
+	files: &[ModuleFile],
+	asts: &mut [Option<File>],
+) {
+	for item in items {
+		let Item::Mod(item_mod) = item else {
+			continue;
+		};
+		let is_loaded = |path: &Path| files.iter().any(|file| file.path == path);
+		let (path, children_dir) = module_file(item_mod, &dirs, is_loaded);
+		// Modules declared several times, eg under different `cfg` attributes, load their file
+		// once per declaration, so each declaration takes the first of them not taken yet.
+		let index =
+			(1..files.len()).find(|&index| files[index].path == path && asts[index].is_none());
+		let inline_dir = inline_module_dir(item_mod, &dirs);
+		let Some((_, ref mut items)) = item_mod.content else {
+			continue;
+		};
+		let Some(index) = index else {
+			split_modules(
+				items,
+				ModuleDirs { path_attr_dir: &inline_dir, children_dir: &inline_dir },
+				files,
+				asts,
+			);
+			continue;
+		};
+		// The nested modules are split first, so the file doesn't contain them anymore.
+		let file_dir = path.parent().unwrap_or(Path::new(""));
+		split_modules(
+			items,
+			ModuleDirs { path_attr_dir: file_dir, children_dir: &children_dir },
+			files,
+			asts,
+		);
+		asts[index] = Some(File {
+			shebang: files[index].ast.shebang.clone(),
+			attrs: files[index].ast.attrs.clone(),
+			items: std::mem::take(items),
+		});
+		item_mod.content = None;
+		item_mod.semi = Some(Default::default());
+	}
+}
+
+// The file of the out-of-line module `item_mod`, following the compiler rules, together with the
+// directory containing the files of its own modules. `is_file` tells whether a `foo.rs` candidate
+// is the module file, as it's preferred over `foo/mod.rs`.
+fn module_file(
+	item_mod: &ItemMod,
+	dirs: &ModuleDirs,
+	is_file: impl Fn(&Path) -> bool,
+) -> (PathBuf, PathBuf) {
+	let name = module_name(item_mod);
+	// Files loaded from `mod.rs` or from a `#[path]` attribute keep their modules in their
+	// directory, while `foo.rs` keeps them in `foo/`.
+	let (path, mod_rs) = match path_attr(item_mod) {
+		Some(path_attr) => (dirs.path_attr_dir.join(path_attr), true),
+		None => match dirs.children_dir.join(format!("{}.rs", name)) {
+			path if is_file(&path) => (path, false),
+			_ => (dirs.children_dir.join(&name).join("mod.rs"), true),
+		},
+	};
+	let children_dir = if mod_rs {
+		path.parent().unwrap_or(Path::new("")).to_path_buf()
+	} else {
+		path.with_extension("")
+	};
+	(path, children_dir)
+}
+
+// The directory where the modules declared inside the inline module `item_mod` are looked for.
+// Inside inline modules, `#[path]` attributes are relative to that directory as well.
+fn inline_module_dir(item_mod: &ItemMod, dirs: &ModuleDirs) -> PathBuf {
+	match path_attr(item_mod) {
+		Some(path_attr) => dirs.path_attr_dir.join(path_attr),
+		None => dirs.children_dir.join(module_name(item_mod)),
+	}
+}
+
+fn module_name(item_mod: &ItemMod) -> String {
+	let name = item_mod.ident.to_string();
+	name.strip_prefix("r#").map(ToOwned::to_owned).unwrap_or(name)
+}
+
+// The value of the `#[path = "..."]` attribute of a module, if any.
+fn path_attr(item_mod: &ItemMod) -> Option<String> {
+	item_mod.attrs.iter().find_map(|attr| match attr.meta {
+		Meta::NameValue(ref meta) if meta.path.is_ident("path") => match meta.value {
+			Expr::Lit(ExprLit { lit: Lit::Str(ref path), .. }) => Some(path.value()),
+			_ => None,
+		},
+		_ => None,
+	})
+}
+
+fn parse_error(path: &Path, err: syn::Error) -> Error {
+	Error::Descriptive(format!("Cannot parse `{}`: {}", path.display(), err))
+}
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::ast::{
+	finder::{Finder, ToFind},
+	implementors::{ItemFromFile, ItemToFile, ItemToMod},
+	mutator::{Mutator, ToMutate},
+};
+use std::io::ErrorKind;
+use syn::parse_quote;
+use test_builder::TestBuilder;
+
+// Writes a crate whose root is `lib.rs` into `dir`:
+// - `a.rs`, which has inner attributes and declares `b`, found in `a/b.rs`.
+// - `c/mod.rs`, which declares `d`, found in `c/d.rs`.
+// - `e`, an inline module declaring `f`, found in `e/f.rs`.
+// - `g`, declared twice with `#[path]` attributes, found in `other/g_unix.rs` and
+//   `other/g_windows.rs`.
+fn write_crate(dir: &Path) {
+	let files = [
+		(
+			"lib.rs",
+			"// Crate root\nmod a;\nmod c;\n\nmod e {\n    mod f;\n}\n\n#[cfg(unix)]\n#[path = \
+			 \"other/g_unix.rs\"]\nmod g;\n#[cfg(windows)]\n#[path = \"other/g_windows.rs\"]\nmod \
+			 g;\n",
+		),
+		("a.rs", "//! Module a\n#![allow(dead_code)]\nmod b;\n\nfn a() {}\n"),
+		("a/b.rs", "// Module b\nfn b() {}\n"),
+		("c/mod.rs", "mod d;\n"),
+		("c/d.rs", "fn d() {}\n"),
+		("e/f.rs", "fn f() {}\n"),
+		("other/g_unix.rs", "fn g_unix() {}\n"),
+		("other/g_windows.rs", "fn g_windows() {}\n"),
+	];
+	for (path, code) in files {
+		let path = dir.join(path);
+		std::fs::create_dir_all(path.parent().expect("The path has a parent; qed;"))
+			.expect("The dir should be created; qed;");
+		std::fs::write(path, code).expect("The file should be writable; qed;");
+	}
+}
+
+fn read(dir: &Path, path: &str) -> String {
+	std::fs::read_to_string(dir.join(path)).expect("File should be readable")
+}
+
+#[test]
+fn load_resolves_all_modules() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		write_crate(dir);
+
+		let crate_tree = CrateTree::load(&dir.join("lib.rs")).expect("This should be Ok; qed;");
+
+		assert_eq!(
+			crate_tree.files(),
+			[
+				"lib.rs",
+				"a.rs",
+				"a/b.rs",
+				"c/mod.rs",
+				"c/d.rs",
+				"e/f.rs",
+				"other/g_unix.rs",
+				"other/g_windows.rs"
+			]
+			.map(|path| dir.join(path))
+		);
+
+		let expected_ast: File = parse_quote! {
+			mod a {
+				mod b {
+					fn b() {}
+				}
+
+				fn a() {}
+			}
+			mod c {
+				mod d {
+					fn d() {}
+				}
+			}
+			mod e {
+				mod f {
+					fn f() {}
+				}
+			}
+			#[cfg(unix)]
+			#[path = "other/g_unix.rs"]
+			mod g {
+				fn g_unix() {}
+			}
+			#[cfg(windows)]
+			#[path = "other/g_windows.rs"]
+			mod g {
+				fn g_windows() {}
+			}
+		};
+		assert_eq!(*crate_tree.ast(), expected_ast);
+	});
+}
+
+#[test]
+fn finder_works_across_files() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		write_crate(dir);
+
+		let crate_tree = CrateTree::load(&dir.join("lib.rs")).expect("This should be Ok; qed;");
+
+		// `mod d;` has been replaced by the content of `c/d.rs`.
+		let item_to_mod: ItemToMod = ("c", parse_quote! { mod d { fn d() {} } }).into();
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(finder.find(crate_tree.ast()));
+
+		let item_to_mod: ItemToMod = ("c::d", parse_quote! { fn d() {} }).into();
+		let mut finder = Finder::default().to_find(&item_to_mod);
+		assert!(finder.find(crate_tree.ast()));
+	});
+}
+
+#[test]
+fn write_only_writes_changed_files() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		write_crate(dir);
+
+		let mut crate_tree = CrateTree::load(&dir.join("lib.rs")).expect("This should be Ok; qed;");
+
+		let item_to_g: ItemToMod = ("g", parse_quote! { fn other_g() {} }).into();
+		let item_to_b: ItemToMod = ("a::b", parse_quote! { fn other_b() {} }).into();
+		let item: Item = parse_quote! { use std::path::Path; };
+		let item_to_file: ItemToFile = item.into();
+		assert!(Mutator::default().to_mutate(&item_to_g).mutate(crate_tree.ast_mut()).is_ok());
+		assert!(Mutator::default().to_mutate(&item_to_b).mutate(crate_tree.ast_mut()).is_ok());
+		assert!(Mutator::default().to_mutate(&item_to_file).mutate(crate_tree.ast_mut()).is_ok());
+
+		assert_eq!(
+			crate_tree.write().expect("This should be Ok; qed;"),
+			["lib.rs", "a/b.rs", "other/g_unix.rs", "other/g_windows.rs"]
+				.map(|path| dir.join(path))
+		);
+		assert_eq!(
+			read(dir, "lib.rs"),
+			"// Crate root\nmod a;\nmod c;\n\nmod e {\n    mod f;\n}\n\n#[cfg(unix)]\n#[path = \
+			 \"other/g_unix.rs\"]\nmod g;\n#[cfg(windows)]\n#[path = \"other/g_windows.rs\"]\nmod \
+			 g;\n\nuse std::path::Path;\n"
+		);
+		assert_eq!(read(dir, "a/b.rs"), "// Module b\nfn b() {}\nfn other_b() {}\n");
+		assert_eq!(read(dir, "other/g_unix.rs"), "fn g_unix() {}\nfn other_g() {}\n");
+		assert_eq!(read(dir, "a.rs"), "//! Module a\n#![allow(dead_code)]\nmod b;\n\nfn a() {}\n");
+
+		// Nothing changed since the last write.
+		assert!(crate_tree.write().expect("This should be Ok; qed;").is_empty());
+	});
+}
+
+#[test]
+fn write_keeps_inner_attributes_of_module_files() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		write_crate(dir);
+
+		let mut crate_tree = CrateTree::load(&dir.join("lib.rs")).expect("This should be Ok; qed;");
+
+		let item_to_a: ItemToMod = ("a", parse_quote! { fn other_a() {} }).into();
+		assert!(Mutator::default().to_mutate(&item_to_a).mutate(crate_tree.ast_mut()).is_ok());
+
+		assert_eq!(crate_tree.write().expect("This should be Ok; qed;"), [dir.join("a.rs")]);
+		assert_eq!(
+			read(dir, "a.rs"),
+			"//! Module a\n#![allow(dead_code)]\nmod b;\n\nfn a() {}\n\nfn other_a() {}\n"
+		);
+	});
+}
+
+#[test]
+fn write_finds_module_files_after_declaring_modules_with_the_same_name() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		write_crate(dir);
+
+		let mut crate_tree = CrateTree::load(&dir.join("lib.rs")).expect("This should be Ok; qed;");
+
+		// Both modules are declared before the loaded ones with the same name.
+		let mod_a: Item = parse_quote! {
+			#[cfg(any())]
+			mod a;
+		};
+		let mod_g: Item = parse_quote! {
+			#[cfg(target_os = "none")]
+			#[path = "other/g_none.rs"]
+			mod g;
+		};
+		crate_tree.ast_mut().items.insert(0, mod_a);
+		crate_tree.ast_mut().items.insert(4, mod_g);
+		let item_to_a: ItemToMod = ("a", parse_quote! { fn other_a() {} }).into();
+		let item_to_g = ItemToMod::from(("g", parse_quote! { fn other_g() {} }))
+			.with_container_attrs(vec![
+				parse_quote! { #[cfg(unix)] },
+				parse_quote! { #[path = "other/g_unix.rs"] },
+			]);
+		assert!(Mutator::default().to_mutate(&item_to_a).mutate(crate_tree.ast_mut()).is_ok());
+		assert!(Mutator::default().to_mutate(&item_to_g).mutate(crate_tree.ast_mut()).is_ok());
+
+		assert_eq!(
+			crate_tree.write().expect("This should be Ok; qed;"),
+			["lib.rs", "a.rs", "other/g_unix.rs"].map(|path| dir.join(path))
+		);
+		assert_eq!(
+			read(dir, "a.rs"),
+			"//! Module a\n#![allow(dead_code)]\nmod b;\n\nfn a() {}\n\nfn other_a() {}\n"
+		);
+		assert_eq!(read(dir, "other/g_unix.rs"), "fn g_unix() {}\nfn other_g() {}\n");
+		assert_eq!(read(dir, "other/g_windows.rs"), "fn g_windows() {}\n");
+		assert_eq!(read(dir, "a/b.rs"), "// Module b\nfn b() {}\n");
+	});
+}
+
+#[test]
+fn write_leaves_files_of_removed_modules_untouched() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		write_crate(dir);
+
+		let mut crate_tree = CrateTree::load(&dir.join("lib.rs")).expect("This should be Ok; qed;");
+
+		let item_from_file = ItemFromFile::from(crate_tree.ast().items[1].clone()); // mod c
+		assert!(Mutator::default()
+			.to_mutate(&item_from_file)
+			.mutate(crate_tree.ast_mut())
+			.is_ok());
+
+		assert_eq!(crate_tree.write().expect("This should be Ok; qed;"), [dir.join("lib.rs")]);
+		assert!(!read(dir, "lib.rs").contains("mod c;"));
+		assert_eq!(read(dir, "c/mod.rs"), "mod d;\n");
+	});
+}
+
+#[test]
+fn load_fails_if_module_file_not_found() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		std::fs::write(dir.join("main.rs"), "mod missing;\nfn main() {}\n")
+			.expect("The file should be writable; qed;");
+
+		assert!(matches!(
+			CrateTree::load(&dir.join("main.rs")),
+			Err(Error::IO(err)) if err.kind() == ErrorKind::NotFound
+		));
+	});
+}
+
+#[test]
+fn load_fails_if_module_file_cannot_be_parsed() {
+	TestBuilder::default().execute(|builder| {
+		let dir = builder.tempdir_path();
+		std::fs::write(dir.join("main.rs"), "mod invalid;\nfn main() {}\n")
+			.expect("The file should be writable; qed;");
+		std::fs::write(dir.join("invalid.rs"), "fn invalid( {}\n")
+			.expect("The file should be writable; qed;");
+
+		assert!(matches!(
+			CrateTree::load(&dir.join("main.rs")),
+			Err(Error::Descriptive(msg)) if msg.starts_with("Cannot parse") && msg.contains("invalid.rs")
+		));
+	});
+}
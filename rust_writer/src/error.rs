@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0
 
+use std::fmt::{Display, Formatter, Result as FmtResult};
 use thiserror::Error;
 
 /// Represents the various errors that can occur in the crate.
@@ -9,6 +10,65 @@ pub enum Error {
 	IO(#[from] std::io::Error),
 	#[error("{0}")]
 	Descriptive(String),
-	#[error("The code cannot be safely preserved. Check 'https://docs.rs/rust_writer/latest/rust_writer/preserver/fn.preserve_and_parse.html' for further information.")]
-	NonPreservableCode,
+	#[error("Cannot mutate using Mutator: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+	MutationFailed(Vec<MutationFailure>),
+	#[error("The code cannot be safely preserved: {message}{}. Check 'https://docs.rs/rust_writer/latest/rust_writer/preserver/fn.preserve_and_parse.html' for further information.", describe_location(.line, .column, .lookup))]
+	NonPreservableCode {
+		/// The message of the parsing error.
+		message: String,
+		/// The line of the original code where the error was found, 1-indexed. It's only reported
+		/// with the `span-locations` feature.
+		line: Option<usize>,
+		/// The column of the original code where the error was found, 0-indexed. As the line, it's
+		/// only reported with the `span-locations` feature, and it's unknown if the error is found
+		/// in the code added to keep the comments of preserved code.
+		column: Option<usize>,
+		/// The lookup of the preserver whose region contains the error, if any. As the line, it's
+		/// only reported with the `span-locations` feature.
+		lookup: Option<String>,
+	},
+}
+
+/// An implementor that failed to mutate an AST, as reported by [`Error::MutationFailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationFailure {
+	/// The index of the failed implementor.
+	pub index: u32,
+	/// The name of the field holding the failed implementor, if it's part of a struct combining
+	/// several implementors.
+	pub field: Option<String>,
+	/// A short description of the failure, eg "trait `MyTrait` not found".
+	pub description: String,
+}
+
+// Describes where a non-preservable code error is, eg " (line 3, column 4, preserved by `fn
+// main`)", or nothing if it's unknown.
+fn describe_location(
+	line: &Option<usize>,
+	column: &Option<usize>,
+	lookup: &Option<String>,
+) -> String {
+	let parts: Vec<String> = [
+		line.map(|line| format!("line {}", line)),
+		column.map(|column| format!("column {}", column)),
+		lookup.as_ref().map(|lookup| format!("preserved by `{}`", lookup)),
+	]
+	.into_iter()
+	.flatten()
+	.collect();
+	if parts.is_empty() {
+		String::new()
+	} else {
+		format!(" ({})", parts.join(", "))
+	}
+}
+
+impl Display for MutationFailure {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self.field {
+			Some(ref field) =>
+				write!(f, "implementor {} (`{}`): {}", self.index, field, self.description),
+			None => write!(f, "implementor {}: {}", self.index, self.description),
+		}
+	}
 }
@@ -8,7 +8,8 @@
 //! capabilities to modify Rust source files. This makes it ideal for tasks that require in-place
 //! source code modifications rather than merely generating new code visible only to the compiler.
 //!
-//! The crate is divided into two modules: the [`preserver`] module and the [`ast`] module. Although
+//! The crate is divided into three modules: the [`preserver`] module, the [`ast`] module and the
+//! [`splicer`](https://docs.rs/rust_writer/latest/rust_writer/splicer/index.html) module. Although
 //! these modules can be used separately and even for purposes other than the crate's primary
 //! objective, using them together unlocks the full potential of the crate.
 //!
@@ -16,6 +17,23 @@
 //!   when it is parsed into an AST.
 //! - The [`ast`] module provides various tools to simplify AST interactions, allowing precise
 //!   modifications exactly where needed.
+//! - The [`splicer`](https://docs.rs/rust_writer/latest/rust_writer/splicer/index.html) module
+//!   writes a mutated AST back to its source file re-emitting only the changed items, so the rest
+//!   of the file is kept byte by byte. It's an alternative to the [`preserver`] module when the
+//!   diff on the source file must only contain the applied changes.
+//!
+//! On top of them, the [`crate_tree`](https://docs.rs/rust_writer/latest/rust_writer/crate_tree/index.html)
+//! module allows to edit a whole crate at once, following its `mod foo;` declarations and writing
+//! back only the files that changed.
+//!
+//! # Features
+//!
+//! - `serde`: implements `Serialize` and `Deserialize` for [`Preserver`](preserver::Preserver) and
+//!   [`Lookup`](preserver::Lookup), so preservers can be stored in configuration files.
+//! - `span-locations`: enables the line/column information of the parsed code, which is used to
+//!   report the span of the nodes located by a [`Finder`](ast::finder::Finder) and where the code
+//!   that cannot be preserved is. It's also needed by the `splicer` and `crate_tree` modules, which
+//!   are only available with this feature.
 //!
 //! For further details, please refer to the individual module documentation. A complete example is
 //! often the best way to illustrate the functionality:
@@ -125,7 +143,11 @@
 //! from a file named accordingly, while `with_something_file` load the entire file.
 
 pub mod ast;
+#[cfg(feature = "span-locations")]
+pub mod crate_tree;
 mod error;
 pub mod preserver;
+#[cfg(feature = "span-locations")]
+pub mod splicer;
 
-pub use error::Error;
+pub use error::{Error, MutationFailure};
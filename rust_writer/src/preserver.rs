@@ -17,10 +17,20 @@
 //! - [`resolve_preserved`]: Takes a preserved AST, un-parses it back to source code using [`prettyplease::unparse`](https://docs.rs/prettyplease/latest/prettyplease/fn.unparse.html),
 //!   and then restores the preserved comments and removes temporary markers.
 //!
+//! Picking the preservers by hand may be error prone, so [`preserve_and_parse_for`] infers them
+//! from the implementors that will mutate the AST. See [`Preserve`] for further details.
+//!
+//! Both functions work with files, but they're thin wrappers over [`preserve_and_parse_str`] and
+//! [`resolve_preserved_to_string`], which work with strings instead. They come in handy when the
+//! code is generated in memory and only written at the end, if written at all.
+//!
 //! # Terminology
 //!
 //! The `Preserver` type specifies which parts of the code should remain unchanged, those parts are
-//! called "preserved code". All the rest is considered non-preserved code.
+//! called "preserved code". All the rest is considered non-preserved code. A `Preserver`
+//! identifies the line starting the preserved code with a [`Lookup`], which may be a plain prefix,
+//! a regex or a syntax-aware description of an item, eg "the `impl` block of `MyTrait` for
+//! `MyStruct`".
 //!
 //! Preserved code is the code that will be parse in the AST in its original form, ie, it's
 //! preserved for the AST.
@@ -93,9 +103,41 @@ mod tests;
 use crate::Error;
 use regex::{Captures, Regex};
 use std::path::Path;
-use syn::File;
-use types::DelimitersCount;
-pub use types::Preserver;
+use syn::{parse_quote, Attribute, Expr, ExprLit, File, Lit, Meta, MetaNameValue};
+pub(crate) use types::SourceFormat;
+use types::{DelimitersCount, PreservedCode};
+pub use types::{Lookup, Preserver};
+
+/// This trait infers the preservers needed by an implementor, this is, the preservers covering the
+/// regions of the code that the implementor mutates when used with
+/// [`Mutator`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Mutator.html).
+/// Eg, an [`ItemToImpl`](https://docs.rs/rust_writer/latest/rust_writer/ast/implementors/struct.ItemToImpl.html)
+/// needs the whole `impl` block it targets to be preserved.
+///
+/// It's implemented by every implementor provided by this crate, by `Mutator` and by the wrappers
+/// generated by the [`#[mutator]`](https://docs.rs/rust_writer/latest/rust_writer/ast/attr.mutator.html)
+/// macro. It's also implemented by slices, so a list of implementors can be used as well, even if
+/// they're of different types (eg, `&[&dyn Preserve]`).
+///
+/// The inferred preservers are used by [`preserve_and_parse_for`] and
+/// [`preserve_and_parse_str_for`].
+pub trait Preserve {
+	/// The preservers needed by the implementor. Implementors which don't need any preserved code
+	/// return an empty `Vec`.
+	fn preservers(&self) -> Vec<Preserver<'_>>;
+}
+
+impl<T: Preserve + ?Sized> Preserve for &T {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		(**self).preservers()
+	}
+}
+
+impl<T: Preserve> Preserve for [T] {
+	fn preservers(&self) -> Vec<Preserver<'_>> {
+		self.iter().flat_map(Preserve::preservers).collect()
+	}
+}
 
 /// Reads the Rust source file at the given `code` path, applies the specified
 /// preservation strategies (via the list of [`Preserver`]), and parses the resulting
@@ -108,7 +150,12 @@ pub use types::Preserver;
 /// This function is useful to ensure that non-code elements such as comments,
 /// empty lines, and global attributes are not lost during parsing. By converting these parts
 /// into doc comment tokens, the overall source code structure is better preserved, which can
-/// simplify later processing and transformations.
+/// simplify later processing and transformations. Both line comments and block comments (even
+/// multi-line ones) are kept. Inside preserved code, a block comment sharing a line with code is
+/// kept if it starts the line, or if it ends the line after a `;` or a `}`. Other comments sharing
+/// a line with code, such as those between two tokens, are dropped by the parser.
+/// The shebang, the byte order mark, the line endings style (LF or CRLF) and whether the file ends
+/// by a newline are kept as well, and [`resolve_preserved`] restores them.
 ///
 /// # Non preservable code
 ///
@@ -138,7 +185,8 @@ pub use types::Preserver;
 /// }
 /// ```
 ///
-/// which is invalid Rust code, impossible to be parsed into an AST. A file containing this lines
+/// which is invalid Rust code, impossible to be parsed into an AST. The same happens with a block
+/// comment starting the line of a field, as it's kept in the same way. A file containing this lines
 /// would be deemed as "non preservable code" if those lines are preserved. But it's perfectly
 /// valid if those lines are non-preserved, so just pay attention to preserved code.
 ///
@@ -158,7 +206,7 @@ pub use types::Preserver;
 ///      builder.tempfile_path("non_preservable_file.rs").expect("This exists; qed;"),
 ///      &[&preserver1, &preserver2, &preserver3]
 ///   ),
-///   Err(Error::NonPreservableCode)
+///   Err(Error::NonPreservableCode { .. })
 ///  ));
 ///
 ///  assert!(rust_writer::preserver::preserve_and_parse(
@@ -168,8 +216,84 @@ pub use types::Preserver;
 /// });
 /// ```
 pub fn preserve_and_parse(code: &Path, preservers: &[&Preserver]) -> Result<File, Error> {
-	let preserved_code = apply_preservers(&std::fs::read_to_string(code)?, preservers);
-	syn::parse_file(&preserved_code).map_err(|_| Error::NonPreservableCode)
+	preserve_and_parse_str(&std::fs::read_to_string(code)?, preservers)
+}
+
+/// Same as [`preserve_and_parse`], but the source code is given as a string instead of being read
+/// from a file.
+///
+/// ```rust
+/// use rust_writer::preserver::Preserver;
+///
+/// let code = "// Some comment\nfn main() {\n\n    let a = 1;\n}\n";
+///
+/// let ast = rust_writer::preserver::preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+///     .expect("This should be Ok; qed;");
+///
+/// assert_eq!(
+///     rust_writer::preserver::resolve_preserved_to_string(&ast),
+///     "// Some comment\nfn main() {\n\n    let a = 1;\n}\n"
+/// );
+/// ```
+pub fn preserve_and_parse_str(code: &str, preservers: &[&Preserver]) -> Result<File, Error> {
+	let preserved_code = apply_preservers(code, preservers);
+	syn::parse_file(&preserved_code.code).map_err(|err| preserved_code.non_preservable(err))
+}
+
+/// Same as [`preserve_and_parse`], but the preservers are inferred from the implementors that will
+/// mutate the AST, so only the code they need is preserved. See [`Preserve`] for further details.
+///
+/// The inference only relies on the targets of the implementors, so it works at the top level
+/// of the file. Eg, a [`TokenStreamToMacro`](https://docs.rs/rust_writer/latest/rust_writer/ast/implementors/struct.TokenStreamToMacro.html)
+/// only preserves top level invocations of its macro. If the implementors target something nested
+/// inside other items, the preservers must be defined by hand.
+///
+/// ```rust
+/// use rust_writer::ast::{
+///     implementors::ItemToImpl,
+///     mutator::{Mutator, ToMutate},
+/// };
+/// use syn::parse_quote;
+/// use test_builder::TestBuilder;
+///
+/// TestBuilder::default().with_complete_file().execute(|builder| {
+///     let item_to_impl: ItemToImpl = (
+///         Some("MyTrait"),
+///         "MyStruct",
+///         parse_quote! {
+///             fn new_method(&self) -> bool {
+///                 true
+///             }
+///         },
+///     )
+///         .into();
+///
+///     // Only `impl MyTrait for MyStruct` is preserved.
+///     let mut ast = rust_writer::preserver::preserve_and_parse_for(
+///         builder.tempfile_path("complete_file.rs").expect("This exists; qed;"),
+///         &item_to_impl,
+///     )
+///     .expect("This should be Ok; qed;");
+///
+///     let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+///     assert!(mutator.mutate(&mut ast).is_ok());
+/// });
+/// ```
+pub fn preserve_and_parse_for<T: Preserve + ?Sized>(
+	code: &Path,
+	implementors: &T,
+) -> Result<File, Error> {
+	preserve_and_parse_str_for(&std::fs::read_to_string(code)?, implementors)
+}
+
+/// Same as [`preserve_and_parse_for`], but the source code is given as a string instead of being
+/// read from a file.
+pub fn preserve_and_parse_str_for<T: Preserve + ?Sized>(
+	code: &str,
+	implementors: &T,
+) -> Result<File, Error> {
+	let preservers = implementors.preservers();
+	preserve_and_parse_str(code, &preservers.iter().collect::<Vec<_>>())
 }
 
 /// Resolves a previously preserved and parsed AST back into source code and writes it to the
@@ -184,9 +308,25 @@ pub fn preserve_and_parse(code: &Path, preservers: &[&Preserver]) -> Result<File
 /// the AST back into source code. While this approach generally preserves the overall structure of
 /// the code, unparsing preserved declarative macro invocations (especially those that are
 /// complex) can sometimes lead to formatting differences from the original source. This is a
-/// well-known challenge in the Rust parsing ecosystem, and something to keep in mind.
+/// well-known challenge in the Rust parsing ecosystem, and something to keep in mind. If the
+/// rest of the file must be kept exactly as it was, have a look at the
+/// [`splicer`](https://docs.rs/rust_writer/latest/rust_writer/splicer/index.html) module.
 pub fn resolve_preserved(ast: &File, path: &Path) -> Result<(), Error> {
+	std::fs::write(path, resolve_preserved_to_string(ast))?;
+	Ok(())
+}
+
+/// Same as [`resolve_preserved`], but the resolved source code is returned instead of being
+/// written to a file.
+pub fn resolve_preserved_to_string(ast: &File) -> String {
 	let code = prettyplease::unparse(ast);
+	// The format of the original code is recorded by a ///TEMP_FORMAT doc comment, if it isn't the
+	// default one. Its marker is deleted together with the rest of markers.
+	let re = Regex::new(r"(?m)^[ \t]*///TEMP_FORMAT([^\n]*)\n").expect("The regex is valid; qed;");
+	let format = re
+		.captures(&code)
+		.map_or_else(SourceFormat::default, |caps| SourceFormat::from_flags(&caps[1]));
+	let code = re.replace_all(&code, "").to_string();
 	// Inside preserved declarative macros invocations, everything is a token so the doc
 	// comments became #[doc] in order to preserve them (tokens doesn't accept doc comments).
 	// ///TEMP_DOC comments became #[doc = "///TEMP_DOC"] which are 4 tokens in the AST. When the
@@ -194,54 +334,188 @@ pub fn resolve_preserved(ast: &File, path: &Path) -> Result<(), Error> {
 	// tokens, so to properly unpreserve them we can use regex.
 	// Attention, before TEMP_DOCs may appear some literal space character (\\s, \\t, \\n). They
 	// must be skipped to avoid having invalid rust code!
-	let re = Regex::new(r#"#\s*\[\s*doc\s*=\s*"TEMP_DOC([\\s\\t\\n]*)(.*?)"\s*\]"#)
-		.expect("The regex is valid; qed;");
+	let re = Regex::new(
+		r#"#\s*\[\s*doc\s*=\s*"TEMP_(?:DOC|INLINE_AFTER|INLINE_BEFORE[ \t]*\|)([\\s\\t\\n]*)(.*?)"\s*\]"#,
+	)
+	.expect("The regex is valid; qed;");
 	let code = re.replace_all(&code, |caps: &Captures| format!("\n{}\n", &caps[2])).to_string();
 	// Same happens with 'type temp_marker = ();'. This lines also delete them from everywhere, not
 	// just inside declarative macros
 	let re = Regex::new(r"(?m)^\s*type\s*temp_marker\s*=\s*\(\);[ \t]*\n?")
 		.expect("The regex is valid; qed;");
 	let code = re.replace_all(&code, "").to_string();
-	// Delete all TEMP_DOCS present in the rest of the code and return the result.
+	// Join the comments sharing a line with code back to that line.
+	let re = Regex::new(r"\n[ \t]*///TEMP_INLINE_AFTER([^\n]*)").expect("The regex is valid; qed;");
+	let code = re.replace_all(&code, "${1}").to_string();
+	let re = Regex::new(r"(?m)^[ \t]*///TEMP_INLINE_BEFORE([ \t]*)\|([^\n]*)\n[ \t]*")
+		.expect("The regex is valid; qed;");
+	let code = re.replace_all(&code, "${2}${1}").to_string();
+	// Delete all TEMP_DOCS present in the rest of the code and restore the original format.
 	let re = Regex::new(r"(?m)^\s*///TEMP_DOC").expect("The regex is valid; qed;");
-	let code = re.replace_all(&code, "").to_string();
+	format.apply(re.replace_all(&code, "").to_string())
+}
 
-	std::fs::write(path, &code)?;
+// The line of non-preserved code carried by `attr`, if it's one of the doc comments carrying them.
+fn non_preserved_line(attr: &Attribute) -> Option<String> {
+	match attr.meta {
+		Meta::NameValue(MetaNameValue {
+			ref path,
+			value: Expr::Lit(ExprLit { lit: Lit::Str(ref lit), .. }),
+			..
+		}) if path.is_ident("doc") => lit.value().strip_prefix("TEMP_DOC").map(str::to_owned),
+		_ => None,
+	}
+}
 
-	Ok(())
+/// Tells whether `attr` carries an empty line of non-preserved code.
+pub(crate) fn is_blank_line(attr: &Attribute) -> bool {
+	non_preserved_line(attr).is_some_and(|line| line.trim().is_empty())
 }
 
-fn apply_preservers(code: &str, preservers: &[&Preserver]) -> String {
+/// Takes from `attrs` the non-preserved code at the head of a file or module, this is, the leading
+/// comments, inner attributes and `extern crate` or `mod` declarations, so it can be attached to a
+/// new item inserted before the one carrying `attrs`. Comments just before the first line out of
+/// the head are left in place, as they likely document that line.
+pub(crate) fn take_non_preserved_head(attrs: &mut Vec<Attribute>) -> Vec<Attribute> {
+	let declaration = Regex::new(r"^(?:pub(?:\([^)]*\))?\s+)?(?:extern\s+crate|mod)\s[^{]*;$")
+		.expect("The regex is valid; qed;");
+	let mut head_len = 0;
+	for (index, line) in attrs.iter().map_while(non_preserved_line).enumerate() {
+		let line = line.trim();
+		// Comments and outer attributes belong to the head only if they precede a line of it.
+		let attached = line.starts_with("//") || (line.starts_with("#[") && line.ends_with(']'));
+		if line.is_empty() {
+			head_len = index;
+		} else if line.starts_with("//!") ||
+			(line.starts_with("#![") && line.ends_with(']')) ||
+			declaration.is_match(line)
+		{
+			head_len = index + 1;
+		} else if !attached {
+			break;
+		}
+	}
+
+	let mut head: Vec<Attribute> = attrs.drain(..head_len).collect();
+	// The blank line separating the head from the rest of the code also separates it from the new
+	// item.
+	if head_len > 0 && attrs.first().is_some_and(is_blank_line) {
+		head.push(parse_quote! { #[doc = "TEMP_DOC"] });
+	}
+	head
+}
+
+/// Takes from `attrs` the non-preserved code carried by an item about to be removed, so it can be
+/// attached to the item following it. The doc comments and outer attributes just before the
+/// removed item belong to it, so they're left in `attrs` and removed together with the item.
+pub(crate) fn take_non_preserved_code(attrs: &mut Vec<Attribute>) -> Vec<Attribute> {
+	let lines: Vec<String> = attrs.iter().map_while(non_preserved_line).collect();
+	// The index of the first line of the doc comments and attributes attached to the item, if any.
+	let mut attached_start = None;
+	let mut attribute = DelimitersCount::new();
+	for (index, line) in lines.iter().enumerate() {
+		let line = line.trim();
+		if !attribute.is_complete() {
+			attribute.count(line);
+		} else if line.starts_with("///") || (line.starts_with("#[") && !line.starts_with("#![")) {
+			attached_start.get_or_insert(index);
+			// Attributes may span several lines.
+			if line.starts_with("#[") {
+				attribute.count(line);
+			}
+		} else {
+			attached_start = None;
+		}
+	}
+
+	attrs.drain(..attached_start.unwrap_or(lines.len())).collect()
+}
+
+fn apply_preservers<'a>(code: &str, preservers: &[&'a Preserver]) -> PreservedCode<'a> {
+	let (format, code) = SourceFormat::detect(code);
+	let mut result = PreservedCode::default();
+
+	// syn keeps the shebang in the AST, so it's left untouched.
+	let (first_line, code) = match code.split_once('\n') {
+		Some((line, rest)) if is_shebang(line) => {
+			result.push_line(line.trim_end_matches('\r'), 0, None);
+			(1, rest)
+		},
+		None if is_shebang(code) => {
+			result.push_line(code, 0, None);
+			(1, "")
+		},
+		_ => (0, code),
+	};
+
+	result.append(apply_preservers_from(code, preservers, first_line, None));
+	result.push_format(&format, first_line + code.lines().count().saturating_sub(1));
+	result
+}
+
+// A shebang is a first line starting by `#!`, unless it's an inner attribute.
+fn is_shebang(line: &str) -> bool {
+	line.strip_prefix("#!").is_some_and(|rest| !rest.trim_start().starts_with('['))
+}
+
+// Applies the preservers to `code`, which starts at `first_line` of the original code and is
+// contained in the region preserved by `region`, if any.
+fn apply_preservers_from<'a>(
+	code: &str,
+	preservers: &[&'a Preserver],
+	first_line: usize,
+	region: Option<&'a Lookup<'a>>,
+) -> PreservedCode<'a> {
 	let mut delimiters_counts = DelimitersCount::new();
 
-	let mut lines = code.lines();
+	let mut lines = (first_line..).zip(code.lines());
 
-	// Non-preserved lines are pushed to the Vec in bundles, whule preserved lines are pushed
-	// together with a '\n' character, so the bound #lines * 2 is an upper
-	// bound of the final capacity (probably far from the real length of the Vec).
-	let mut result: Vec<String> = Vec::with_capacity(code.lines().count() * 2);
+	let mut result = PreservedCode::default();
+	let mut lookup = region;
 
-	while let Some(line) = lines.next() {
+	while let Some((line_number, line)) = lines.next() {
 		let trimmed_line = line.trim_start();
-		if let Some(index) = preservers
-			.iter()
-			.position(|preserver| trimmed_line.starts_with(preserver.lookup()))
+		// Lookups may look at the lines following the current one. `line` is a slice of `code`, so
+		// its position in `code` is given by their pointers.
+		let rest = &code[line.as_ptr() as usize - code.as_ptr() as usize..];
+		if let Some(index) =
+			preservers.iter().position(|preserver| preserver.lookup().matches(rest))
 		{
+			if delimiters_counts.is_complete() {
+				lookup = Some(preservers[index].lookup());
+			}
 			delimiters_counts.count(line);
-			result.push(line.to_owned());
-			result.push("\n".to_owned());
+			result.push_line(line, line_number, lookup);
+
+			let inner_preservers: Vec<&Preserver> = preservers[index].get_inners().iter().collect();
+			let mut last_header_line = line_number;
 
-			let inner_preserver = preservers[index].get_inner();
+			// If the header of the matched item spans several lines, all of them are preserved. The
+			// inner preservers only apply from the line opening the item body on.
+			if preservers[index].lookup().matches_header() || !inner_preservers.is_empty() {
+				let header_len = types::item_header(rest).lines().count();
+				for (header_line_number, line) in lines.by_ref().take(header_len.saturating_sub(1))
+				{
+					delimiters_counts.count(line);
+					result.push_line(line, header_line_number, lookup);
+					last_header_line = header_line_number;
+				}
+			}
 
-			if let Some(inner_preserver_pointer) = inner_preserver {
+			if !inner_preservers.is_empty() {
 				let mut inner_code = String::new();
-				for line in lines.by_ref() {
+				let inner_first_line = last_header_line + 1;
+				for (line_number, line) in lines.by_ref() {
 					delimiters_counts.count(line);
 
 					if delimiters_counts.is_complete() {
-						result.push(apply_preservers(&inner_code, &[inner_preserver_pointer]));
-						result.push(line.to_owned());
-						result.push("\n".to_owned());
+						result.append(apply_preservers_from(
+							&inner_code,
+							&inner_preservers,
+							inner_first_line,
+							lookup,
+						));
+						result.push_line(line, line_number, lookup);
 						break;
 					} else {
 						inner_code.push_str(line);
@@ -250,7 +524,45 @@ fn apply_preservers(code: &str, preservers: &[&Preserver]) -> String {
 				}
 			}
 		} else if delimiters_counts.is_complete() {
-			result.push(format!("///TEMP_DOC{}\n", line));
+			// A block comment may contain a lookup, so the whole comment is consumed at once to
+			// avoid preserving it.
+			let (comment_lines, _) = take_block_comment((line_number, line), &mut lines);
+			for (line_number, line) in comment_lines {
+				result.push_temp_doc(line, line_number, region);
+			}
+		} else if starts_block_comment(trimmed_line) {
+			let (comment_lines, comment_only) = take_block_comment((line_number, line), &mut lines);
+			let comment_end = block_comment_end(&comment_lines);
+			for (index, &(line_number, line)) in comment_lines.iter().enumerate() {
+				match comment_end {
+					Some(end) if !comment_only && index == comment_lines.len() - 1 => {
+						// Some code follows the comment, so the comment and the spaces before the
+						// code are moved to their own line, joined back to the code when it's
+						// resolved. They're blanked out in the code line to keep the original
+						// columns.
+						let code_start = end + (line[end..].len() - line[end..].trim_start().len());
+						let (comment, code) = line.split_at(code_start);
+						result.push_inline_before(comment.split_at(end), line_number, lookup);
+						result.push_marker(line_number, lookup);
+						let blank = " ".repeat(comment.chars().count());
+						push_code_line(
+							&mut result,
+							&format!("{}{}", blank, code),
+							line_number,
+							lookup,
+							&DelimitersCount::new(),
+						);
+					},
+					Some(_) => result.push_temp_doc(line, line_number, lookup),
+					// The comment isn't closed, so the lines are left for the parser to report.
+					None => result.push_line(line, line_number, lookup),
+				}
+				delimiters_counts.count(line);
+			}
+			if let (true, Some(&(line_number, _))) = (comment_only, comment_lines.last()) {
+				// Preserve block comments inside a non-preserved block
+				result.push_marker(line_number, lookup);
+			}
 		} else {
 			if (trimmed_line.starts_with("//") &&
 				!trimmed_line.starts_with("///") &&
@@ -260,20 +572,109 @@ fn apply_preservers(code: &str, preservers: &[&Preserver]) -> String {
 				// Preserve comments and global attributes.
 				// Global attributes may be hard to parse with syn, so we comment them to solve
 				// potential issues related to them.
-				result.push(format!("///TEMP_DOC{}\ntype temp_marker = ();\n", line));
+				result.push_temp_doc(line, line_number, lookup);
+				result.push_marker(line_number, lookup);
 			} else if trimmed_line.is_empty() {
 				// Preserve empty lines inside a non-preserved block
-				result.push("///TEMP_DOC\ntype temp_marker = ();\n".to_owned());
+				result.push_temp_doc("", line_number, lookup);
+				result.push_marker(line_number, lookup);
 			} else {
-				result.push(line.to_owned());
-				result.push("\n".to_owned());
+				push_code_line(&mut result, line, line_number, lookup, &delimiters_counts);
 			}
 
 			delimiters_counts.count(line);
 		}
 	}
 
-	result.push("type temp_marker = ();\n".to_owned());
+	result.push_marker(first_line + code.lines().count().saturating_sub(1), region);
+
+	result
+}
+
+// Pushes a line of code. If it ends by a block comment following a statement or a block, the
+// comment and the spaces before it are moved to their own line, joined back to the code when it's
+// resolved. `delimiters_counts` holds the lexer state before the line.
+fn push_code_line<'a>(
+	result: &mut PreservedCode<'a>,
+	line: &str,
+	line_number: usize,
+	lookup: Option<&'a Lookup<'a>>,
+	delimiters_counts: &DelimitersCount,
+) {
+	let comment_start = delimiters_counts
+		.trailing_block_comment(line)
+		.map(|start| line[..start].trim_end().len())
+		.filter(|&code_end| line[..code_end].ends_with([';', '}']));
+	match comment_start {
+		Some(code_end) => {
+			let (code, comment) = line.split_at(code_end);
+			result.push_line(code, line_number, lookup);
+			result.push_inline_after(comment.trim_end(), line_number, lookup);
+			result.push_marker(line_number, lookup);
+		},
+		None => result.push_line(line, line_number, lookup),
+	}
+}
+
+// Regular block comments are dropped by the parser, unlike block doc comments.
+fn starts_block_comment(trimmed_line: &str) -> bool {
+	trimmed_line.starts_with("/*") &&
+		(!trimmed_line.starts_with("/**") || trimmed_line.starts_with("/**/")) &&
+		!trimmed_line.starts_with("/*!")
+}
+
+// The byte offset, in the last of `comment_lines`, just after the end of the block comment they
+// start by. Block comments may be nested. Returns `None` if the comment isn't closed.
+fn block_comment_end(comment_lines: &[(usize, &str)]) -> Option<usize> {
+	let mut depth = 0usize;
+	for (_, line) in comment_lines {
+		let bytes = line.as_bytes();
+		let mut index = 0;
+		while index + 1 < bytes.len() {
+			match &bytes[index..index + 2] {
+				b"/*" => {
+					depth += 1;
+					index += 2;
+				},
+				b"*/" if depth > 0 => {
+					depth -= 1;
+					index += 2;
+					if depth == 0 {
+						return Some(index);
+					}
+				},
+				_ => index += 1,
+			}
+		}
+	}
+	None
+}
+
+// Takes the lines of the block comment starting at `line`, which may span several lines. If
+// `line` doesn't start a block comment, that line is the only one taken. The returned flag
+// indicates whether those lines only contain the comment.
+fn take_block_comment<'a>(
+	line: (usize, &'a str),
+	lines: &mut impl Iterator<Item = (usize, &'a str)>,
+) -> (Vec<(usize, &'a str)>, bool) {
+	if !starts_block_comment(line.1.trim_start()) {
+		return (vec![line], false);
+	}
+
+	let mut delimiters_counts = DelimitersCount::new();
+	let mut comment_lines = vec![line];
+	delimiters_counts.count(line.1);
+	while delimiters_counts.in_block_comment() {
+		match lines.next() {
+			Some(line) => {
+				delimiters_counts.count(line.1);
+				comment_lines.push(line);
+			},
+			None => break,
+		}
+	}
 
-	result.join("")
+	let comment_only = delimiters_counts == DelimitersCount::new() &&
+		comment_lines.last().is_some_and(|(_, line)| line.trim_end().ends_with("*/"));
+	(comment_lines, comment_only)
 }
@@ -2,6 +2,7 @@
 
 use super::*;
 use std::io::ErrorKind;
+use syn::{parse_quote, ImplItem, Item};
 use test_builder::TestBuilder;
 
 #[test]
@@ -27,7 +28,7 @@ fn apply_preservers_works() {
 
 			assert_eq!(
 				preserved_code,
-				apply_preservers(&code, &[&preserver1, &preserver2, &preserver3])
+				apply_preservers(&code, &[&preserver1, &preserver2, &preserver3]).code
 			);
 		});
 }
@@ -57,6 +58,30 @@ fn preserve_and_parse_works() {
 		});
 }
 
+#[test]
+fn preserve_and_parse_str_works() {
+	TestBuilder::default()
+		.with_complete_file()
+		.with_preserved_file_ast()
+		.execute(|builder| {
+			let code = std::fs::read_to_string(
+				builder.tempfile_path("complete_file.rs").expect("This exists; qed;"),
+			)
+			.expect("File should be readable");
+
+			let preserver1 = Preserver::new("struct MyStruct");
+			let mut preserver2 = Preserver::new("impl MyTrait for MyStruct");
+			preserver2.add_inners(&["fn trait_method"]);
+			let preserver3 = Preserver::new("fn main");
+
+			assert_eq!(
+				*builder.get_ref_ast_file("preserved_file.rs").expect("This exists; qed;"),
+				preserve_and_parse_str(&code, &[&preserver1, &preserver2, &preserver3])
+					.expect("This should be Ok; qed;")
+			);
+		});
+}
+
 #[test]
 fn preserve_and_parse_fails_if_path_not_readable() {
 	TestBuilder::default()
@@ -79,12 +104,16 @@ fn preserve_and_parse_fails_if_non_preservable_code() {
 		preserver2.add_inners(&["fn trait_method"]);
 		let preserver3 = Preserver::new("fn main");
 
+		let result = preserve_and_parse(
+			builder.tempfile_path("non_preservable_file.rs").expect("This exists; qed;"),
+			&[&preserver1, &preserver2, &preserver3],
+		);
+		assert!(matches!(result, Err(Error::NonPreservableCode { .. })));
+		#[cfg(feature = "span-locations")]
 		assert!(matches!(
-			preserve_and_parse(
-				builder.tempfile_path("non_preservable_file.rs").expect("This exists; qed;"),
-				&[&preserver1, &preserver2, &preserver3]
-			),
-			Err(Error::NonPreservableCode)
+			result,
+			Err(Error::NonPreservableCode { message, line: Some(23), column: None, lookup: Some(lookup) })
+			if lookup == "struct MyStruct" && !message.contains("type")
 		));
 	});
 }
@@ -103,7 +132,7 @@ fn resolve_preserved_works() {
 				builder.tempfile_path("resolved_file.rs").expect("This exists; qed;");
 
 			let expected_code =
-				std::fs::read_to_string(&resolved_file_path).expect("File should be readable");
+				std::fs::read_to_string(resolved_file_path).expect("File should be readable");
 
 			assert!(resolve_preserved(
 				builder.get_ref_ast_file("preserved_file.rs").expect("This exists; qed;"),
@@ -118,6 +147,26 @@ fn resolve_preserved_works() {
 		});
 }
 
+#[test]
+fn resolve_preserved_to_string_works() {
+	TestBuilder::default()
+		.with_resolved_file()
+		.with_preserved_file_ast()
+		.execute(|builder| {
+			let expected_code = std::fs::read_to_string(
+				builder.tempfile_path("resolved_file.rs").expect("This exists; qed;"),
+			)
+			.expect("File should be readable");
+
+			assert_eq!(
+				resolve_preserved_to_string(
+					builder.get_ref_ast_file("preserved_file.rs").expect("This exists; qed;")
+				),
+				expected_code
+			);
+		});
+}
+
 #[test]
 fn resolve_preserved_fails_if_path_not_writable() {
 	TestBuilder::default()
@@ -132,3 +181,356 @@ fn resolve_preserved_fails_if_path_not_writable() {
 				), Err(Error::IO(err)) if err.kind() == ErrorKind::PermissionDenied ));
 		});
 }
+
+#[test]
+fn block_comments_survive_round_trip() {
+	let code =
+		"/* Top level comment\n   fn main() {} */\nfn main() {\n    /* Inner comment */\n    \
+	            let a = 1;\n    /*\n     * Multi-line comment /* nested */\n     */\n    let b = \
+	            2;\n}\n\n/* Comment */\nfn other_func() {\n    /* Non-preserved comment */\n}\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn block_comments_followed_by_code_survive_round_trip() {
+	let code =
+		"fn main() {\n    /* Some comment */ let a = 1;\n    /*\n     * Multi-line /* nested \
+	            */\n     */   let b = 2;\n}\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn block_comments_following_code_survive_round_trip() {
+	let code = "fn main() {\n    let a = 1; /* Some comment */\n    if a == 1 {\n        let b = \
+	            \"/*\";\n    }  /* Some /* nested */ comment */\n}\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn block_comments_sharing_lines_with_code_survive_round_trip() {
+	let code =
+		"fn main() {\n    let a = 1; /* trailing */\n    /* lead */ let b = 2;\n    /* both \
+	            */ let c = 3; /* sides */\n}\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn block_comments_followed_by_code_cannot_be_preserved_where_items_arent_allowed() {
+	let code = "struct MyStruct {\n    /* Some comment */ field: u8,\n}\n";
+
+	assert!(matches!(
+		preserve_and_parse_str(code, &[&Preserver::new("struct MyStruct")]),
+		Err(Error::NonPreservableCode { message, .. })
+		if message.starts_with("comments and empty lines inside preserved code")
+	));
+}
+
+#[test]
+#[cfg(feature = "span-locations")]
+fn block_comments_followed_by_code_keep_the_code_location() {
+	let code = "fn main() {\n    /* Some comment */ let b = ;\n}\n";
+
+	assert!(matches!(
+		preserve_and_parse_str(code, &[&Preserver::new("fn main")]),
+		Err(Error::NonPreservableCode { message, line: Some(2), column: Some(31), .. })
+		if message == "expected an expression"
+	));
+}
+
+#[test]
+#[cfg(feature = "span-locations")]
+fn preserve_and_parse_str_fails_with_original_location() {
+	let code = "// Some comment\nfn main() {\n    let a = 1;\n    let b = ;\n}\n";
+
+	assert!(matches!(
+		preserve_and_parse_str(code, &[&Preserver::new("fn main")]),
+		Err(Error::NonPreservableCode { message, line: Some(4), column: Some(12), lookup: Some(lookup) })
+		if message == "expected an expression" && lookup == "fn main"
+	));
+}
+
+#[test]
+fn preserve_and_parse_str_works_with_syntactic_lookups() {
+	let code = "// Some comment\nimpl<T> MyTrait for MyStruct<T> {\n    // Inner comment\n    fn \
+	            some_func() {}\n}\n\npub(crate) fn main() {\n\n    let a = 1;\n}\n";
+
+	let preserver1 = Preserver::with_lookup(Lookup::Impl {
+		trait_name: Some("MyTrait".into()),
+		implementor_name: "MyStruct".into(),
+	});
+	let preserver2 =
+		Preserver::with_lookup(Lookup::Item { kind: "fn".into(), name: "main".into() });
+
+	let ast =
+		preserve_and_parse_str(code, &[&preserver1, &preserver2]).expect("This should be Ok; qed;");
+
+	assert_eq!(ast.items.len(), 3);
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn preserve_and_parse_str_preserves_sibling_inner_blocks() {
+	let code =
+		"impl X {\n    // Comment a\n    fn a() {}\n\n    fn b() {\n        // Comment b\n    \
+	            }\n\n    fn c() {\n        let _c = 1;\n    }\n}\n";
+
+	let mut preserver = Preserver::new("impl X");
+	preserver.add_inner(Preserver::new("fn a"));
+	preserver.add_inner(Preserver::new("fn b"));
+
+	let ast = preserve_and_parse_str(code, &[&preserver]).expect("This should be Ok; qed;");
+
+	let Item::Impl(ref item_impl) = ast.items[0] else {
+		panic!("The first item is the impl block; qed;");
+	};
+	let fn_names: Vec<String> = item_impl
+		.items
+		.iter()
+		.filter_map(|item| match item {
+			ImplItem::Fn(item_fn) => Some(item_fn.sig.ident.to_string()),
+			_ => None,
+		})
+		.collect();
+	assert_eq!(fn_names, ["a", "b"]);
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn preserve_and_parse_str_for_infers_preservers() {
+	use crate::ast::{
+		implementors::{ItemToFile, ItemToImpl},
+		mutator::{Mutator, ToMutate},
+	};
+
+	let code = "// Some comment\nstruct MyStruct;\n\nimpl MyTrait for MyStruct {\n    // Inner \
+	            comment\n    fn some_func() {}\n}\n\nfn main() {\n    let a = ;\n}\n";
+
+	let item_to_impl: ItemToImpl =
+		(Some("MyTrait"), "MyStruct", parse_quote! { fn other_func() {} }).into();
+	let item: Item = parse_quote! { fn main() {} };
+	let item_to_file: ItemToFile = item.into();
+	let implementors: [&dyn Preserve; 2] = [&item_to_impl, &item_to_file];
+
+	// `fn main` isn't valid code, but it isn't preserved as `item_to_file` just appends an item.
+	let mut ast =
+		preserve_and_parse_str_for(code, &implementors[..]).expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		resolve_preserved_to_string(&ast),
+		code.replace(
+			"    fn some_func() {}\n}",
+			"    fn some_func() {}\n    fn other_func() {}\n}"
+		)
+	);
+}
+
+#[test]
+fn preserve_and_parse_str_for_works_with_multi_line_signatures() {
+	use crate::ast::{
+		implementors::{StmtInsertion, StmtToFn},
+		mutator::{Mutator, ToMutate},
+	};
+
+	let code =
+		"// Some comment\npub fn build(\n    name: &str,\n    plugins: Vec<Plugin>,\n) -> App \
+	            {\n    // Create the app\n    App::new(name)\n}\n\nimpl<T> Builder<T>\nwhere\n    T: \
+	            Plugin,\n{\n    // Builds the app\n    pub fn build(\n        self,\n    ) -> App \
+	            {\n        App::new()\n    }\n}\n";
+
+	let stmt_to_fn =
+		StmtToFn::from(("build", parse_quote! { init(); })).with_insertion(StmtInsertion::Start);
+	let mut ast = preserve_and_parse_str_for(code, &stmt_to_fn).expect("This should be Ok; qed;");
+
+	let mut mutator = Mutator::default().to_mutate(&stmt_to_fn);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		resolve_preserved_to_string(&ast),
+		"// Some comment\npub fn build(name: &str, plugins: Vec<Plugin>) -> App {\n    init();\n    \
+		 // Create the app\n    App::new(name)\n}\n\nimpl<T> Builder<T>\nwhere\n    T: Plugin,\n{\n    \
+		 // Builds the app\n    pub fn build(self) -> App {\n        init();\n        \
+		 App::new()\n    }\n}\n"
+	);
+}
+
+#[test]
+fn preserve_and_parse_str_explains_non_preservable_comments() {
+	let code = "struct MyStruct {\n// Invalid comment\nfield1: i32,\n}\n";
+
+	let err = preserve_and_parse_str(code, &[&Preserver::new("struct MyStruct")])
+		.expect_err("The comment cannot be kept; qed;");
+	assert!(matches!(
+		err,
+		Error::NonPreservableCode { ref message, .. }
+		if message == "comments and empty lines inside preserved code can only be kept where an item \
+					   or a statement is allowed"
+	));
+
+	#[cfg(feature = "span-locations")]
+	assert!(err.to_string().contains("(line 2, preserved by `struct MyStruct`)"));
+	// The location is unknown, so it isn't reported.
+	#[cfg(not(feature = "span-locations"))]
+	assert!(!err.to_string().contains("(line"));
+}
+
+#[test]
+fn preserve_and_parse_str_reports_errors_of_preserved_code_itself() {
+	let code = "fn main() {\n    // Some comment\n    let a = ;\n}\n";
+
+	assert!(matches!(
+		preserve_and_parse_str(code, &[&Preserver::new("fn main")]),
+		Err(Error::NonPreservableCode { message, .. }) if message == "expected an expression"
+	));
+}
+
+#[test]
+fn preserve_and_parse_str_for_fails_if_mutated_code_isnt_preservable() {
+	use crate::ast::implementors::{ItemToFile, MutationMode};
+
+	let code = "fn main() {\n    let a = ;\n}\n";
+
+	let item: Item = parse_quote! { fn main() {} };
+	let item_to_file = ItemToFile::from(item).with_mode(MutationMode::Replace);
+
+	let result = preserve_and_parse_str_for(code, &item_to_file);
+	assert!(matches!(result, Err(Error::NonPreservableCode { .. })));
+	#[cfg(feature = "span-locations")]
+	assert!(matches!(
+		result,
+		Err(Error::NonPreservableCode { line: Some(2), lookup: Some(lookup), .. }) if lookup == "fn main"
+	));
+}
+
+#[test]
+fn preserve_and_parse_str_keeps_crlf_line_endings() {
+	let code =
+		"// Some comment\r\nfn main() {\r\n    // Inner comment\r\n\r\n    let a = 1;\r\n}\r\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn preserve_and_parse_str_keeps_byte_order_mark() {
+	let code = "\u{feff}fn main() {\n    // Inner comment\n    let a = 1;\n}\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	// The lookup matches the first line despite the byte order mark.
+	assert!(matches!(ast.items[0], Item::Fn(_)));
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn preserve_and_parse_str_keeps_shebang() {
+	let code = "#!/usr/bin/env rust-script\n#![allow(unused)]\nfn main() {\n\n    let a = \
+	            1;\n}\n";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(ast.shebang.as_deref(), Some("#!/usr/bin/env rust-script"));
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn preserve_and_parse_str_keeps_missing_final_newline() {
+	let code = "// Some comment\nfn main() {\n    let a = 1;\n}";
+
+	let ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+
+	assert_eq!(resolve_preserved_to_string(&ast), code);
+}
+
+#[test]
+fn preserve_and_parse_str_keeps_combined_format() {
+	let code =
+		"\u{feff}#!/usr/bin/env rust-script\r\n// Some comment\r\nfn main() {\r\n    let a = \
+	            1;\r\n}";
+
+	let mut ast = preserve_and_parse_str(code, &[&Preserver::new("fn main")])
+		.expect("This should be Ok; qed;");
+	ast.items.push(parse_quote! { fn other_func() {} });
+
+	assert_eq!(
+		resolve_preserved_to_string(&ast),
+		"\u{feff}#!/usr/bin/env rust-script\r\n// Some comment\r\nfn main() {\r\n    let a = \
+		 1;\r\n}\r\nfn other_func() {}"
+	);
+}
+
+#[test]
+fn preserve_and_parse_str_preserves_multi_line_headers() {
+	let code = "// Some comment\nimpl<T> Tr for S<T>\nwhere\n    T: Clone,\n{\n    // Inner \
+	            comment\n    pub fn build(\n        name: &str,\n    ) -> Self {\n        \
+	            Self::new(name)\n    }\n\n    fn other() {}\n}\n";
+
+	let mut preserver = Preserver::with_lookup(Lookup::Impl {
+		trait_name: Some("Tr".into()),
+		implementor_name: "S".into(),
+	});
+	preserver.add_inner_lookups(vec![Lookup::Item { kind: "fn".into(), name: "build".into() }]);
+
+	let ast = preserve_and_parse_str(code, &[&preserver]).expect("This should be Ok; qed;");
+
+	let Item::Impl(ref item_impl) = ast.items[0] else {
+		panic!("The impl block is preserved; qed;");
+	};
+	assert!(item_impl.generics.where_clause.is_some());
+	assert!(
+		matches!(item_impl.items[0], ImplItem::Fn(ref item_fn) if item_fn.sig.ident == "build")
+	);
+	assert_eq!(
+		resolve_preserved_to_string(&ast),
+		"// Some comment\nimpl<T> Tr for S<T>\nwhere\n    T: Clone,\n{\n    // Inner comment\n    pub fn \
+		 build(name: &str) -> Self {\n        Self::new(name)\n    }\n\n    fn other() {}\n}\n"
+	);
+}
+
+#[test]
+fn take_non_preserved_head_works() {
+	let temp_docs = |lines: &[&str]| -> Vec<Attribute> {
+		lines
+			.iter()
+			.map(|line| {
+				let doc = format!("TEMP_DOC{}", line);
+				parse_quote! { #[doc = #doc] }
+			})
+			.collect()
+	};
+
+	let mut attrs =
+		temp_docs(&["// Header", "", "#![no_std]", "mod a;", "", "// Docs", "fn a() {}"]);
+	let head = take_non_preserved_head(&mut attrs);
+	assert_eq!(head[..4], temp_docs(&["// Header", "", "#![no_std]", "mod a;"]));
+	assert_eq!(head[4..], temp_docs(&[""]));
+	assert_eq!(attrs, temp_docs(&["", "// Docs", "fn a() {}"]));
+
+	// Comments just before the code stay with it.
+	let mut attrs = temp_docs(&["// Docs", "fn a() {}"]);
+	assert!(take_non_preserved_head(&mut attrs).is_empty());
+	assert_eq!(attrs.len(), 2);
+}
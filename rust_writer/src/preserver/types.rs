@@ -3,6 +3,16 @@
 #[cfg(test)]
 mod tests;
 
+use crate::{ast::implementors::helpers::Identity, Error};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+	borrow::Cow,
+	fmt::{Display, Formatter, Result as FmtResult},
+};
+use syn::{Item, Type};
+
 /// The `Preserver` type specifies which fragments of code should be preserved. It uses a lookup
 /// that identifies the beginning of a line that should be preserved. If such a line doesn't start
 /// a new block (eg, `let something = false;`), that line is the only preserved thing by this type.
@@ -10,7 +20,7 @@ mod tests;
 ///
 /// It's possible to preserve only a part of a preserved block, while keeping that block itself
 /// preserved by using inner preservers, that is, a `Preserver` contained inside another one (and so
-/// on).
+/// on). A `Preserver` may contain several inner preservers, forming a tree.
 ///
 /// ```no_compile
 /// trait External{
@@ -38,61 +48,669 @@ mod tests;
 /// preserver.add_inners(&["fn inner_function", "fn super_inner_function"]);
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Preserver<'a> {
-	lookup: &'a str,
-	inner: Option<Box<Preserver<'a>>>,
+	lookup: Lookup<'a>,
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+	inners: Vec<Preserver<'a>>,
 }
 
 impl<'a> Preserver<'a> {
-	/// Creates a new preserver using the provided lookup.  
-	pub fn new(lookup: &'a str) -> Self {
-		Self { lookup, inner: None }
+	/// Creates a new preserver using the provided lookup as a [`Lookup::Prefix`]. The lookup may be
+	/// either borrowed or owned.
+	pub fn new(lookup: impl Into<Cow<'a, str>>) -> Self {
+		Self::with_lookup(Lookup::Prefix(lookup.into()))
+	}
+
+	/// Creates a new preserver using any kind of [`Lookup`].
+	pub fn with_lookup(lookup: Lookup<'a>) -> Self {
+		Self { lookup, inners: Vec::new() }
 	}
 
 	/// Add inner preservers, in order to preserve just an inner block of a preserved block, keeping
 	/// the outer block itself preserved. Inner preserver will be composed sequentially as they are
-	/// defined in the input slice.
+	/// defined in the input slice. Calling this method several times adds several chains of inner
+	/// preservers, which are siblings.
 	pub fn add_inners(&mut self, lookups: &[&'a str]) {
-		let mut current = self;
-		for lookup in lookups {
-			current.inner = Some(Box::new(Self::new(lookup)));
-			current = current.inner.as_mut().expect("Inner is Some due to the previous line; qed");
+		self.add_inner_lookups(
+			lookups.iter().map(|lookup| Lookup::Prefix(Cow::Borrowed(*lookup))).collect(),
+		);
+	}
+
+	/// Same as [`add_inners`](#method.add_inners), but using any kind of [`Lookup`].
+	pub fn add_inner_lookups(&mut self, lookups: Vec<Lookup<'a>>) {
+		if let Some(inner) = lookups.into_iter().rev().fold(None, |inner, lookup| {
+			let mut preserver = Self::with_lookup(lookup);
+			preserver.inners.extend(inner);
+			Some(preserver)
+		}) {
+			self.inners.push(inner);
 		}
 	}
 
+	/// Add an inner preserver, which is a sibling of the inner preservers already added. This way,
+	/// several blocks inside the same preserved block can be preserved, forming a tree of
+	/// preservers.
+	///
+	/// ```no_compile
+	/// // This preserves fn a and fn b inside the impl block, but nothing else inside it.
+	/// let mut preserver = Preserver::new("impl X");
+	/// preserver.add_inner(Preserver::new("fn a"));
+	/// preserver.add_inner(Preserver::new("fn b"));
+	/// ```
+	pub fn add_inner(&mut self, inner: Preserver<'a>) {
+		self.inners.push(inner);
+	}
+
 	/// Gets the lookup for a `Preserver`.
-	pub fn lookup(&self) -> &str {
-		self.lookup
+	pub fn lookup(&self) -> &Lookup<'a> {
+		&self.lookup
+	}
+
+	/// Gets the first inner preserver for a `Preserver`, if any.
+	pub fn get_inner(&self) -> Option<&Preserver<'a>> {
+		self.inners.first()
+	}
+
+	/// Gets all the inner preservers for a `Preserver`.
+	pub fn get_inners(&self) -> &[Preserver<'a>] {
+		&self.inners
 	}
 
-	/// Gets the outermost inner preserver for a `Preserver`, if any.
-	pub fn get_inner(&self) -> Option<&Preserver> {
-		self.inner.as_deref()
+	/// Converts the preserver and all its inner preservers into owned ones, which don't borrow
+	/// anything. Owned preservers can be stored in long-lived structs or sent across threads.
+	pub fn into_owned(self) -> Preserver<'static> {
+		Preserver {
+			lookup: self.lookup.into_owned(),
+			inners: self.inners.into_iter().map(Preserver::into_owned).collect(),
+		}
 	}
 }
 
+/// The way a [`Preserver`] identifies the line starting the code to preserve. Lookups are always
+/// matched against the line without its leading whitespace. `Impl` and `Item` lookups take into
+/// account the whole header of the item starting at that line, even if it spans several lines, eg
+/// a function whose arguments are split across lines or an `impl` block followed by a `where`
+/// clause.
+///
+/// ```rust
+/// use regex::Regex;
+/// use rust_writer::preserver::Lookup;
+///
+/// let line = "impl<T>  MyTrait for MyStruct<T> {";
+///
+/// assert!(!Lookup::Prefix("impl MyTrait for MyStruct".into()).matches(line));
+/// assert!(Lookup::Regex(Regex::new(r"impl<\w+>\s+MyTrait").expect("Valid regex; qed;")).matches(line));
+/// assert!(Lookup::Impl { trait_name: Some("MyTrait".into()), implementor_name: "MyStruct".into() }.matches(line));
+/// assert!(!Lookup::Impl { trait_name: None, implementor_name: "MyStruct".into() }.matches(line));
+///
+/// assert!(Lookup::Item { kind: "fn".into(), name: "main".into() }.matches("pub(crate) fn main() {"));
+/// assert!(Lookup::Item { kind: "fn".into(), name: "build".into() }
+///     .matches("pub fn build(\n    name: &str,\n) -> Self {\n    Self::new(name)\n}"));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum Lookup<'a> {
+	/// The line starts with the given string.
+	Prefix(Cow<'a, str>),
+	/// The regex matches at the beginning of the line. It's (de)serialized as its pattern.
+	Regex(#[cfg_attr(feature = "serde", serde(with = "regex_serde"))] Regex),
+	/// The line starts an `impl` block for the given implementor, implementing the given trait if
+	/// any. Generics, visibility and spacing don't matter, eg `impl<T> MyTrait for MyStruct<T> {`
+	/// is matched by `trait_name: Some("MyTrait"), implementor_name: "MyStruct"`.
+	Impl { trait_name: Option<Cow<'a, str>>, implementor_name: Cow<'a, str> },
+	/// The line starts an item of the given kind (eg `fn`, `struct`, `trait`, `mod`...) with the
+	/// given name. As for `Impl`, generics, visibility and spacing don't matter.
+	Item { kind: Cow<'a, str>, name: Cow<'a, str> },
+}
+
+impl Lookup<'_> {
+	/// Converts the lookup into an owned one, which doesn't borrow anything.
+	pub fn into_owned(self) -> Lookup<'static> {
+		match self {
+			Self::Prefix(prefix) => Lookup::Prefix(Cow::Owned(prefix.into_owned())),
+			Self::Regex(regex) => Lookup::Regex(regex),
+			Self::Impl { trait_name, implementor_name } => Lookup::Impl {
+				trait_name: trait_name.map(|trait_name| Cow::Owned(trait_name.into_owned())),
+				implementor_name: Cow::Owned(implementor_name.into_owned()),
+			},
+			Self::Item { kind, name } => Lookup::Item {
+				kind: Cow::Owned(kind.into_owned()),
+				name: Cow::Owned(name.into_owned()),
+			},
+		}
+	}
+
+	/// Tells whether the lookup matches the code starting at a line. `Prefix` and `Regex` lookups
+	/// only look at that line, while `Impl` and `Item` lookups look at the header of the item
+	/// starting there, which may span the following lines of `code`.
+	pub fn matches(&self, code: &str) -> bool {
+		// Only the leading whitespace of the line is skipped, an empty line doesn't match anything.
+		let code = code.trim_start_matches([' ', '\t']);
+		let line = code.lines().next().unwrap_or_default();
+		match self {
+			Self::Prefix(prefix) => line.starts_with(prefix.as_ref()),
+			Self::Regex(regex) => regex.find(line).is_some_and(|found| found.start() == 0),
+			Self::Impl { trait_name, implementor_name } => {
+				if !line.contains("impl") {
+					return false;
+				}
+				let header = item_header(code);
+				if !header.contains(implementor_name.as_ref()) {
+					return false;
+				}
+				let Some(Item::Impl(item_impl)) = parse_item_header(header) else {
+					return false;
+				};
+				let trait_matches = match (trait_name, item_impl.trait_) {
+					(Some(trait_name), Some((_, path, _))) => path
+						.segments
+						.last()
+						.is_some_and(|segment| segment.ident == trait_name.as_ref()),
+					(None, None) => true,
+					_ => false,
+				};
+				trait_matches && last_segment_is(&item_impl.self_ty, implementor_name)
+			},
+			Self::Item { kind, name } =>
+				line.contains(name.as_ref()) &&
+					parse_item_header(item_header(code)).is_some_and(|item| {
+						item.identity().is_some_and(|(item_kind, ident)| {
+							item_kind == kind.as_ref() && ident == name.as_ref()
+						})
+					}),
+		}
+	}
+
+	/// Whether the lookup matches the whole header of an item, which may span several lines.
+	pub(crate) fn matches_header(&self) -> bool {
+		matches!(self, Self::Impl { .. } | Self::Item { .. })
+	}
+}
+
+impl Display for Lookup<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+		match self {
+			Self::Prefix(prefix) => write!(f, "{}", prefix),
+			Self::Regex(regex) => write!(f, "{}", regex.as_str()),
+			Self::Impl { trait_name: Some(trait_name), implementor_name } =>
+				write!(f, "impl {} for {}", trait_name, implementor_name),
+			Self::Impl { trait_name: None, implementor_name } =>
+				write!(f, "impl {}", implementor_name),
+			Self::Item { kind, name } => write!(f, "{} {}", kind, name),
+		}
+	}
+}
+
+// Regex doesn't implement `PartialEq`, so regex lookups are compared by their pattern.
+impl PartialEq for Lookup<'_> {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Prefix(a), Self::Prefix(b)) => a == b,
+			(Self::Regex(a), Self::Regex(b)) => a.as_str() == b.as_str(),
+			(
+				Self::Impl { trait_name: a_trait, implementor_name: a_implementor },
+				Self::Impl { trait_name: b_trait, implementor_name: b_implementor },
+			) => a_trait == b_trait && a_implementor == b_implementor,
+			(
+				Self::Item { kind: a_kind, name: a_name },
+				Self::Item { kind: b_kind, name: b_name },
+			) => a_kind == b_kind && a_name == b_name,
+			_ => false,
+		}
+	}
+}
+
+impl PartialEq<str> for Lookup<'_> {
+	fn eq(&self, other: &str) -> bool {
+		matches!(self, Self::Prefix(prefix) if prefix == other)
+	}
+}
+
+#[cfg(feature = "serde")]
+mod regex_serde {
+	use regex::Regex;
+	use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+	pub(super) fn serialize<S: Serializer>(
+		regex: &Regex,
+		serializer: S,
+	) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(regex.as_str())
+	}
+
+	pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+		deserializer: D,
+	) -> Result<Regex, D::Error> {
+		Regex::new(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+	}
+}
+
+/// The header of the item starting `code`, this is, its lines up to the one opening its body, or
+/// up to the one ending the item if it has no body.
+pub(crate) fn item_header(code: &str) -> &str {
+	let mut delimiters_counts = DelimitersCount::new();
+	let mut end = 0;
+	for line in code.split_inclusive('\n') {
+		end += line.len();
+		delimiters_counts.count(line);
+		if delimiters_counts.has_opened_brace() || delimiters_counts.is_terminated() {
+			break;
+		}
+	}
+	&code[..end]
+}
+
+// Parses the item started by a header, replacing its body by an empty one if the header opens it.
+fn parse_item_header(header: &str) -> Option<Item> {
+	match header.find('{') {
+		Some(index) => syn::parse_str(&format!("{}{{}}", &header[..index])).ok(),
+		None => syn::parse_str(header).ok(),
+	}
+}
+
+fn last_segment_is(ty: &Type, name: &str) -> bool {
+	matches!(ty, Type::Path(type_path)
+		if type_path.path.segments.last().is_some_and(|segment| segment.ident == name))
+}
+
+// Where the lexer stands when a line ends, as literals and block comments may span several lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LexState {
+	Code,
+	// Block comments may be nested, so their depth is tracked.
+	BlockComment(usize),
+	// String, byte string and C string literals.
+	Str,
+	// Raw string literals, with the number of `#` delimiting them.
+	RawStr(usize),
+}
+
+/// Counts the delimiters found in the code line by line, skipping those inside comments and
+/// string, byte string, raw string and char literals.
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct DelimitersCount {
-	counts: [u8; 6],
+	counts: [usize; 6],
+	state: LexState,
+	// Whether a `;` has been found outside any delimiter.
+	terminated: bool,
 }
 
 impl DelimitersCount {
 	pub(crate) fn new() -> Self {
-		Self { counts: [0; 6] }
+		Self { counts: [0; 6], state: LexState::Code, terminated: false }
 	}
 
 	pub(crate) fn is_complete(&self) -> bool {
 		self.counts[0] == self.counts[1] && // `{` and `}`
         self.counts[2] == self.counts[3] && // `(` and `)`
-        self.counts[4] == self.counts[5] // `[` and `]`
+        self.counts[4] == self.counts[5] && // `[` and `]`
+        self.state == LexState::Code
+	}
+
+	pub(crate) fn has_opened_brace(&self) -> bool {
+		self.counts[0] > 0
+	}
+
+	pub(crate) fn is_terminated(&self) -> bool {
+		self.terminated
+	}
+
+	pub(crate) fn in_block_comment(&self) -> bool {
+		matches!(self.state, LexState::BlockComment(_))
+	}
+
+	/// The byte offset where the block comment ending `line` starts, if some code precedes it on
+	/// that line. `self` holds the lexer state before `line`.
+	pub(crate) fn trailing_block_comment(&self, line: &str) -> Option<usize> {
+		let line = line.trim_end();
+		if !line.ends_with("*/") {
+			return None;
+		}
+		line.rmatch_indices("/*").map(|(start, _)| start).find(|&start| {
+			let mut before = self.clone();
+			before.count(&line[..start]);
+			// The comment must only be closed by the `*/` ending the line.
+			let mut comment = DelimitersCount::new();
+			comment.count(&line[start..line.len() - 2]);
+			before.state == LexState::Code &&
+				!line[..start].trim().is_empty() &&
+				comment.state == LexState::BlockComment(1)
+		})
 	}
 
 	pub(crate) fn count(&mut self, line: &str) {
-		self.counts[0] += line.matches('{').count() as u8;
-		self.counts[1] += line.matches('}').count() as u8;
-		self.counts[2] += line.matches('(').count() as u8;
-		self.counts[3] += line.matches(')').count() as u8;
-		self.counts[4] += line.matches('[').count() as u8;
-		self.counts[5] += line.matches(']').count() as u8;
+		let chars: Vec<char> = line.chars().collect();
+		let mut index = 0;
+		while index < chars.len() {
+			index = match self.state {
+				LexState::Code => self.count_code(&chars, index),
+				LexState::BlockComment(depth) => match (chars[index], chars.get(index + 1)) {
+					('/', Some('*')) => {
+						self.state = LexState::BlockComment(depth + 1);
+						index + 2
+					},
+					('*', Some('/')) => {
+						self.state = match depth {
+							1 => LexState::Code,
+							_ => LexState::BlockComment(depth - 1),
+						};
+						index + 2
+					},
+					_ => index + 1,
+				},
+				LexState::Str => match chars[index] {
+					'\\' => index + 2,
+					'"' => {
+						self.state = LexState::Code;
+						index + 1
+					},
+					_ => index + 1,
+				},
+				LexState::RawStr(hashes) => {
+					let closes = chars[index] == '"' &&
+						chars
+							.get(index + 1..index + 1 + hashes)
+							.is_some_and(|closing| closing.iter().all(|&c| c == '#'));
+					if closes {
+						self.state = LexState::Code;
+						index + 1 + hashes
+					} else {
+						index + 1
+					}
+				},
+			};
+		}
+	}
+
+	// Counts the token starting at `index`, returning the index where the next token starts.
+	fn count_code(&mut self, chars: &[char], index: usize) -> usize {
+		match (chars[index], chars.get(index + 1)) {
+			('/', Some('/')) => chars.len(),
+			('/', Some('*')) => {
+				self.state = LexState::BlockComment(1);
+				index + 2
+			},
+			('"', _) => {
+				self.state = LexState::Str;
+				index + 1
+			},
+			('\'', _) => skip_char_or_lifetime(chars, index),
+			(c, _) if c.is_alphabetic() || c == '_' => {
+				let end = index +
+					chars[index..]
+						.iter()
+						.take_while(|c| c.is_alphanumeric() || **c == '_')
+						.count();
+				let prefix: String = chars[index..end].iter().collect();
+				match (prefix.as_str(), chars.get(end)) {
+					("b" | "c", Some('"')) => {
+						self.state = LexState::Str;
+						end + 1
+					},
+					("b", Some('\'')) => skip_char_or_lifetime(chars, end),
+					("r" | "br" | "cr", Some('"' | '#')) => {
+						let hashes = chars[end..].iter().take_while(|&&c| c == '#').count();
+						// Otherwise, it's a raw identifier.
+						if chars.get(end + hashes) == Some(&'"') {
+							self.state = LexState::RawStr(hashes);
+							end + hashes + 1
+						} else {
+							end
+						}
+					},
+					_ => end,
+				}
+			},
+			(';', _) => {
+				self.terminated |= self.is_complete();
+				index + 1
+			},
+			(delimiter, _) => {
+				if let Some(position) =
+					['{', '}', '(', ')', '[', ']'].iter().position(|&c| c == delimiter)
+				{
+					self.counts[position] += 1;
+				}
+				index + 1
+			},
+		}
+	}
+}
+
+/// The details of the source code layout that the AST cannot keep: the byte order mark, the line
+/// endings and whether the code ends with a newline. If they aren't the default ones, they're
+/// recorded in the preserved code by a doc comment starting by ///TEMP_FORMAT, so they can be
+/// restored when the code is resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct SourceFormat {
+	bom: bool,
+	crlf: bool,
+	missing_final_newline: bool,
+}
+
+impl SourceFormat {
+	/// Detects the format of `code`, returning it together with the code without byte order mark.
+	pub(crate) fn detect(code: &str) -> (Self, &str) {
+		let (bom, code) = match code.strip_prefix('\u{feff}') {
+			Some(code) => (true, code),
+			None => (false, code),
+		};
+		// The first line ending sets the style.
+		let crlf = code.find('\n').is_some_and(|index| code[..index].ends_with('\r'));
+		let missing_final_newline = !code.is_empty() && !code.ends_with('\n');
+		(Self { bom, crlf, missing_final_newline }, code)
+	}
+
+	/// The line ending used by the code.
+	#[cfg(feature = "span-locations")]
+	pub(crate) fn line_ending(&self) -> &'static str {
+		if self.crlf {
+			"\r\n"
+		} else {
+			"\n"
+		}
+	}
+
+	fn flags(&self) -> Vec<&'static str> {
+		[(self.bom, "bom"), (self.crlf, "crlf"), (self.missing_final_newline, "no_final_newline")]
+			.into_iter()
+			.filter_map(|(set, flag)| set.then_some(flag))
+			.collect()
+	}
+
+	/// Reads the format from the flags recorded by the ///TEMP_FORMAT doc comment.
+	pub(crate) fn from_flags(flags: &str) -> Self {
+		let flags: Vec<&str> = flags.split_whitespace().collect();
+		Self {
+			bom: flags.contains(&"bom"),
+			crlf: flags.contains(&"crlf"),
+			missing_final_newline: flags.contains(&"no_final_newline"),
+		}
+	}
+
+	/// Applies the format to some code using `\n` line endings and ending by a newline.
+	pub(crate) fn apply(&self, code: String) -> String {
+		let code = match code.strip_suffix('\n') {
+			Some(stripped) if self.missing_final_newline => stripped.to_owned(),
+			_ => code,
+		};
+		let code = if self.crlf { code.replace('\n', "\r\n") } else { code };
+		if self.bom {
+			format!("\u{feff}{}", code)
+		} else {
+			code
+		}
+	}
+}
+
+// Where a line of the preserved code comes from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LineOrigin<'a> {
+	// The line of the original code, 0-indexed.
+	line: usize,
+	// The length of the prefix added in front of the original line. It's `None` for the lines
+	// added by the preservation process, such as markers.
+	prefix_len: Option<usize>,
+	// The lookup of the preserver whose region contains the line, if any.
+	lookup: Option<&'a Lookup<'a>>,
+}
+
+/// The code resulting from applying some preservers, keeping track of where each of its lines
+/// comes from in the original code.
+#[derive(Debug, Default)]
+pub(crate) struct PreservedCode<'a> {
+	pub(crate) code: String,
+	origins: Vec<LineOrigin<'a>>,
+}
+
+impl<'a> PreservedCode<'a> {
+	pub(crate) fn push_line(&mut self, line: &str, origin: usize, lookup: Option<&'a Lookup<'a>>) {
+		self.code.push_str(line);
+		self.code.push('\n');
+		self.origins.push(LineOrigin { line: origin, prefix_len: Some(0), lookup });
+	}
+
+	pub(crate) fn push_temp_doc(
+		&mut self,
+		line: &str,
+		origin: usize,
+		lookup: Option<&'a Lookup<'a>>,
+	) {
+		self.push_prefixed("///TEMP_DOC", line, origin, lookup);
+	}
+
+	/// Pushes the part of a line of code found before the code, split into the comment and the
+	/// spaces separating it from the code. It's joined back to the next line of code when the code
+	/// is resolved.
+	pub(crate) fn push_inline_before(
+		&mut self,
+		(comment, spaces): (&str, &str),
+		origin: usize,
+		lookup: Option<&'a Lookup<'a>>,
+	) {
+		// Doc comments lose their trailing spaces, so the spaces are recorded first.
+		let part = format!("{}|{}", spaces, comment);
+		self.push_prefixed("///TEMP_INLINE_BEFORE", &part, origin, lookup);
+	}
+
+	/// Pushes the part of a line of code found after the code, which is joined back to the previous
+	/// line of code when the code is resolved.
+	pub(crate) fn push_inline_after(
+		&mut self,
+		part: &str,
+		origin: usize,
+		lookup: Option<&'a Lookup<'a>>,
+	) {
+		self.push_prefixed("///TEMP_INLINE_AFTER", part, origin, lookup);
+	}
+
+	fn push_prefixed(
+		&mut self,
+		prefix: &str,
+		line: &str,
+		origin: usize,
+		lookup: Option<&'a Lookup<'a>>,
+	) {
+		self.code.push_str(prefix);
+		self.push_line(line, origin, lookup);
+		if let Some(last) = self.origins.last_mut() {
+			last.prefix_len = Some(prefix.len());
+		}
+	}
+
+	pub(crate) fn push_marker(&mut self, origin: usize, lookup: Option<&'a Lookup<'a>>) {
+		self.code.push_str("type temp_marker = ();\n");
+		self.origins.push(LineOrigin { line: origin, prefix_len: None, lookup });
+	}
+
+	/// Records the format of the original code if it isn't the default one.
+	pub(crate) fn push_format(&mut self, format: &SourceFormat, origin: usize) {
+		let flags = format.flags();
+		if !flags.is_empty() {
+			self.code.push_str(&format!("///TEMP_FORMAT {}\n", flags.join(" ")));
+			self.origins.push(LineOrigin { line: origin, prefix_len: None, lookup: None });
+			self.push_marker(origin, None);
+		}
+	}
+
+	pub(crate) fn append(&mut self, other: PreservedCode<'a>) {
+		self.code.push_str(&other.code);
+		self.origins.extend(other.origins);
+	}
+
+	/// Builds the error returned when the preserved code cannot be parsed, mapping the error
+	/// location back to the original code.
+	///
+	/// The parser messages may refer to the doc comments and markers added by the preservation
+	/// process instead of the original code. If the code parses without them, they're the culprits,
+	/// so the message explains it. Otherwise, the error found without them is reported.
+	pub(crate) fn non_preservable(&self, err: syn::Error) -> Error {
+		let (message, err) = match syn::parse_file(&self.without_added_lines()) {
+			Ok(_) => (NON_PRESERVABLE_COMMENTS.to_owned(), err),
+			Err(err) => (err.to_string(), err),
+		};
+		let (line, column, lookup) = self.location(&err);
+		Error::NonPreservableCode { message, line, column, lookup }
+	}
+
+	// The code with the lines added or prefixed by the preservation process blanked out, so it only
+	// contains preserved code and its lines still match `origins`.
+	fn without_added_lines(&self) -> String {
+		self.code
+			.lines()
+			.zip(&self.origins)
+			.map(|(line, origin)| if origin.prefix_len == Some(0) { line } else { "" })
+			.fold(String::new(), |mut code, line| {
+				code.push_str(line);
+				code.push('\n');
+				code
+			})
+	}
+
+	// The line, column and lookup of the original code where `err` is found.
+	#[cfg(feature = "span-locations")]
+	fn location(&self, err: &syn::Error) -> (Option<usize>, Option<usize>, Option<String>) {
+		let start = err.span().start();
+		// Errors at the end of the input may not point to any line.
+		let origin = start
+			.line
+			.checked_sub(1)
+			.and_then(|index| self.origins.get(index))
+			.or_else(|| self.origins.last());
+
+		(
+			origin.map(|origin| origin.line + 1),
+			origin
+				.and_then(|origin| origin.prefix_len)
+				.map(|prefix_len| start.column.saturating_sub(prefix_len)),
+			origin.and_then(|origin| origin.lookup).map(ToString::to_string),
+		)
+	}
+
+	// Without the line/column information of the parsed code, the error location is unknown.
+	#[cfg(not(feature = "span-locations"))]
+	fn location(&self, _err: &syn::Error) -> (Option<usize>, Option<usize>, Option<String>) {
+		(None, None, None)
+	}
+}
+
+const NON_PRESERVABLE_COMMENTS: &str =
+	"comments and empty lines inside preserved code can only be \
+                                        kept where an item or a statement is allowed";
+
+// Skips a char literal starting at `index`. If it's a lifetime or a label instead, only the quote
+// is skipped.
+fn skip_char_or_lifetime(chars: &[char], index: usize) -> usize {
+	match (chars.get(index + 1), chars.get(index + 2)) {
+		// The escaped char is skipped, as it may be a quote.
+		(Some('\\'), _) => chars
+			.iter()
+			.enumerate()
+			.skip(index + 3)
+			.find(|(_, &c)| c == '\'')
+			.map_or(chars.len(), |(position, _)| position + 1),
+		(Some(_), Some('\'')) => index + 3,
+		_ => index + 1,
 	}
 }
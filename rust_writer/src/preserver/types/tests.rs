@@ -6,7 +6,7 @@ use super::*;
 fn create_preserver() {
 	let preserver = Preserver::new("root");
 	assert_eq!(preserver.lookup(), "root");
-	assert!(preserver.inner.is_none());
+	assert!(preserver.get_inners().is_empty());
 }
 
 #[test]
@@ -29,7 +29,123 @@ fn preserver_should_handle_empty_inners() {
 	let mut preserver = Preserver::new("root");
 	preserver.add_inners(&[]);
 	assert_eq!(preserver.lookup(), "root");
-	assert!(preserver.inner.is_none());
+	assert!(preserver.get_inners().is_empty());
+}
+
+#[test]
+fn add_sibling_inners_to_preserver() {
+	let mut preserver = Preserver::new("root");
+	preserver.add_inners(&["inner1", "inner2"]);
+	preserver.add_inner(Preserver::new("inner3"));
+
+	let inners = preserver.get_inners();
+	assert_eq!(inners.len(), 2);
+	assert_eq!(inners[0].lookup(), "inner1");
+	assert_eq!(inners[0].get_inners().len(), 1);
+	assert_eq!(inners[0].get_inners()[0].lookup(), "inner2");
+	assert_eq!(inners[1].lookup(), "inner3");
+	assert!(inners[1].get_inners().is_empty());
+}
+
+#[test]
+fn add_inner_lookups_to_preserver() {
+	let mut preserver = Preserver::new("root");
+	preserver.add_inner_lookups(vec![
+		Lookup::Item { kind: "fn".into(), name: "inner1".into() },
+		Lookup::Prefix("inner2".into()),
+	]);
+
+	let mut inner = preserver.get_inner().expect("No inner");
+	assert_eq!(inner.lookup(), &Lookup::Item { kind: "fn".into(), name: "inner1".into() });
+	inner = inner.get_inner().expect("No inner");
+	assert_eq!(inner.lookup(), "inner2");
+	assert!(inner.get_inner().is_none());
+}
+
+#[test]
+fn prefix_lookup_matches() {
+	let lookup = Lookup::Prefix("impl MyTrait for MyStruct".into());
+
+	assert!(lookup.matches("    impl MyTrait for MyStruct {"));
+	assert!(!lookup.matches("impl<T> MyTrait for MyStruct<T> {"));
+}
+
+#[test]
+fn regex_lookup_matches_at_line_start() {
+	let lookup =
+		Lookup::Regex(Regex::new(r"(pub(\(crate\))? )?fn main").expect("Valid regex; qed;"));
+
+	assert!(lookup.matches("  pub(crate) fn main() {"));
+	assert!(lookup.matches("fn main() {"));
+	assert!(!lookup.matches("// fn main"));
+}
+
+#[test]
+fn impl_lookup_matches() {
+	let with_trait =
+		Lookup::Impl { trait_name: Some("MyTrait".into()), implementor_name: "MyStruct".into() };
+	let without_trait = Lookup::Impl { trait_name: None, implementor_name: "MyStruct".into() };
+
+	assert!(with_trait.matches("impl<T: Clone>  MyTrait for  MyStruct<T> {"));
+	assert!(with_trait.matches("impl some::path::MyTrait for MyStruct where T: Clone {"));
+	assert!(!with_trait.matches("impl MyTrait for OtherStruct {"));
+	assert!(!with_trait.matches("impl MyStruct {"));
+	assert!(without_trait.matches("impl<T> MyStruct<T> {"));
+	assert!(!without_trait.matches("impl MyTrait for MyStruct {"));
+}
+
+#[test]
+fn item_lookup_matches() {
+	let lookup = Lookup::Item { kind: "fn".into(), name: "main".into() };
+
+	assert!(lookup.matches("pub(crate) fn main() -> Result<(), Error> {"));
+	assert!(lookup.matches("async fn main<T>() {"));
+	assert!(!lookup.matches("fn main_func() {"));
+	assert!(!lookup.matches("struct main;"));
+	assert!(Lookup::Item { kind: "struct".into(), name: "MyStruct".into() }
+		.matches("pub struct MyStruct;"));
+}
+
+#[test]
+fn lookups_match_multi_line_headers() {
+	let impl_lookup = Lookup::Impl { trait_name: Some("Tr".into()), implementor_name: "S".into() };
+	let item_lookup = Lookup::Item { kind: "fn".into(), name: "build".into() };
+
+	assert!(impl_lookup.matches("impl<T> Tr for S<T>\nwhere\n    T: Clone,\n{\n    fn a() {}\n}"));
+	assert!(!impl_lookup
+		.matches("impl<T> Tr for Other<T>\nwhere\n    T: Clone,\n{\n    type A = S;\n}"));
+	assert!(
+		item_lookup.matches("pub fn build(\n    name: &str,\n    value: [u8; 4],\n) -> Self {\n}")
+	);
+	assert!(
+		item_lookup.matches("pub fn build<T>(name: T) -> Self\nwhere\n    T: AsRef<str>,\n{\n}")
+	);
+	assert!(!item_lookup.matches("pub fn builder(\n    name: &str,\n) -> Self {\n}"));
+}
+
+#[test]
+fn item_header_works() {
+	assert_eq!(item_header("fn a(\n    b: u8,\n) {\n    b;\n}\n"), "fn a(\n    b: u8,\n) {\n");
+	assert_eq!(
+		item_header("fn a(b: [u8; 4])\n    -> u8;\nfn c();\n"),
+		"fn a(b: [u8; 4])\n    -> u8;\n"
+	);
+	assert_eq!(item_header("struct A {}\nstruct B;\n"), "struct A {}\n");
+}
+
+#[test]
+fn lookup_display_works() {
+	assert_eq!(Lookup::Prefix("fn main".into()).to_string(), "fn main");
+	assert_eq!(
+		Lookup::Regex(Regex::new("fn ma.n").expect("Valid regex; qed;")).to_string(),
+		"fn ma.n"
+	);
+	assert_eq!(
+		Lookup::Impl { trait_name: Some("MyTrait".into()), implementor_name: "MyStruct".into() }
+			.to_string(),
+		"impl MyTrait for MyStruct"
+	);
+	assert_eq!(Lookup::Item { kind: "fn".into(), name: "main".into() }.to_string(), "fn main");
 }
 
 #[test]
@@ -66,3 +182,152 @@ fn delimiters_count_should_not_be_complete() {
 	delimiters_count.count("{ ( [ ) }");
 	assert!(!delimiters_count.is_complete());
 }
+
+#[test]
+fn delimiters_count_skips_string_literals() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	delimiters_count.count(r#"fn main() { println!("{"); let a = b"(\"["; let b = c"]";"#);
+	assert!(!delimiters_count.is_complete());
+	delimiters_count.count("}");
+	assert!(delimiters_count.is_complete());
+}
+
+#[test]
+fn delimiters_count_skips_multiline_string_literals() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	delimiters_count.count(r#"const A: &str = "{"#);
+	delimiters_count.count(r#"}";"#);
+	assert!(delimiters_count.is_complete());
+	assert_eq!(delimiters_count.counts, [0; 6]);
+}
+
+#[test]
+fn delimiters_count_skips_raw_string_literals() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	delimiters_count.count(r###"let a = r#"{ "} "#; let b = br"("; let c = cr##"[""##;"###);
+	assert!(delimiters_count.is_complete());
+	assert_eq!(delimiters_count.counts, [0; 6]);
+
+	delimiters_count.count("let r#fn = (1);");
+	assert_eq!(delimiters_count.counts, [0, 0, 1, 1, 0, 0]);
+}
+
+#[test]
+fn delimiters_count_skips_char_literals() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	delimiters_count.count(r"let a = ['{', '\'', '\u{7D}', b'}'];");
+	assert!(delimiters_count.is_complete());
+	assert_eq!(delimiters_count.counts, [0, 0, 0, 0, 1, 1]);
+}
+
+#[test]
+fn delimiters_count_counts_delimiters_after_lifetimes() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	delimiters_count.count("fn func<'a>(a: &'a str) -> &'a str {");
+	assert!(!delimiters_count.is_complete());
+	assert_eq!(delimiters_count.counts, [1, 0, 1, 1, 0, 0]);
+}
+
+#[test]
+fn delimiters_count_skips_comments() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	delimiters_count.count("fn main() { // }");
+	assert!(!delimiters_count.is_complete());
+	delimiters_count.count("/* } /* nested } */");
+	delimiters_count.count("still commented } */ }");
+	assert!(delimiters_count.is_complete());
+}
+
+#[test]
+fn delimiters_count_supports_large_counts() {
+	let mut delimiters_count = DelimitersCount::new();
+
+	for _ in 0..300 {
+		delimiters_count.count("{");
+	}
+	assert_eq!(delimiters_count.counts[0], 300);
+	for _ in 0..300 {
+		delimiters_count.count("}");
+	}
+	assert!(delimiters_count.is_complete());
+}
+
+#[test]
+fn preserver_into_owned_works() {
+	let lookup = String::from("root");
+	let mut preserver = Preserver::new(lookup.as_str());
+	preserver.add_inner(Preserver::with_lookup(Lookup::Impl {
+		trait_name: Some("MyTrait".into()),
+		implementor_name: "MyStruct".into(),
+	}));
+
+	let owned_preserver: Preserver<'static> = preserver.clone().into_owned();
+	drop(lookup);
+
+	assert_eq!(owned_preserver.lookup(), "root");
+	assert_eq!(
+		owned_preserver.get_inners()[0].lookup(),
+		&Lookup::Impl { trait_name: Some("MyTrait".into()), implementor_name: "MyStruct".into() }
+	);
+	assert!(matches!(owned_preserver.lookup(), Lookup::Prefix(Cow::Owned(_))));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn preserver_serde_round_trip_works() {
+	let mut preserver = Preserver::new("impl MyTrait for MyStruct");
+	preserver.add_inner(Preserver::with_lookup(Lookup::Regex(
+		Regex::new(r"fn \w+").expect("Valid regex; qed;"),
+	)));
+	preserver
+		.add_inner(Preserver::with_lookup(Lookup::Item { kind: "fn".into(), name: "main".into() }));
+
+	let json = serde_json::to_string(&preserver).expect("This should be Ok; qed;");
+	assert_eq!(
+		json,
+		r#"{"lookup":{"prefix":"impl MyTrait for MyStruct"},"inners":[{"lookup":{"regex":"fn \\w+"}},{"lookup":{"item":{"kind":"fn","name":"main"}}}]}"#
+	);
+
+	let deserialized: Preserver<'static> =
+		serde_json::from_str(&json).expect("This should be Ok; qed;");
+	assert_eq!(deserialized, preserver);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn preserver_deserialization_fails_if_invalid_regex() {
+	assert!(serde_json::from_str::<Preserver>(r#"{"lookup":{"regex":"fn ("}}"#).is_err());
+}
+
+#[test]
+fn source_format_detection_works() {
+	assert_eq!(SourceFormat::detect("fn main() {}\n"), (SourceFormat::default(), "fn main() {}\n"));
+	assert_eq!(SourceFormat::detect(""), (SourceFormat::default(), ""));
+	assert_eq!(
+		SourceFormat::detect("\u{feff}fn main() {\r\n}"),
+		(SourceFormat { bom: true, crlf: true, missing_final_newline: true }, "fn main() {\r\n}")
+	);
+}
+
+#[test]
+fn source_format_flags_round_trip() {
+	let format = SourceFormat { bom: true, crlf: false, missing_final_newline: true };
+
+	assert_eq!(format.flags(), ["bom", "no_final_newline"]);
+	assert_eq!(SourceFormat::from_flags(&format.flags().join(" ")), format);
+	assert!(SourceFormat::default().flags().is_empty());
+}
+
+#[test]
+fn source_format_apply_works() {
+	let format = SourceFormat { bom: true, crlf: true, missing_final_newline: true };
+
+	assert_eq!(format.apply("fn main() {\n}\n".to_owned()), "\u{feff}fn main() {\r\n}");
+	assert_eq!(SourceFormat::default().apply("fn main() {}\n".to_owned()), "fn main() {}\n");
+}
@@ -0,0 +1,893 @@
+// SPDX-License-Identifier: GPL-3.0
+
+//! This module provides an alternative to [`resolve_preserved`](https://docs.rs/rust_writer/latest/rust_writer/preserver/fn.resolve_preserved.html)
+//! that writes a mutated AST back to its source file without re-printing the whole file.
+//!
+//! Instead of un-parsing the complete AST, the splicer compares the mutated AST with the AST
+//! originally parsed from the source code and only re-emits the items that were actually changed
+//! or inserted, copying every other byte of the original source verbatim. Comments, empty lines,
+//! macro invocations and formatting outside the changed items remain untouched, so there's no need
+//! to use [`Preserver`](https://docs.rs/rust_writer/latest/rust_writer/preserver/struct.Preserver.html)
+//! at all.
+//!
+//! The public API consists of two functions:
+//!
+//! - [`splice`]: Returns the source code resulting from splicing the changes of the mutated AST
+//!   into the original source code.
+//!
+//! - [`resolve_spliced`]: Same as [`splice`], but the resulting code is written to the specified
+//!   path.
+//!
+//! Both functions expect the original AST to be parsed directly from the original source code, eg
+//! using [`syn::parse_file`](https://docs.rs/syn/latest/syn/fn.parse_file.html), as the spans of
+//! its items are used to locate them in the source code. For the same reason, this module is only
+//! available with the `span-locations` feature.
+//!
+//! # How changes are spliced
+//!
+//! The items of the original and the mutated ASTs are aligned, so unchanged items are copied from
+//! the original source code. If an item only differs in its body, the same process is applied to
+//! the body:
+//!
+//! - The items of `impl` blocks, traits and inline modules.
+//! - The statements of functions and methods.
+//! - The fields of structs and the variants of enums.
+//! - The tokens of macro invocations.
+//!
+//! Otherwise, changed and new elements are printed using [`prettyplease::unparse`](https://docs.rs/prettyplease/latest/prettyplease/fn.unparse.html)
+//! and indented like their neighbours, following the line endings and the indentation style
+//! (tabs or spaces) of the source code. Removed elements are deleted together with the code
+//! between them and the previous element, such as comments and empty lines.
+//!
+//! # Example
+//!
+//! ```rust
+//! use test_builder::TestBuilder;
+//! use rust_writer::ast::{
+//!     implementors::ItemToImpl,
+//!     mutator::{Mutator, ToMutate},
+//! };
+//! use syn::{parse_quote, visit_mut::VisitMut};
+//!
+//! TestBuilder::default()
+//!     .with_complete_file()
+//!     .execute(|builder| {
+//!         let complete_file_path = builder.tempfile_path("complete_file.rs")
+//!             .expect("This exists; qed;");
+//!         let source = std::fs::read_to_string(complete_file_path)
+//!             .expect("File should be readable");
+//!
+//!         let original_ast = syn::parse_file(&source).expect("This should be Ok; qed;");
+//!         let mut ast = original_ast.clone();
+//!
+//!         let item_to_impl: ItemToImpl = (
+//!             None,
+//!             "MyStruct",
+//!             parse_quote! {
+//!                 fn field1(&self) -> i32 {
+//!                     self.field1
+//!                 }
+//!             },
+//!         )
+//!         .into();
+//!         let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+//!         assert!(mutator.mutate(&mut ast).is_ok());
+//!
+//!         assert!(rust_writer::splicer::resolve_spliced(
+//!             &source,
+//!             &original_ast,
+//!             &ast,
+//!             complete_file_path
+//!         )
+//!         .is_ok());
+//!
+//!         // Only the new function has been added to the file.
+//!         let expected_code = source.replace(
+//!             "            field2: text.to_string(),\n        }\n    }\n",
+//!             "            field2: text.to_string(),\n        }\n    }\n    fn field1(&self) -> \
+//!              i32 {\n        self.field1\n    }\n",
+//!         );
+//!         let actual_code = std::fs::read_to_string(complete_file_path)
+//!             .expect("File should be readable");
+//!
+//!         assert_eq!(actual_code, expected_code);
+//!     });
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use crate::{ast::implementors::helpers::Identity, preserver::SourceFormat, Error};
+use proc_macro2::{extra::DelimSpan, LineColumn, Span, TokenStream, TokenTree};
+use std::{borrow::Cow, ops::Range, path::Path};
+use syn::{
+	parse_quote, punctuated::Punctuated, spanned::Spanned, token::Comma, Block, Field, Fields,
+	File, ImplItem, Item, ItemEnum, ItemImpl, ItemMod, ItemStruct, ItemTrait, Macro,
+	MacroDelimiter, Stmt, TraitItem, Variant,
+};
+
+/// Splices the changes of the `mutated` AST into the `source` code, returning the resulting code.
+/// The `original` AST must be the result of parsing `source`, and `mutated` the result of applying
+/// some mutations to it.
+///
+/// Only the changed or inserted elements are printed, all the rest of `source` is copied verbatim.
+///
+/// # Errors
+///
+/// This function fails if `original` doesn't come from `source`, or if the inner attributes or
+/// the shebang of the file have been changed, as splicing them isn't supported.
+pub fn splice(source: &str, original: &File, mutated: &File) -> Result<String, Error> {
+	if original.attrs != mutated.attrs || original.shebang != mutated.shebang {
+		return Err(Error::Descriptive(
+			"Cannot splice changes to the file's inner attributes or shebang".to_owned(),
+		));
+	}
+
+	// `syn::parse_file` skips the byte order mark, so spans don't take it into account.
+	let (bom, code) = match source.strip_prefix('\u{feff}') {
+		Some(code) => ("\u{feff}", code),
+		None => ("", source),
+	};
+
+	let splicer = Splicer::new(code);
+	let spliced = match splicer.splice_items(&original.items, &mutated.items, 0..code.len())? {
+		Some(spliced) => spliced,
+		None => {
+			let separator =
+				if code.is_empty() || code.ends_with('\n') { "" } else { splicer.line_ending };
+			let items: Vec<String> =
+				mutated.items.iter().map(|item| splicer.indented(&item.print(), "")).collect();
+			format!(
+				"{}{}{}{}",
+				code,
+				separator,
+				items.join(splicer.line_ending),
+				splicer.line_ending
+			)
+		},
+	};
+
+	Ok(format!("{}{}", bom, spliced))
+}
+
+/// Splices the changes of the `mutated` AST into the `source` code as [`splice`] does, and writes
+/// the result to the specified `path`.
+pub fn resolve_spliced(
+	source: &str,
+	original: &File,
+	mutated: &File,
+	path: &Path,
+) -> Result<(), Error> {
+	std::fs::write(path, splice(source, original, mutated)?)?;
+	Ok(())
+}
+
+// How an element of the original AST relates to the mutated AST.
+#[derive(Debug, PartialEq)]
+enum Op {
+	Keep(usize),
+	Modify(usize, usize),
+	Insert(usize),
+	Delete(usize),
+}
+
+// The bodies of two versions of an element which only differ in them.
+enum Body<'a> {
+	Impl(DelimSpan, &'a [ImplItem], &'a [ImplItem]),
+	Trait(DelimSpan, &'a [TraitItem], &'a [TraitItem]),
+	Mod(DelimSpan, &'a [Item], &'a [Item]),
+	Block(&'a Block, &'a Block),
+	Fields(DelimSpan, &'a Punctuated<Field, Comma>, &'a Punctuated<Field, Comma>),
+	Variants(DelimSpan, &'a Punctuated<Variant, Comma>, &'a Punctuated<Variant, Comma>),
+	Tokens(&'a Macro, &'a Macro),
+}
+
+// The elements that may be spliced.
+trait Spliceable: Clone + PartialEq + Spanned + Identity {
+	// Prints the element on its own, without trailing new line.
+	fn print(&self) -> String;
+
+	// If `mutated` only differs from `self` in its body, returns both bodies.
+	fn body<'a>(&'a self, _mutated: &'a Self) -> Option<Body<'a>> {
+		None
+	}
+
+	// Whether a mutated element is a new version of `self`, so it should take its place.
+	fn is_replaced_by(&self, mutated: &Self) -> bool {
+		self.body(mutated).is_some() ||
+			(self.identity().is_some() && self.identity() == mutated.identity())
+	}
+}
+
+impl Spliceable for Item {
+	fn print(&self) -> String {
+		let file = File { shebang: None, attrs: Vec::new(), items: vec![self.clone()] };
+		prettyplease::unparse(&file).trim_end().to_owned()
+	}
+
+	fn body<'a>(&'a self, mutated: &'a Self) -> Option<Body<'a>> {
+		match (self, mutated) {
+			(Item::Impl(original), Item::Impl(mutated))
+				if ItemImpl { items: Vec::new(), ..original.clone() } ==
+					ItemImpl { items: Vec::new(), ..mutated.clone() } =>
+				Some(Body::Impl(original.brace_token.span, &original.items, &mutated.items)),
+			(Item::Trait(original), Item::Trait(mutated))
+				if ItemTrait { items: Vec::new(), ..original.clone() } ==
+					ItemTrait { items: Vec::new(), ..mutated.clone() } =>
+				Some(Body::Trait(original.brace_token.span, &original.items, &mutated.items)),
+			(
+				Item::Mod(original @ ItemMod { content: Some((brace, original_items)), .. }),
+				Item::Mod(mutated @ ItemMod { content: Some((_, mutated_items)), .. }),
+			) if ItemMod { content: None, ..original.clone() } ==
+				ItemMod { content: None, ..mutated.clone() } =>
+				Some(Body::Mod(brace.span, original_items, mutated_items)),
+			(Item::Fn(original), Item::Fn(mutated))
+				if original.attrs == mutated.attrs &&
+					original.vis == mutated.vis &&
+					original.sig == mutated.sig =>
+				Some(Body::Block(&original.block, &mutated.block)),
+			(
+				Item::Struct(original @ ItemStruct { fields: Fields::Named(original_fields), .. }),
+				Item::Struct(mutated @ ItemStruct { fields: Fields::Named(mutated_fields), .. }),
+			) if ItemStruct { fields: Fields::Unit, ..original.clone() } ==
+				ItemStruct { fields: Fields::Unit, ..mutated.clone() } =>
+				Some(Body::Fields(
+					original_fields.brace_token.span,
+					&original_fields.named,
+					&mutated_fields.named,
+				)),
+			(Item::Enum(original), Item::Enum(mutated))
+				if ItemEnum { variants: Punctuated::new(), ..original.clone() } ==
+					ItemEnum { variants: Punctuated::new(), ..mutated.clone() } =>
+				Some(Body::Variants(
+					original.brace_token.span,
+					&original.variants,
+					&mutated.variants,
+				)),
+			(Item::Macro(original), Item::Macro(mutated))
+				if original.attrs == mutated.attrs &&
+					original.ident == mutated.ident &&
+					original.semi_token == mutated.semi_token =>
+				macro_body(&original.mac, &mutated.mac),
+			_ => None,
+		}
+	}
+}
+
+impl Spliceable for ImplItem {
+	fn print(&self) -> String {
+		let mut item_impl: ItemImpl = parse_quote! { impl Splice {} };
+		item_impl.items.push(self.clone());
+		print_inner(Item::Impl(item_impl))
+	}
+
+	fn body<'a>(&'a self, mutated: &'a Self) -> Option<Body<'a>> {
+		match (self, mutated) {
+			(ImplItem::Fn(original), ImplItem::Fn(mutated))
+				if original.attrs == mutated.attrs &&
+					original.vis == mutated.vis &&
+					original.defaultness == mutated.defaultness &&
+					original.sig == mutated.sig =>
+				Some(Body::Block(&original.block, &mutated.block)),
+			(ImplItem::Macro(original), ImplItem::Macro(mutated))
+				if original.attrs == mutated.attrs && original.semi_token == mutated.semi_token =>
+				macro_body(&original.mac, &mutated.mac),
+			_ => None,
+		}
+	}
+}
+
+impl Spliceable for TraitItem {
+	fn print(&self) -> String {
+		let mut item_trait: ItemTrait = parse_quote! { trait Splice {} };
+		item_trait.items.push(self.clone());
+		print_inner(Item::Trait(item_trait))
+	}
+
+	fn body<'a>(&'a self, mutated: &'a Self) -> Option<Body<'a>> {
+		match (self, mutated) {
+			(TraitItem::Fn(original), TraitItem::Fn(mutated))
+				if original.attrs == mutated.attrs && original.sig == mutated.sig =>
+				match (&original.default, &mutated.default) {
+					(Some(original), Some(mutated)) => Some(Body::Block(original, mutated)),
+					_ => None,
+				},
+			(TraitItem::Macro(original), TraitItem::Macro(mutated))
+				if original.attrs == mutated.attrs && original.semi_token == mutated.semi_token =>
+				macro_body(&original.mac, &mutated.mac),
+			_ => None,
+		}
+	}
+}
+
+impl Spliceable for Stmt {
+	fn print(&self) -> String {
+		let mut item_fn: syn::ItemFn = parse_quote! { fn splice() {} };
+		item_fn.block.stmts.push(self.clone());
+		print_inner(Item::Fn(item_fn))
+	}
+
+	fn body<'a>(&'a self, mutated: &'a Self) -> Option<Body<'a>> {
+		match (self, mutated) {
+			(Stmt::Item(original), Stmt::Item(mutated)) => original.body(mutated),
+			(Stmt::Macro(original), Stmt::Macro(mutated))
+				if original.attrs == mutated.attrs && original.semi_token == mutated.semi_token =>
+				macro_body(&original.mac, &mutated.mac),
+			_ => None,
+		}
+	}
+}
+
+impl Spliceable for Field {
+	fn print(&self) -> String {
+		let mut item_struct: ItemStruct = parse_quote! { struct Splice {} };
+		if let Fields::Named(ref mut fields) = item_struct.fields {
+			fields.named.push(self.clone());
+		}
+		print_inner(Item::Struct(item_struct))
+	}
+}
+
+impl Spliceable for Variant {
+	fn print(&self) -> String {
+		let mut item_enum: ItemEnum = parse_quote! { enum Splice {} };
+		item_enum.variants.push(self.clone());
+		print_inner(Item::Enum(item_enum))
+	}
+}
+
+// Macro invocations only differing in their tokens.
+fn macro_body<'a>(original: &'a Macro, mutated: &'a Macro) -> Option<Body<'a>> {
+	(original.path == mutated.path && original.delimiter == mutated.delimiter)
+		.then_some(Body::Tokens(original, mutated))
+}
+
+// Prints the only inner element of a container, removing the container's lines and indentation.
+fn print_inner(container: Item) -> String {
+	let code = container.print();
+	let lines: Vec<&str> = code.lines().collect();
+	lines[1..lines.len() - 1]
+		.iter()
+		.map(|line| line.strip_prefix("    ").unwrap_or(line))
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+// Prints some tokens as prettyplease does inside a macro invocation.
+fn print_tokens(tokens: &[TokenTree]) -> String {
+	let tokens: TokenStream = tokens.iter().cloned().collect();
+	let code = Item::Macro(parse_quote! { splice! { #tokens } }).print();
+	let body = code
+		.strip_prefix("splice! {")
+		.and_then(|code| code.strip_suffix('}'))
+		.unwrap_or(&code);
+	if body.contains('\n') {
+		body.trim_matches('\n')
+			.lines()
+			.map(|line| line.strip_prefix("    ").unwrap_or(line))
+			.collect::<Vec<_>>()
+			.join("\n")
+	} else {
+		body.trim().to_owned()
+	}
+}
+
+fn delim_span(delimiter: &MacroDelimiter) -> DelimSpan {
+	match delimiter {
+		MacroDelimiter::Paren(paren) => paren.span,
+		MacroDelimiter::Brace(brace) => brace.span,
+		MacroDelimiter::Bracket(bracket) => bracket.span,
+	}
+}
+
+struct Splicer<'a> {
+	code: &'a str,
+	// The byte offset where each line of `code` starts.
+	line_starts: Vec<usize>,
+	line_ending: &'static str,
+	// The string indenting one level of code, eg four spaces or a tab.
+	indent_unit: String,
+}
+
+impl<'a> Splicer<'a> {
+	fn new(code: &'a str) -> Self {
+		let line_starts = std::iter::once(0)
+			.chain(code.match_indices('\n').map(|(index, _)| index + 1))
+			.collect();
+		let (format, _) = SourceFormat::detect(code);
+		Self {
+			code,
+			line_starts,
+			line_ending: format.line_ending(),
+			indent_unit: indent_unit(code),
+		}
+	}
+
+	// Spans count columns in chars, so they must be converted to byte offsets.
+	fn offset(&self, location: LineColumn) -> Result<usize, Error> {
+		let line_start = location
+			.line
+			.checked_sub(1)
+			.and_then(|line| self.line_starts.get(line))
+			.ok_or_else(not_from_source)?;
+		let line = &self.code[*line_start..];
+		line.char_indices()
+			.map(|(index, _)| index)
+			.chain(std::iter::once(line.len()))
+			.nth(location.column)
+			.map(|index| line_start + index)
+			.ok_or_else(not_from_source)
+	}
+
+	fn range(&self, span: Span) -> Result<Range<usize>, Error> {
+		let range = self.offset(span.start())?..self.offset(span.end())?;
+		// Nodes that don't come from the source code have empty spans.
+		if range.is_empty() {
+			return Err(not_from_source());
+		}
+		Ok(range)
+	}
+
+	// The indentation of the line containing `offset`.
+	fn line_indent(&self, offset: usize) -> &'a str {
+		let line_start = self.code[..offset].rfind('\n').map_or(0, |index| index + 1);
+		let line = &self.code[line_start..];
+		&line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+	}
+
+	// Indents the code printed by prettyplease, which indents by four spaces and breaks lines by
+	// `\n`, as the rest of the source code. The first line isn't indented.
+	fn indented(&self, code: &str, indent: &str) -> String {
+		code.lines()
+			.enumerate()
+			.map(|(index, line)| {
+				let content = line.trim_start_matches(' ');
+				let spaces = line.len() - content.len();
+				let line_indent =
+					format!("{}{}", self.indent_unit.repeat(spaces / 4), " ".repeat(spaces % 4));
+				match index {
+					0 => line.to_owned(),
+					_ if line.is_empty() => String::new(),
+					_ => format!("{}{}{}", indent, line_indent, content),
+				}
+			})
+			.collect::<Vec<_>>()
+			.join(self.line_ending)
+	}
+
+	fn splice_item<T: Spliceable>(
+		&self,
+		original: &T,
+		mutated: &T,
+		indent: &str,
+	) -> Result<String, Error> {
+		let spliced = match original.body(mutated) {
+			Some(body) => self.splice_body(original.span(), body)?,
+			None => None,
+		};
+		Ok(spliced.unwrap_or_else(|| self.indented(&mutated.print(), indent)))
+	}
+
+	// Splices the body of an element spanning `span`. Returns `None` if there's nothing to take
+	// the formatting from, ie, elements are added to an empty body.
+	fn splice_body(&self, span: Span, body: Body) -> Result<Option<String>, Error> {
+		match body {
+			Body::Impl(brace, original, mutated) => self.splice_container(span, brace, |region| {
+				self.splice_items(original, mutated, region)
+			}),
+			Body::Trait(brace, original, mutated) => self.splice_container(span, brace, |region| {
+				self.splice_items(original, mutated, region)
+			}),
+			Body::Mod(brace, original, mutated) => self.splice_container(span, brace, |region| {
+				self.splice_items(original, mutated, region)
+			}),
+			Body::Block(original, mutated) =>
+				self.splice_container(span, original.brace_token.span, |region| {
+					self.splice_items(&original.stmts, &mutated.stmts, region)
+				}),
+			Body::Fields(brace, original, mutated) =>
+				self.splice_container(span, brace, |region| {
+					self.splice_punctuated(original, mutated, region)
+				}),
+			Body::Variants(brace, original, mutated) =>
+				self.splice_container(span, brace, |region| {
+					self.splice_punctuated(original, mutated, region)
+				}),
+			Body::Tokens(original, mutated) =>
+				self.splice_container(span, delim_span(&original.delimiter), |region| {
+					self.splice_tokens(original.tokens.clone(), mutated.tokens.clone(), region)
+				}),
+		}
+	}
+
+	// Splices the body delimited by `delimiters` inside the element spanning `span`.
+	fn splice_container(
+		&self,
+		span: Span,
+		delimiters: DelimSpan,
+		splice_region: impl FnOnce(Range<usize>) -> Result<Option<String>, Error>,
+	) -> Result<Option<String>, Error> {
+		let element = self.range(span)?;
+		let body =
+			self.offset(delimiters.open().end())?..self.offset(delimiters.close().start())?;
+		Ok(splice_region(body.clone())?.map(|body_code| {
+			format!(
+				"{}{}{}",
+				&self.code[element.start..body.start],
+				body_code,
+				&self.code[body.end..element.end]
+			)
+		}))
+	}
+
+	// Splices `mutated` into the region of the code containing `original`. Returns `None` if
+	// there's nothing to take the formatting from, ie, elements are added to an empty region.
+	fn splice_items<T: Spliceable>(
+		&self,
+		original: &[T],
+		mutated: &[T],
+		region: Range<usize>,
+	) -> Result<Option<String>, Error> {
+		let ranges = original
+			.iter()
+			.map(|item| self.range(item.span()))
+			.collect::<Result<Vec<_>, _>>()?;
+		self.splice_ranges(original, mutated, &ranges, region, None)
+	}
+
+	// Same as `splice_items`, but the elements are separated by commas. The comma following an
+	// original element goes along with it.
+	fn splice_punctuated<T: Spliceable>(
+		&self,
+		original: &Punctuated<T, Comma>,
+		mutated: &Punctuated<T, Comma>,
+		region: Range<usize>,
+	) -> Result<Option<String>, Error> {
+		let ranges = original
+			.pairs()
+			.map(|pair| {
+				let value = self.range(pair.value().span())?;
+				match pair.punct() {
+					Some(comma) => Ok(value.start..self.range(comma.span)?.end),
+					None => Ok(value),
+				}
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+		let original: Vec<T> = original.iter().cloned().collect();
+		let mutated: Vec<T> = mutated.iter().cloned().collect();
+		self.splice_ranges(&original, &mutated, &ranges, region, Some(","))
+	}
+
+	fn splice_ranges<T: Spliceable>(
+		&self,
+		original: &[T],
+		mutated: &[T],
+		ranges: &[Range<usize>],
+		region: Range<usize>,
+		separator: Option<&str>,
+	) -> Result<Option<String>, Error> {
+		if original.is_empty() {
+			return Ok(mutated.is_empty().then(|| self.code[region].to_owned()));
+		}
+
+		// The code preceding each original element. The first one is the region's prefix.
+		let mut gaps = Vec::with_capacity(ranges.len());
+		let mut previous_end = region.start;
+		for range in ranges {
+			gaps.push(self.code.get(previous_end..range.start).ok_or_else(not_from_source)?);
+			previous_end = range.end;
+		}
+		let tail = self.code.get(previous_end..region.end).ok_or_else(not_from_source)?;
+
+		// The region's prefix is split into the region's head and the comments attached to the
+		// first element, which go along with it.
+		let at_line_start = region.start == 0 || self.code[..region.start].ends_with('\n');
+		let (head, first_gap) = gaps[0].split_at(attached_comments_start(gaps[0], at_line_start));
+		let (indent, line_break) = if self.code[region].contains('\n') {
+			let indent = first_gap
+				.rsplit('\n')
+				.next()
+				.filter(|indent| indent.trim().is_empty())
+				.unwrap_or_default();
+			// New elements are separated by an empty line if the original ones are.
+			let line_break = if gaps[1..].iter().any(|gap| has_empty_line(gap)) {
+				Cow::Owned(self.line_ending.repeat(2))
+			} else {
+				Cow::Borrowed(self.line_ending)
+			};
+			(indent, line_break)
+		} else {
+			// The elements are on the same line, so are the new ones.
+			("", Cow::Borrowed(" "))
+		};
+
+		// The code preceding each element in the result, together with the element's code.
+		let mut pieces: Vec<(Cow<str>, Cow<str>)> = Vec::new();
+		for op in align(original, mutated) {
+			let (index, item_code) = match op {
+				Op::Keep(index) => (Some(index), Cow::Borrowed(&self.code[ranges[index].clone()])),
+				Op::Modify(index, mutated_index) => (
+					Some(index),
+					Cow::Owned(self.splice_item(
+						&original[index],
+						&mutated[mutated_index],
+						indent,
+					)?),
+				),
+				Op::Insert(index) =>
+					(None, Cow::Owned(self.indented(&mutated[index].print(), indent))),
+				Op::Delete(_) => continue,
+			};
+			let first = pieces.is_empty();
+			let gap = match index {
+				Some(0) if first => Cow::Borrowed(first_gap),
+				Some(0) => Cow::Owned(format!("{}{}", line_break, first_gap)),
+				Some(index) if first => Cow::Borrowed(without_leading_empty_lines(gaps[index])),
+				Some(index) => Cow::Borrowed(gaps[index]),
+				None if first => Cow::Borrowed(indent),
+				None => Cow::Owned(format!("{}{}", line_break, indent)),
+			};
+			pieces.push((gap, item_code));
+		}
+
+		if let Some(separator) = separator {
+			// Every element but the last one is followed by the separator. The last one is only
+			// followed by it if the original last element was.
+			let trailing = self.code[ranges[ranges.len() - 1].clone()].ends_with(separator);
+			let last = pieces.len().saturating_sub(1);
+			for (index, (_, item_code)) in pieces.iter_mut().enumerate() {
+				let terminated = item_code.ends_with(separator);
+				if (index < last || trailing) && !terminated {
+					item_code.to_mut().push_str(separator);
+				} else if index == last && !trailing && terminated {
+					let len = item_code.len() - separator.len();
+					item_code.to_mut().truncate(len);
+				}
+			}
+		}
+
+		let mut code = head.to_owned();
+		for (gap, item_code) in &pieces {
+			code.push_str(gap);
+			code.push_str(item_code);
+		}
+		if pieces.is_empty() {
+			// Every element has been deleted.
+			code.truncate(code.trim_end().len());
+		}
+		code.push_str(tail);
+
+		Ok(Some(code))
+	}
+
+	// Splices the `mutated` tokens into the region of the code containing the `original` ones.
+	// The tokens shared by both at their start and their end are kept, and if they only differ in
+	// the content of a group, the group is spliced. Otherwise, the differing tokens are printed.
+	fn splice_tokens(
+		&self,
+		original: TokenStream,
+		mutated: TokenStream,
+		region: Range<usize>,
+	) -> Result<Option<String>, Error> {
+		let original: Vec<TokenTree> = original.into_iter().collect();
+		let mutated: Vec<TokenTree> = mutated.into_iter().collect();
+		let same = |(original, mutated): (&TokenTree, &TokenTree)| {
+			original.to_string() == mutated.to_string()
+		};
+		let prefix = original.iter().zip(&mutated).take_while(|&pair| same(pair)).count();
+		let suffix = original[prefix..]
+			.iter()
+			.rev()
+			.zip(mutated[prefix..].iter().rev())
+			.take_while(|&pair| same(pair))
+			.count();
+		let original_middle = &original[prefix..original.len() - suffix];
+		let mutated_middle = &mutated[prefix..mutated.len() - suffix];
+
+		let (replaced, code) = match (original_middle, mutated_middle) {
+			([], []) => return Ok(Some(self.code[region].to_owned())),
+			([TokenTree::Group(original_group)], [TokenTree::Group(mutated_group)])
+				if original_group.delimiter() == mutated_group.delimiter() =>
+			{
+				let group = self.range(original_group.span())?;
+				let inner = self.offset(original_group.span_open().end())?..
+					self.offset(original_group.span_close().start())?;
+				let Some(inner_code) = self.splice_tokens(
+					original_group.stream(),
+					mutated_group.stream(),
+					inner.clone(),
+				)?
+				else {
+					return Ok(None);
+				};
+				let code = format!(
+					"{}{}{}",
+					&self.code[group.start..inner.start],
+					inner_code,
+					&self.code[inner.end..group.end]
+				);
+				(group, code)
+			},
+			_ if original.is_empty() => return Ok(None),
+			([first, ..], _) => {
+				let replaced = self.range(first.span())?.start..
+					self.range(original_middle[original_middle.len() - 1].span())?.end;
+				let indent = self.line_indent(replaced.start);
+				(replaced, self.indented(&print_tokens(mutated_middle), indent))
+			},
+			([], _) => {
+				// The new tokens are placed next to the closest original token, in a new line if
+				// the tokens span several lines.
+				let (anchor, after) = match prefix.checked_sub(1) {
+					Some(index) => (self.range(original[index].span())?.end, true),
+					None => (self.range(original[0].span())?.start, false),
+				};
+				let indent = self.line_indent(anchor);
+				let separator = if self.code[region.clone()].contains('\n') {
+					format!("{}{}", self.line_ending, indent)
+				} else {
+					" ".to_owned()
+				};
+				let printed = self.indented(&print_tokens(mutated_middle), indent);
+				let code = if after {
+					format!("{}{}", separator, printed)
+				} else {
+					format!("{}{}", printed, separator)
+				};
+				(anchor..anchor, code)
+			},
+		};
+
+		Ok(Some(format!(
+			"{}{}{}",
+			&self.code[region.start..replaced.start],
+			code,
+			&self.code[replaced.end..region.end]
+		)))
+	}
+}
+
+// Aligns the original elements with the mutated ones. The elements shared by both at their start
+// and their end are kept, and the rest are aligned using their longest common subsequence.
+fn align<T: Spliceable>(original: &[T], mutated: &[T]) -> Vec<Op> {
+	let prefix = original.iter().zip(mutated).take_while(|(a, b)| a == b).count();
+	let suffix = original[prefix..]
+		.iter()
+		.rev()
+		.zip(mutated[prefix..].iter().rev())
+		.take_while(|(a, b)| a == b)
+		.count();
+
+	let mut ops: Vec<Op> = (0..prefix).map(Op::Keep).collect();
+	ops.extend(
+		align_lcs(
+			&original[prefix..original.len() - suffix],
+			&mutated[prefix..mutated.len() - suffix],
+		)
+		.into_iter()
+		.map(|op| match op {
+			Op::Keep(index) => Op::Keep(prefix + index),
+			Op::Modify(index, mutated_index) => Op::Modify(prefix + index, prefix + mutated_index),
+			Op::Insert(index) => Op::Insert(prefix + index),
+			Op::Delete(index) => Op::Delete(prefix + index),
+		}),
+	);
+	ops.extend((original.len() - suffix..original.len()).map(Op::Keep));
+	ops
+}
+
+// Aligns the original elements with the mutated ones using their longest common subsequence. The
+// elements between two kept elements are paired when a mutated element replaces an original one.
+fn align_lcs<T: Spliceable>(original: &[T], mutated: &[T]) -> Vec<Op> {
+	let (original_len, mutated_len) = (original.len(), mutated.len());
+	// lcs[i][j] is the length of the longest common subsequence of original[i..] and mutated[j..]
+	let mut lcs = vec![vec![0usize; mutated_len + 1]; original_len + 1];
+	for i in (0..original_len).rev() {
+		for j in (0..mutated_len).rev() {
+			lcs[i][j] = if original[i] == mutated[j] {
+				lcs[i + 1][j + 1] + 1
+			} else {
+				lcs[i + 1][j].max(lcs[i][j + 1])
+			};
+		}
+	}
+
+	let mut ops = Vec::new();
+	let (mut deleted, mut inserted) = (Vec::new(), Vec::new());
+	let (mut i, mut j) = (0, 0);
+	while i < original_len || j < mutated_len {
+		if i < original_len && j < mutated_len && original[i] == mutated[j] {
+			pair_items(&mut ops, original, mutated, &mut deleted, &mut inserted);
+			ops.push(Op::Keep(i));
+			i += 1;
+			j += 1;
+		} else if j < mutated_len && (i == original_len || lcs[i][j + 1] >= lcs[i + 1][j]) {
+			inserted.push(j);
+			j += 1;
+		} else {
+			deleted.push(i);
+			i += 1;
+		}
+	}
+	pair_items(&mut ops, original, mutated, &mut deleted, &mut inserted);
+
+	ops
+}
+
+fn pair_items<T: Spliceable>(
+	ops: &mut Vec<Op>,
+	original: &[T],
+	mutated: &[T],
+	deleted: &mut Vec<usize>,
+	inserted: &mut Vec<usize>,
+) {
+	let mut next_deleted = 0;
+	for mutated_index in inserted.drain(..) {
+		match deleted[next_deleted..]
+			.iter()
+			.position(|&index| original[index].is_replaced_by(&mutated[mutated_index]))
+		{
+			Some(position) => {
+				ops.extend(
+					deleted[next_deleted..next_deleted + position]
+						.iter()
+						.map(|&index| Op::Delete(index)),
+				);
+				ops.push(Op::Modify(deleted[next_deleted + position], mutated_index));
+				next_deleted += position + 1;
+			},
+			None => ops.push(Op::Insert(mutated_index)),
+		}
+	}
+	ops.extend(deleted.drain(..).skip(next_deleted).map(Op::Delete));
+}
+
+// The indentation of the first indented line of code, which is usually one level deep. Defaults to
+// four spaces.
+fn indent_unit(code: &str) -> String {
+	code.lines()
+		.find_map(|line| {
+			let content = line.trim_start_matches([' ', '\t']);
+			// Block comments usually indent their lines by one space before a `*`.
+			(!content.is_empty() && !content.starts_with('*') && content.len() < line.len()).then(
+				|| match line.starts_with('\t') {
+					true => "\t".to_owned(),
+					false => " ".repeat(line.len() - line.trim_start_matches(' ').len()),
+				},
+			)
+		})
+		.unwrap_or_else(|| "    ".to_owned())
+}
+
+// Finds where the comment lines attached to the first element of a region start in the region's
+// prefix. The last line of the prefix is the first element's indentation.
+fn attached_comments_start(prefix: &str, at_line_start: bool) -> usize {
+	let mut start = match prefix.rfind('\n') {
+		Some(index) => index + 1,
+		None => return if at_line_start { 0 } else { prefix.len() },
+	};
+	while start > 0 {
+		let line_start = prefix[..start - 1].rfind('\n').map_or(0, |index| index + 1);
+		let line = prefix[line_start..start].trim();
+		if !line.starts_with("//") || line.starts_with("//!") || (line_start == 0 && !at_line_start)
+		{
+			break;
+		}
+		start = line_start;
+	}
+	start
+}
+
+fn without_leading_empty_lines(gap: &str) -> &str {
+	let content_start = gap.find(|c: char| !c.is_whitespace()).unwrap_or(gap.len());
+	gap[..content_start].rfind('\n').map_or(gap, |index| &gap[index + 1..])
+}
+
+fn has_empty_line(gap: &str) -> bool {
+	let lines: Vec<&str> = gap.split('\n').collect();
+	lines.len() > 2 && lines[1..lines.len() - 1].iter().any(|line| line.trim().is_empty())
+}
+
+fn not_from_source() -> Error {
+	Error::Descriptive("The original AST doesn't come from the given source code".to_owned())
+}
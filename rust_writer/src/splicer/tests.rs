@@ -0,0 +1,357 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use super::*;
+use crate::ast::{
+	implementors::{
+		FieldToStruct, Insertion, ItemFromFile, ItemToFile, ItemToImpl, MutationMode, StmtToFn,
+		TokenStreamToMacro, VariantToEnum,
+	},
+	mutator::{Mutator, ToMutate},
+};
+use test_builder::TestBuilder;
+
+fn complete_file_source(builder: &TestBuilder) -> String {
+	std::fs::read_to_string(builder.tempfile_path("complete_file.rs").expect("This exists; qed;"))
+		.expect("File should be readable")
+}
+
+#[test]
+fn splice_returns_source_if_ast_unchanged() {
+	TestBuilder::default().with_complete_file().execute(|builder| {
+		let source = complete_file_source(&builder);
+		let ast = syn::parse_file(&source).expect("This should be Ok; qed;");
+
+		assert_eq!(splice(&source, &ast, &ast).expect("This should be Ok; qed;"), source);
+	});
+}
+
+#[test]
+fn splice_inserts_item_into_impl_block() {
+	TestBuilder::default().with_complete_file().execute(|builder| {
+		let source = complete_file_source(&builder);
+		let original_ast = syn::parse_file(&source).expect("This should be Ok; qed;");
+		let mut ast = original_ast.clone();
+
+		let item_to_impl: ItemToImpl = (
+			Some("MyTrait"),
+			"MyStruct",
+			parse_quote! {
+				fn new_method(&self) -> bool {
+					true
+				}
+			},
+		)
+			.into();
+		let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+		assert!(mutator.mutate(&mut ast).is_ok());
+
+		let expected_code = source.replace(
+			"    fn other_method(&self) {\n        println!(\"Trait method called!\");\n    }\n",
+			"    fn other_method(&self) {\n        println!(\"Trait method called!\");\n    }\n\n    fn \
+			 new_method(&self) -> bool {\n        true\n    }\n",
+		);
+
+		assert_eq!(
+			splice(&source, &original_ast, &ast).expect("This should be Ok; qed;"),
+			expected_code
+		);
+	});
+}
+
+#[test]
+fn splice_inserts_item_into_file() {
+	TestBuilder::default().with_complete_file().execute(|builder| {
+		let source = complete_file_source(&builder);
+		let original_ast = syn::parse_file(&source).expect("This should be Ok; qed;");
+		let mut ast = original_ast.clone();
+
+		let item: Item = parse_quote! { use std::path::Path; };
+		let item_to_file = ItemToFile::from(item).with_insertion(Insertion::Start);
+		let mut mutator = Mutator::default().to_mutate(&item_to_file);
+		assert!(mutator.mutate(&mut ast).is_ok());
+
+		let expected_code = source.replace(
+			"// A simple function\nfn my_function",
+			"use std::path::Path;\n\n// A simple function\nfn my_function",
+		);
+
+		assert_eq!(
+			splice(&source, &original_ast, &ast).expect("This should be Ok; qed;"),
+			expected_code
+		);
+	});
+}
+
+#[test]
+fn splice_removes_item_from_file() {
+	TestBuilder::default().with_complete_file().execute(|builder| {
+		let source = complete_file_source(&builder);
+		let original_ast = syn::parse_file(&source).expect("This should be Ok; qed;");
+		let mut ast = original_ast.clone();
+
+		let item: Item = parse_quote! {
+			trait MyTrait {
+				fn trait_method(&self);
+			}
+		};
+		let item_from_file: ItemFromFile = item.into();
+		let mut mutator = Mutator::default().to_mutate(&item_from_file);
+		assert!(mutator.mutate(&mut ast).is_ok());
+
+		let expected_code =
+			source.replace("\n\n// A trait\ntrait MyTrait {\n    fn trait_method(&self);\n}", "");
+
+		assert_eq!(
+			splice(&source, &original_ast, &ast).expect("This should be Ok; qed;"),
+			expected_code
+		);
+	});
+}
+
+#[test]
+fn splice_replaces_item_in_place() {
+	let source =
+		"// Header\n\n// Some function\nfn some_func() -> u8 {\n    1\n}\n\nfn other_func() {}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let item: Item = parse_quote! { fn some_func() -> u8 { 2 } };
+	let item_to_file = ItemToFile::from(item).with_mode(MutationMode::Replace);
+	let mut mutator = Mutator::default().to_mutate(&item_to_file);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"// Header\n\n// Some function\nfn some_func() -> u8 {\n    2\n}\n\nfn other_func() {}\n"
+	);
+}
+
+#[test]
+fn splice_reprints_container_if_it_was_empty() {
+	let source = "// Header\nimpl SomeStruct {}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let item_to_impl: ItemToImpl = (None, "SomeStruct", parse_quote! { fn some_func() {} }).into();
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"// Header\nimpl SomeStruct {\n    fn some_func() {}\n}\n"
+	);
+}
+
+#[test]
+fn splice_keeps_byte_order_mark() {
+	let source = "\u{feff}fn some_func() {}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+	ast.items.push(parse_quote! { fn other_func() {} });
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"\u{feff}fn some_func() {}\nfn other_func() {}\n"
+	);
+}
+
+#[test]
+fn splice_fails_if_ast_doesnt_come_from_source() {
+	let original_ast: File = parse_quote! { fn some_func() {} };
+
+	assert!(matches!(
+		splice("fn some_func() {}\n", &original_ast, &original_ast),
+		Err(Error::Descriptive(msg)) if msg == "The original AST doesn't come from the given source code"
+	));
+}
+
+#[test]
+fn splice_fails_if_inner_attributes_changed() {
+	let source = "#![some_attr]\nfn some_func() {}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+	ast.attrs.clear();
+
+	assert!(matches!(
+		splice(source, &original_ast, &ast),
+		Err(Error::Descriptive(msg))
+		if msg == "Cannot splice changes to the file's inner attributes or shebang"
+	));
+}
+
+#[test]
+fn align_pairs_replaced_items() {
+	let original: Vec<Item> = vec![
+		parse_quote! { fn a() {} },
+		parse_quote! { fn b() {} },
+		parse_quote! { struct C; },
+		parse_quote! { fn d() {} },
+	];
+	let mutated: Vec<Item> = vec![
+		parse_quote! { fn a() {} },
+		parse_quote! { fn b() -> u8 { 1 } },
+		parse_quote! { fn e() {} },
+		parse_quote! { fn d() {} },
+	];
+
+	assert_eq!(
+		align(&original, &mutated),
+		vec![Op::Keep(0), Op::Modify(1, 1), Op::Insert(2), Op::Delete(2), Op::Keep(3)]
+	);
+}
+
+#[test]
+fn splice_works_inside_nested_containers() {
+	let source =
+		"mod some_mod {\n    // Some comment\n    trait SomeTrait {\n        // Doc\n        \
+	              fn some_func();\n\n        fn other_func();\n    }\n}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	match ast.items[0] {
+		Item::Mod(ItemMod { content: Some((_, ref mut items)), .. }) => match items[0] {
+			Item::Trait(ref mut item_trait) => {
+				item_trait.items.remove(0);
+				item_trait.items.insert(0, parse_quote! { type SomeType; });
+			},
+			_ => unreachable!("By construction this is a trait; qed;"),
+		},
+		_ => unreachable!("By construction this is a mod; qed;"),
+	}
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"mod some_mod {\n    // Some comment\n    trait SomeTrait {\n        type SomeType;\n\n        \
+		 fn other_func();\n    }\n}\n"
+	);
+}
+
+#[test]
+fn splice_inserts_stmt_into_commented_fn_body() {
+	let source =
+		"fn main() {\n    // Create the app\n    let app = App::new();\n\n    /* Configure it \
+	              */\n    app.configure(); // Inline comment\n\n    app.run();\n}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let stmt_to_fn: StmtToFn = ("main", parse_quote! { app.add_plugin(SomePlugin); }).into();
+	let mut mutator = Mutator::default().to_mutate(&stmt_to_fn);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"fn main() {\n    // Create the app\n    let app = App::new();\n\n    /* Configure it */\n    \
+		 app.configure(); // Inline comment\n\n    app.run();\n\n    \
+		 app.add_plugin(SomePlugin);\n}\n"
+	);
+}
+
+#[test]
+fn splice_inserts_field_into_struct() {
+	let source =
+		"struct SomeStruct {\n    // Some comment\n    field1: u8,\n    field2: String\n}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let field_to_struct: FieldToStruct =
+		("SomeStruct", parse_quote! { pub field3: Vec<u8> }).into();
+	let mut mutator = Mutator::default().to_mutate(&field_to_struct);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"struct SomeStruct {\n    // Some comment\n    field1: u8,\n    field2: String,\n    pub \
+		 field3: Vec<u8>\n}\n"
+	);
+}
+
+#[test]
+fn splice_inserts_variant_into_inline_enum() {
+	let source = "enum SomeEnum { A, B /* Some comment */ }\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let variant_to_enum: VariantToEnum = ("SomeEnum", parse_quote! { C(u8) }).into();
+	let mut mutator = Mutator::default().to_mutate(&variant_to_enum);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"enum SomeEnum { A, B, C(u8) /* Some comment */ }\n"
+	);
+}
+
+#[test]
+fn splice_inserts_tokens_into_macro() {
+	let source =
+		"some_macro! {\n    // Some comment\n    SomeGroup {\n        A: a,\n\n        // \
+	              Other comment\n        B: b,\n    }\n}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let token_stream_to_macro: TokenStreamToMacro =
+		(parse_quote! { some_macro }, Some(parse_quote! { SomeGroup }), parse_quote! { C: c, })
+			.into();
+	let mut mutator = Mutator::default().to_mutate(&token_stream_to_macro);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"some_macro! {\n    // Some comment\n    SomeGroup {\n        A: a,\n\n        // Other \
+		 comment\n        B: b,\n        C : c,\n    }\n}\n"
+	);
+}
+
+#[test]
+fn splice_keeps_crlf_line_endings() {
+	let source = "impl A {\r\n    fn a() {}\r\n}\r\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let item_to_impl: ItemToImpl =
+		(None, "A", parse_quote! { fn b() -> u8 { let b = 1; b } }).into();
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"impl A {\r\n    fn a() {}\r\n    fn b() -> u8 {\r\n        let b = 1;\r\n        \
+		 b\r\n    }\r\n}\r\n"
+	);
+}
+
+#[test]
+fn splice_keeps_tab_indentation() {
+	let source = "mod a {\n\timpl A {\n\t\tfn a() {}\n\t}\n}\n";
+	let original_ast = syn::parse_file(source).expect("This should be Ok; qed;");
+	let mut ast = original_ast.clone();
+
+	let item_to_impl: ItemToImpl =
+		(None, "A", parse_quote! { fn b() -> u8 { let b = 1; b } }).into();
+	let mut mutator = Mutator::default().to_mutate(&item_to_impl);
+	assert!(mutator.mutate(&mut ast).is_ok());
+
+	assert_eq!(
+		splice(source, &original_ast, &ast).expect("This should be Ok; qed;"),
+		"mod a {\n\timpl A {\n\t\tfn a() {}\n\t\tfn b() -> u8 {\n\t\t\tlet b = 1;\n\t\t\tb\n\t\t}\n\t}\n}\n"
+	);
+}
+
+#[test]
+fn align_only_compares_the_changed_middle() {
+	let original: Vec<Item> = (0..1000usize)
+		.map(|index| {
+			let ident = quote::format_ident!("f{}", index);
+			parse_quote! { fn #ident() {} }
+		})
+		.collect();
+	let mut mutated = original.clone();
+	mutated.insert(500, parse_quote! { fn new() {} });
+
+	let ops = align(&original, &mutated);
+
+	assert_eq!(ops.len(), 1001);
+	assert_eq!(ops[500], Op::Insert(500));
+	assert!(ops[..500].iter().enumerate().all(|(index, op)| *op == Op::Keep(index)));
+	assert!(ops[501..].iter().enumerate().all(|(index, op)| *op == Op::Keep(index + 500)));
+}
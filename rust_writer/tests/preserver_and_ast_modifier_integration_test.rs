@@ -29,7 +29,7 @@ fn preserver_and_ast_modifier_integration() {
 				builder.tempfile_path("expanded_file.rs").expect("This exists; qed;");
 
 			let expected_code =
-				std::fs::read_to_string(&expanded_file_path).expect("File should be readable");
+				std::fs::read_to_string(expanded_file_path).expect("File should be readable");
 
 			let preserver1 = Preserver::new("impl MyTrait for MyStruct");
 			let mut preserver2 = Preserver::new("fn main");
@@ -119,10 +119,7 @@ pub(crate) fn expand_finder(parsed: MacroFinderMutatorParsed) -> TokenStream {
 			fn to_find(self, finder: &#finder_lifetime #struct_name<#generics_idents>)
 			->
 			rust_writer::ast::finder::Finder<#finder_lifetime, #struct_name<#generics_idents>,#implementors_count> {
-				rust_writer::ast::finder::Finder {
-					found: [false; #implementors_count],
-					finder
-				}
+				rust_writer::ast::finder::Finder::new(finder)
 			}
 		}
 	};
@@ -164,6 +161,27 @@ pub(crate) fn expand_finder(parsed: MacroFinderMutatorParsed) -> TokenStream {
 					.all(|(_, &x)| x)
 			}
 
+			fn locate(&mut self, file: &#finder_lifetime syn::File, index: u32) -> Vec<rust_writer::ast::finder::Location> {
+				#(
+					if index == #crate_implementors_indexes {
+						let mut finder = rust_writer::ast::finder::Finder::default()
+							.to_find(&self.0.finder.#crate_implementors_idents);
+						let locations = finder.locate(file);
+						self.0.found[#crate_implementors_indexes] = finder.found[0];
+						return locations;
+					}
+				)*
+
+				#(
+					if index == #local_implementors_indexes {
+						self.0.found[#local_implementors_indexes] =
+							self.0.finder.#local_implementors_idents.clone().find(file);
+					}
+				)*
+
+				Vec::new()
+			}
+
 			fn get_missing_indexes(&self) -> Option<Vec<u32>> {
 				let missing_indexes: Vec<u32> = self.0.found
 					.iter()
@@ -186,12 +204,19 @@ pub(crate) fn expand_finder(parsed: MacroFinderMutatorParsed) -> TokenStream {
 		}
 	};
 
+	let impl_ensure = if already_expanded {
+		helpers::expand_ensure(&struct_, &implementors_count)
+	} else {
+		quote! {}
+	};
+
 	quote! {
 		#struct_
 		#impl_from_block
 		#finder_wrapper
 		#impl_to_find
 		#impl_wrapper
+		#impl_ensure
 	}
 }
 
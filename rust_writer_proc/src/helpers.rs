@@ -3,10 +3,11 @@
 #[cfg(test)]
 mod tests;
 
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
 use syn::{
-	parse_quote, punctuated::Punctuated, GenericArgument, GenericParam, Ident, ItemStruct, Path,
-	PathArguments, Token,
+	parse_quote, punctuated::Punctuated, GenericArgument, GenericParam, Ident, Index, ItemStruct,
+	Path, PathArguments, Token,
 };
 
 pub(crate) fn remove_impl_from_attr(struct_: &mut ItemStruct) {
@@ -127,3 +128,60 @@ pub(crate) fn add_new_implementors_generics(struct_: &mut ItemStruct, generics:
 		}
 	}
 }
+
+// The `ensure` method needs both the mutator and the finder wrappers, so it's expanded by the
+// second macro applied to the struct, no matter if it's #[mutator] or #[finder].
+pub(crate) fn expand_ensure(struct_: &ItemStruct, implementors_count: &Index) -> TokenStream {
+	let struct_name = struct_.ident.to_string();
+	let mutator_wrapper_name =
+		Ident::new(&(struct_name.clone() + "MutatorWrapper"), Span::call_site());
+	let finder_wrapper_name = Ident::new(&(struct_name + "FinderWrapper"), Span::call_site());
+
+	let (generics_declarations, generics_idents, where_clause) =
+		rustilities::parsing::extract_generics(&struct_.generics);
+
+	let where_clause = where_clause.unwrap_or(parse_quote! {where});
+
+	quote! {
+		impl<'mutator, #generics_declarations>
+		#mutator_wrapper_name<'mutator, #generics_idents>
+		#where_clause
+		{
+			fn ensure(
+				&mut self,
+				file: &mut syn::File,
+				indexes: Option<&[u32]>,
+			) -> Result<rust_writer::ast::mutator::Ensured, rust_writer::Error> {
+				let in_indexes = |index: &u32| match indexes {
+					Some(indexes) => indexes.contains(index),
+					None => true,
+				};
+
+				let mut finder: #finder_wrapper_name<'_, #generics_idents> =
+					rust_writer::ast::finder::ToFind::to_find(
+						rust_writer::ast::finder::Finder::default(),
+						self.0.mutator,
+					)
+					.into();
+				finder.find(file, indexes);
+
+				let applied: Vec<u32> = finder
+					.get_missing_indexes()
+					.unwrap_or_default()
+					.into_iter()
+					.filter(in_indexes)
+					.collect();
+
+				let satisfied: Vec<u32> = (0..#implementors_count)
+					.filter(|index| in_indexes(index) && !applied.contains(index))
+					.collect();
+
+				if !applied.is_empty() {
+					self.mutate(file, Some(&applied))?;
+				}
+
+				Ok(rust_writer::ast::mutator::Ensured { applied, satisfied })
+			}
+		}
+	}
+}
@@ -227,7 +227,10 @@ use proc_macro::TokenStream;
 ///
 /// 1. Implements the `ToMutate` trait for this implementor, so a `Mutator` instance can be loaded,
 ///    even if the `mutate` method isn't available for it. That instance's `mutated` array has an
-///    entry for each inner implementor, reflecting if that implementor mutations succeed.
+///    entry for each inner implementor, reflecting if that implementor mutations succeed. In the
+///    same way, its `occurrences` method reports how many containers each inner implementor from
+///    the predefined set matched, while the entries of the implementors created with the
+///    `#[local_mutator]` macro are left to 0.
 ///
 /// 1. Creates a wrapper for that `Mutator` called `implementor_name + MutatorWrapper`. So for an
 ///    implementor called `NewImplementor` this wrapper would be called
@@ -235,11 +238,23 @@ use proc_macro::TokenStream;
 ///    constructing this wrappers is seamless.
 ///
 /// 1. Implements a `mutate` method for the wrapper which works exactly as the `mutate` method works
-///    for a regular `Mutator`, with the only difference that it accepts an extra parameter of the
-///    type `Option<&[u32]>`. If it's `Some`, the inner slice would tell the wrapper which
-///    implementors apply to the AST. This is specially useful when some elements are already in the
-///    AST and duplication isn't desired (the `#[finder]` macro may help to identify such elements).
-///    If the parameter is `None`, all the implementors are applied.
+///    for a regular `Mutator`, with the only difference that it accepts an extra parameter of the type
+///    `Option<&[u32]>`. If it's `Some`, the inner slice would tell the wrapper which implementors apply
+///    to the AST. This is specially useful when some elements are already in the AST and duplication
+///    isn't desired (the `#[finder]` macro may help to identify such elements). If the parameter is
+///    `None`, all the implementors are applied. If some of them fail, the returned `Error::MutationFailed`
+///    contains their indexes, the names of their fields in the struct and a short description of each
+///    failure. Local implementors are described by their [`Describe`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/trait.Describe.html)
+///    implementation if they have one, called with 0 occurrences, or by a generic description
+///    otherwise.
+///
+/// 1. Implements the [`Preserve`](https://docs.rs/rust_writer/latest/rust_writer/preserver/trait.Preserve.html)
+///    trait for both the implementor and the wrapper, so the code mutated by the implementors from
+///    the predefined set can be preserved using
+///    [`preserve_and_parse_for`](https://docs.rs/rust_writer/latest/rust_writer/preserver/fn.preserve_and_parse_for.html).
+///    The implementors created with the `#[local_mutator]` macro don't contribute any preserver.
+///    This trait is only implemented if every implementor from the predefined set implements
+///    `Preserve` too, so the implementors don't need to implement it to be used by the macro.
 ///
 /// ```rust
 /// use rust_writer::ast::{mutator, implementors::ItemToTrait, mutator::{ToMutate, Mutator}};
@@ -308,6 +323,54 @@ use proc_macro::TokenStream;
 /// #[finder(ItemToImpl<'a>, ItemToTrait<'a>)]
 /// struct NewImplementor;
 /// ```
+///
+/// When both macros are combined, the mutator wrapper also gets an
+/// `fn ensure(&mut self, file: &mut syn::File, indexes: Option<&[u32]>) -> Result<Ensured, Error>`
+/// method. It runs the finder first and only applies the mutations whose targets aren't in the AST
+/// yet, so it can be called as many times as needed without duplicating elements. The returned
+/// [`Ensured`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/struct.Ensured.html)
+/// tells which implementors have been applied and which ones were already satisfied.
+///
+/// ```rust
+/// use rust_writer::ast::{
+///   mutator,
+///   finder,
+///   implementors::ItemToTrait,
+///   mutator::{Ensured, Mutator, ToMutate},
+///   finder::ToFind
+/// };
+/// use syn::{parse_quote, visit_mut::VisitMut};
+/// use test_builder::TestBuilder;
+///
+/// #[mutator(ItemToTrait<'a>, ItemToTrait<'a>)]
+/// #[finder(ItemToTrait<'a>, ItemToTrait<'a>)]
+/// #[impl_from]
+/// struct NewImplementor;
+///
+/// TestBuilder::default().with_trait_ast().execute(|mut builder|{
+///  let new_implementor: NewImplementor = (
+///    ("MyTrait", parse_quote!(type Type1: From<String>;)).into(),
+///    ("MyTrait", parse_quote!(type Type3: From<String>;)).into()
+///  ).into();
+///
+///  let mut mutator: NewImplementorMutatorWrapper =
+///   Mutator::default().to_mutate(&new_implementor).into();
+///
+///  let ast = builder.get_mut_ast_file("trait.rs").expect("This exists; qed;");
+///
+///  // `Type1` is already in the trait, so only `Type3` is added.
+///  assert_eq!(
+///    mutator.ensure(ast, None).expect("This is Ok; qed;"),
+///    Ensured { applied: vec![1], satisfied: vec![0] }
+///  );
+///
+///  // Nothing to do the second time.
+///  assert_eq!(
+///    mutator.ensure(ast, None).expect("This is Ok; qed;"),
+///    Ensured { applied: vec![], satisfied: vec![0, 1] }
+///  );
+/// });
+/// ```
 #[proc_macro_attribute]
 pub fn mutator(attrs: TokenStream, item: TokenStream) -> TokenStream {
 	mutator::mutator(attrs, item)
@@ -377,6 +440,13 @@ pub fn mutator(attrs: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Note that the `reset` method is called `mutator_reset` in this case. This is because a local
 /// mutator can also be a local finder, hence this distinction is needed.
+///
+/// If the local mutator implements
+/// [`Describe`](https://docs.rs/rust_writer/latest/rust_writer/ast/mutator/trait.Describe.html),
+/// its failures are described by calling `describe_failure` with 0 occurrences, as local mutators
+/// don't keep track of their matches. Otherwise, they're reported as "cannot mutate using
+/// `LocalMutatorName`". The same description is used when the local mutator is combined with other
+/// implementors by the `#[mutator]` macro.
 #[proc_macro_attribute]
 pub fn local_mutator(_: TokenStream, item: TokenStream) -> TokenStream {
 	mutator::local_mutator(item)
@@ -615,6 +685,12 @@ pub fn local_mutator(_: TokenStream, item: TokenStream) -> TokenStream {
 /// 1. Implements the method `fn get_missing_indexes(&self) -> Option<Vec<u32>>` which precisely
 ///    reflects which implementors have succeeded in their research up to the point of this call.
 ///
+/// 1. Implements the method `fn locate(&mut self, file: &syn::File, index: u32) -> Vec<Location>`,
+///    which works as the [`locate`](https://docs.rs/rust_writer/latest/rust_writer/ast/finder/struct.Finder.html#method.locate)
+///    method of a regular `Finder` for the implementor at `index`, updating its entry of the
+///    `found` array. The implementors created with the `#[local_finder]` macro cannot be located,
+///    so they're only applied and an empty `Vec` is returned for them.
+///
 /// ```rust
 /// use rust_writer::ast::{finder, implementors::ItemToTrait, finder::{ToFind, Finder}};
 /// use syn::parse_quote;
@@ -660,7 +736,9 @@ pub fn local_mutator(_: TokenStream, item: TokenStream) -> TokenStream {
 /// # Compatibility with [`#[mutator]`](https://docs.rs/rust_writer/latest/rust_writer/ast/attr.mutator.html)
 ///
 /// It's perfectly possible to use the `#[finder]` macro in combination with the `#[mutator]`
-/// macro. The order in which macros are applied doesn't matter.
+/// macro. The order in which macros are applied doesn't matter. Combining them provides the
+/// mutator wrapper with an `ensure` method, which only applies the mutations whose targets
+/// cannot be found.
 ///
 /// **There's just one golden rule to combine both macros**: The list of implementors should be the
 /// same and come in the same order in both macros.
@@ -8,7 +8,7 @@ use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use syn::{
 	parse_quote, punctuated::Punctuated, token::Brace, Field, Fields, FieldsNamed, Ident, Index,
-	Lifetime, Token, Type,
+	Lifetime, Token, Type, WherePredicate,
 };
 
 pub(crate) fn expand_mutator(parsed: MacroFinderMutatorParsed) -> TokenStream {
@@ -118,10 +118,7 @@ pub(crate) fn expand_mutator(parsed: MacroFinderMutatorParsed) -> TokenStream {
 			fn to_mutate(self, mutator: &#mutator_lifetime #struct_name<#generics_idents>)
 			->
 			rust_writer::ast::mutator::Mutator<#mutator_lifetime, #struct_name<#generics_idents>,#implementors_count> {
-				rust_writer::ast::mutator::Mutator {
-					mutated: [false; #implementors_count],
-					mutator
-				}
+				rust_writer::ast::mutator::Mutator::new(mutator)
 			}
 		}
 	};
@@ -136,6 +133,8 @@ pub(crate) fn expand_mutator(parsed: MacroFinderMutatorParsed) -> TokenStream {
 				file: &mut syn::File,
 				indexes: Option<&[u32]>,
 			) -> Result<(), rust_writer::Error> {
+				let mut failures = Vec::new();
+
 				#(
 					match indexes {
 						Some(indexes) if !indexes.contains(&#crate_implementors_indexes) => (),
@@ -145,6 +144,18 @@ pub(crate) fn expand_mutator(parsed: MacroFinderMutatorParsed) -> TokenStream {
 							mutator.visit_file_mut(file);
 							self.0.mutated[#crate_implementors_indexes] =
 								mutator.mutated.iter().all(|&x| x);
+							let occurrences = mutator.occurrences().iter().sum();
+							self.0.set_occurrences(#crate_implementors_indexes as usize, occurrences);
+							if !self.0.mutated[#crate_implementors_indexes] {
+								failures.push(rust_writer::MutationFailure {
+									index: #crate_implementors_indexes,
+									field: Some(stringify!(#crate_implementors_idents).to_owned()),
+									description: rust_writer::ast::mutator::Describe::describe_failure(
+										&self.0.mutator.#crate_implementors_idents,
+										occurrences,
+									),
+								});
+							}
 						}
 					}
 				)*
@@ -157,38 +168,93 @@ pub(crate) fn expand_mutator(parsed: MacroFinderMutatorParsed) -> TokenStream {
 							mutator.#local_implementors_idents.visit_file_mut(file);
 							self.0.mutated[#local_implementors_indexes] =
 								mutator.#local_implementors_idents.mutated.iter().all(|&x| x);
+							if !self.0.mutated[#local_implementors_indexes] {
+								failures.push(rust_writer::MutationFailure {
+									index: #local_implementors_indexes,
+									field: Some(stringify!(#local_implementors_idents).to_owned()),
+									description: {
+										use rust_writer::ast::mutator::{
+											DescribeLocal as _, DescribeLocalDefault as _,
+										};
+										(&rust_writer::ast::mutator::LocalFailure(
+											&self.0.mutator.#local_implementors_idents,
+											"cannot mutate using the local implementor",
+										))
+											.describe_local()
+									},
+								});
+							}
 						}
 					}
 				)*
 
-				if self
-					.0
-					.mutated
-					.iter()
-					.enumerate()
-					.filter(|(index, _)| match indexes {
-						Some(indexes) if !indexes.contains(&(*index as u32)) => false,
-						_ => true,
-					})
-					.all(|(_, &x)| x)
-				{
+				if failures.is_empty() {
 					Ok(())
 				} else {
-					Err(rust_writer::Error::Descriptive(format!(
-						"Cannot mutate using Mutator: {:?}",
-						self.0.mutator
-					)))
+					Err(rust_writer::Error::MutationFailed(failures))
 				}
 			}
 		}
 	};
 
+	// Local implementors cannot be inferred, so only the crate implementors are preserved. The
+	// implementation only exists if all of them are `Preserve`, so custom crate implementors don't
+	// need to be. The bounds are higher-ranked so they aren't rejected when they don't hold for
+	// implementors without generics.
+	let crate_implementors_types: Vec<&Type> = crate_implementors_idents
+		.iter()
+		.filter_map(|ident| {
+			struct_
+				.fields
+				.iter()
+				.find(|field| field.ident.as_ref() == Some(ident))
+				.map(|field| &field.ty)
+		})
+		.collect();
+	let mut preserve_where_clause = where_clause.clone();
+	preserve_where_clause.predicates.extend(crate_implementors_types.iter().map(
+		|ty| -> WherePredicate {
+			parse_quote!(for<'__preserve> #ty: rust_writer::preserver::Preserve)
+		},
+	));
+
+	let impl_preserve = quote! {
+		impl<#generics_declarations> rust_writer::preserver::Preserve
+		for #struct_name<#generics_idents>
+		#preserve_where_clause
+		{
+			fn preservers(&self) -> Vec<rust_writer::preserver::Preserver<'_>> {
+				let preservers: Vec<Vec<rust_writer::preserver::Preserver<'_>>> = vec![
+					#(rust_writer::preserver::Preserve::preservers(&self.#crate_implementors_idents)),*
+				];
+				preservers.into_iter().flatten().collect()
+			}
+		}
+
+		impl<#mutator_lifetime, #generics_declarations> rust_writer::preserver::Preserve
+		for #mutator_wrapper_name<#mutator_lifetime, #generics_idents>
+		#preserve_where_clause
+		{
+			fn preservers(&self) -> Vec<rust_writer::preserver::Preserver<'_>> {
+				rust_writer::preserver::Preserve::preservers(&self.0)
+			}
+		}
+	};
+
+	let impl_ensure = if already_expanded {
+		helpers::expand_ensure(&struct_, &implementors_count)
+	} else {
+		quote! {}
+	};
+
 	quote! {
 		#struct_
 		#impl_from_block
 		#mutator_wrapper
 		#impl_to_mutate
 		#impl_mutate
+		#impl_preserve
+		#impl_ensure
 	}
 }
 
@@ -203,12 +269,31 @@ pub(crate) fn expand_local_mutator(parsed: MacroLocalParsed) -> TokenStream {
 		#where_clause
 		{
 			fn mutate(&mut self, file: &mut syn::File) -> Result<(), rust_writer::Error>{
+				use rust_writer::ast::mutator::{DescribeLocal as _, DescribeLocalDefault as _};
+
 				self.visit_file_mut(file);
 
-				if self.mutated.iter().all(|&x| x){
+				let default_description =
+					format!("cannot mutate using `{}`", stringify!(#struct_name));
+				let description =
+					(&rust_writer::ast::mutator::LocalFailure(&*self, &default_description))
+						.describe_local();
+				let failures: Vec<rust_writer::MutationFailure> = self
+					.mutated
+					.iter()
+					.enumerate()
+					.filter(|(_, &mutated)| !mutated)
+					.map(|(index, _)| rust_writer::MutationFailure {
+						index: index as u32,
+						field: None,
+						description: description.clone(),
+					})
+					.collect();
+
+				if failures.is_empty() {
 					Ok(())
 				} else {
-					Err(rust_writer::Error::Descriptive(format!("Cannot mutate using Mutator: {:?}", self)))
+					Err(rust_writer::Error::MutationFailed(failures))
 				}
 			}
 
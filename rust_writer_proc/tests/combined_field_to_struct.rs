@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use rust_writer::ast::{
+	finder::{Finder, ToFind},
+	implementors::{FieldToStruct, ItemToFile},
+	mutator::{Mutator, ToMutate},
+};
+use rust_writer_proc::{finder, mutator};
+use syn::{parse_quote, visit_mut::VisitMut, Item};
+use test_builder::TestBuilder;
+
+#[mutator(FieldToStruct<'a>, ItemToFile)]
+#[finder(FieldToStruct<'a>, ItemToFile)]
+#[impl_from]
+struct SomeStruct;
+
+#[test]
+fn combined_field_to_struct() {
+	TestBuilder::default().with_struct_ast().execute(|mut builder| {
+		let field_to_struct: FieldToStruct =
+			("MyStruct", parse_quote! { pub config: Config }).into();
+
+		let item: Item = parse_quote! { use std::path::PathBuf; };
+		let item_to_file: ItemToFile = item.into();
+
+		let some_struct: SomeStruct = (field_to_struct, item_to_file).into();
+
+		let ast = builder.get_mut_ast_file("struct.rs").expect("This should exist");
+
+		let mut finder: SomeStructFinderWrapper = Finder::default().to_find(&some_struct).into();
+		assert!(!finder.find(ast, None));
+		assert_eq!(finder.get_missing_indexes(), Some(vec![0]));
+
+		let mut mutator: SomeStructMutatorWrapper =
+			Mutator::default().to_mutate(&some_struct).into();
+		assert!(mutator.mutate(ast, finder.get_missing_indexes().as_deref()).is_ok());
+
+		let mut finder: SomeStructFinderWrapper = Finder::default().to_find(&some_struct).into();
+		assert!(finder.find(ast, None));
+		assert_eq!(format!("{:?}", ast).matches("PathBuf").count(), 2);
+	});
+}
@@ -1,9 +1,12 @@
 // SPDX-License-Identifier: GPL-3.0
 
-use rust_writer::ast::{
-	finder::{Finder, ToFind},
-	implementors::{ItemToImpl, ItemToTrait},
-	mutator::{Mutator, ToMutate},
+use rust_writer::{
+	ast::{
+		finder::{Finder, ToFind},
+		implementors::{ItemToImpl, ItemToTrait},
+		mutator::{Mutator, ToMutate},
+	},
+	Error, MutationFailure,
 };
 use rust_writer_proc::{finder, mutator};
 use syn::{parse_quote, visit_mut::VisitMut, ImplItem, TraitItem};
@@ -63,6 +66,14 @@ fn mutator_fails_if_a_single_implementor_fails_to_mutate() {
 
 		let mut mutator: SomeStructMutatorWrapper =
 			Mutator::default().to_mutate(&some_struct).into();
-		assert!(mutator.mutate(ast, None).is_err());
+		assert!(matches!(
+			mutator.mutate(ast, None),
+			Err(Error::MutationFailed(failures))
+			if failures == [MutationFailure {
+				index: 1,
+				field: Some("itemtoimpl".to_owned()),
+				description: "impl `UnexistingTrait for SomeImplementor` not found".to_owned()
+			}]
+		));
 	});
 }
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use rust_writer::ast::{
+	finder::{Finder, ToFind},
+	implementors::{ItemToImpl, ItemToTrait},
+	mutator::{Ensured, Mutator, ToMutate},
+};
+use rust_writer_proc::{finder, mutator};
+use syn::{parse_quote, visit_mut::VisitMut, ImplItem, TraitItem};
+use test_builder::TestBuilder;
+
+#[mutator(ItemToTrait<'a>, ItemToImpl<'a>)]
+#[finder(ItemToTrait<'a>, ItemToImpl<'a>)]
+#[impl_from]
+struct MutatorFirst;
+
+#[finder(ItemToTrait<'a>, ItemToImpl<'a>)]
+#[mutator(ItemToTrait<'a>, ItemToImpl<'a>)]
+#[impl_from]
+struct FinderFirst;
+
+fn implementors<'a>() -> (ItemToTrait<'a>, ItemToImpl<'a>) {
+	(
+		("MyTrait", TraitItem::Type(parse_quote! {type Type1: From<String>;})).into(),
+		(
+			Some("SomeTrait"),
+			"SomeImplementor",
+			ImplItem::Fn(parse_quote! {
+				fn other_func(&self) -> bool{
+					false
+				}
+			}),
+		)
+			.into(),
+	)
+}
+
+#[test]
+fn ensure_only_applies_missing_mutations() {
+	TestBuilder::default().with_trait_and_impl_block_ast().execute(|mut builder| {
+		let mutator_first: MutatorFirst = implementors().into();
+
+		let ast = builder.get_mut_ast_file("trait_and_impl_block.rs").expect("This should exist");
+
+		let mut mutator: MutatorFirstMutatorWrapper =
+			Mutator::default().to_mutate(&mutator_first).into();
+
+		assert_eq!(
+			mutator.ensure(ast, None).expect("This should be Ok; qed;"),
+			Ensured { applied: vec![1], satisfied: vec![0] }
+		);
+		assert_eq!(mutator.0.occurrences(), [0, 1]);
+		assert_eq!(format!("{:?}", ast).matches("Type1").count(), 1);
+		assert_eq!(format!("{:?}", ast).matches("other_func").count(), 1);
+
+		let mut mutator: MutatorFirstMutatorWrapper =
+			Mutator::default().to_mutate(&mutator_first).into();
+
+		assert_eq!(
+			mutator.ensure(ast, None).expect("This should be Ok; qed;"),
+			Ensured { applied: Vec::new(), satisfied: vec![0, 1] }
+		);
+		assert_eq!(format!("{:?}", ast).matches("other_func").count(), 1);
+
+		let mut finder: MutatorFirstFinderWrapper =
+			Finder::default().to_find(&mutator_first).into();
+		assert!(finder.find(ast, None));
+	});
+}
+
+#[test]
+fn ensure_works_regardless_of_macros_order() {
+	TestBuilder::default().with_trait_and_impl_block_ast().execute(|mut builder| {
+		let finder_first: FinderFirst = implementors().into();
+
+		let ast = builder.get_mut_ast_file("trait_and_impl_block.rs").expect("This should exist");
+
+		let mut mutator: FinderFirstMutatorWrapper =
+			Mutator::default().to_mutate(&finder_first).into();
+
+		assert_eq!(
+			mutator.ensure(ast, None).expect("This should be Ok; qed;"),
+			Ensured { applied: vec![1], satisfied: vec![0] }
+		);
+	});
+}
+
+#[test]
+fn ensure_respects_indexes() {
+	TestBuilder::default().with_trait_and_impl_block_ast().execute(|mut builder| {
+		let mutator_first: MutatorFirst = implementors().into();
+
+		let ast = builder.get_mut_ast_file("trait_and_impl_block.rs").expect("This should exist");
+
+		let mut mutator: MutatorFirstMutatorWrapper =
+			Mutator::default().to_mutate(&mutator_first).into();
+
+		assert_eq!(
+			mutator.ensure(ast, Some(&[0])).expect("This should be Ok; qed;"),
+			Ensured { applied: Vec::new(), satisfied: vec![0] }
+		);
+		assert_eq!(format!("{:?}", ast).matches("other_func").count(), 0);
+	});
+}
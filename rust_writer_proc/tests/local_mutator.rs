@@ -4,10 +4,11 @@ use rust_writer::{
 	ast::{
 		finder::{Finder, ToFind},
 		implementors::ItemToTrait,
+		mutator::{Describe, Mutator, ToMutate},
 	},
-	Error,
+	Error, MutationFailure,
 };
-use rust_writer_proc::local_mutator;
+use rust_writer_proc::{local_mutator, mutator};
 use syn::{parse_quote, visit_mut::VisitMut, ItemTrait, TraitItem};
 use test_builder::TestBuilder;
 
@@ -22,7 +23,7 @@ struct SomeStruct<'a, T: Clone + std::fmt::Debug> {
 	just_extra_data: T,
 }
 
-impl<'a, T: Clone + std::fmt::Debug> VisitMut for SomeStruct<'a, T> {
+impl<T: Clone + std::fmt::Debug> VisitMut for SomeStruct<'_, T> {
 	fn visit_item_trait_mut(&mut self, item_trait: &mut ItemTrait) {
 		if item_trait.ident == self.trait_name {
 			self.mutated[0] = true;
@@ -79,13 +80,91 @@ fn local_mutator_struct_fails_if_unable_to_mutate() {
 		let mut finder = Finder::default().to_find(&item_to_trait);
 		assert!(!finder.find(ast));
 
-		let expected_msg = format!("Cannot mutate using Mutator: {:?}", some_struct);
 		assert!(matches!(
-			some_struct.mutate(ast),
-			Err(Error::Descriptive(msg))
-			if msg == expected_msg));
+		some_struct.mutate(ast),
+		Err(Error::MutationFailed(failures))
+		if failures == [MutationFailure {
+			index: 0,
+			field: None,
+			description: "cannot mutate using `SomeStruct`".to_owned()
+		}]));
 
 		let mut finder = Finder::default().to_find(&item_to_trait);
 		assert!(!finder.find(ast));
 	});
 }
+
+// Local mutators don't need to be `Debug` to mutate an AST on their own.
+#[local_mutator]
+struct NotDebug {
+	mutated: [bool; 1],
+}
+
+impl VisitMut for NotDebug {}
+
+#[test]
+fn local_mutator_struct_doesnt_need_debug() {
+	let mut not_debug = NotDebug { mutated: [false] };
+	let mut ast = parse_quote! { trait MyTrait {} };
+
+	assert!(matches!(
+		not_debug.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "cannot mutate using `NotDebug`"
+	));
+}
+
+// Local mutators may implement `Describe` to explain their failures.
+#[local_mutator]
+#[derive(Debug, Clone)]
+struct DescribedMutator {
+	mutated: [bool; 1],
+	trait_name: String,
+}
+
+impl VisitMut for DescribedMutator {}
+
+impl Describe for DescribedMutator {
+	fn describe_failure(&self, _occurrences: usize) -> String {
+		format!("trait `{}` not found", self.trait_name)
+	}
+}
+
+#[mutator(ItemToTrait<'a>, local = DescribedMutator)]
+#[impl_from]
+struct DescribedStruct;
+
+#[test]
+fn local_mutator_struct_uses_its_description() {
+	let mut described_mutator =
+		DescribedMutator { mutated: [false], trait_name: "MyTrait".to_owned() };
+	let mut ast = parse_quote! { trait OtherTrait {} };
+
+	assert!(matches!(
+		described_mutator.mutate(&mut ast),
+		Err(Error::MutationFailed(failures))
+		if failures[0].description == "trait `MyTrait` not found"
+	));
+}
+
+#[test]
+fn mutator_uses_the_description_of_local_implementors() {
+	let described_struct: DescribedStruct = (
+		("OtherTrait", TraitItem::Type(parse_quote! {type Something: From<String>;})).into(),
+		DescribedMutator { mutated: [false], trait_name: "MyTrait".to_owned() },
+	)
+		.into();
+	let mut ast = parse_quote! { trait OtherTrait {} };
+
+	let mut mutator: DescribedStructMutatorWrapper =
+		Mutator::default().to_mutate(&described_struct).into();
+	assert!(matches!(
+		mutator.mutate(&mut ast, None),
+		Err(Error::MutationFailed(failures))
+		if failures == [MutationFailure {
+			index: 1,
+			field: Some("describedmutator".to_owned()),
+			description: "trait `MyTrait` not found".to_owned()
+		}]
+	));
+}
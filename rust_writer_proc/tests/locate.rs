@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use rust_writer::ast::{
+	finder::{Finder, Location, LocationSegment, ToFind},
+	implementors::ItemToTrait,
+};
+use rust_writer_proc::{finder, local_finder};
+use syn::{parse_quote, visit::Visit, File, ItemTrait, TraitItem};
+
+// A custom finder looking for a trait
+#[local_finder('a)]
+#[derive(Debug, Clone)]
+struct TraitFinder {
+	found: [bool; 1],
+	trait_name: String,
+}
+
+impl Visit<'_> for TraitFinder {
+	fn visit_item_trait(&mut self, item_trait: &ItemTrait) {
+		if item_trait.ident == self.trait_name {
+			self.found[0] = true;
+		}
+	}
+}
+
+#[finder(ItemToTrait<'a>, ItemToTrait<'a>, local = TraitFinder)]
+#[impl_from]
+struct SomeStruct;
+
+#[test]
+fn finder_wrapper_locates_each_implementor() {
+	let ast: File = parse_quote! {
+		trait MyTrait {
+			type Type1: From<String>;
+			type Type2: From<String>;
+		}
+	};
+	let some_struct: SomeStruct = (
+		("MyTrait", TraitItem::Type(parse_quote! {type Type2: From<String>;})).into(),
+		("MyTrait", TraitItem::Type(parse_quote! {type Type3: From<String>;})).into(),
+		TraitFinder { found: [false], trait_name: "MyTrait".to_owned() },
+	)
+		.into();
+
+	let mut finder: SomeStructFinderWrapper = Finder::default().to_find(&some_struct).into();
+
+	let locations = finder.locate(&ast, 0);
+	assert_eq!(locations.len(), 1);
+	assert_eq!(
+		locations[0].path,
+		[LocationSegment { kind: "trait", name: "MyTrait".to_owned(), index: 0 }]
+	);
+	assert_eq!(locations[0].index, 1);
+	assert_eq!(finder.get_missing_indexes(), Some(vec![1, 2]));
+
+	assert_eq!(finder.locate(&ast, 1), Vec::<Location>::new());
+	assert_eq!(finder.get_missing_indexes(), Some(vec![1, 2]));
+
+	// Local implementors cannot be located, but they're still applied.
+	assert_eq!(finder.locate(&ast, 2), Vec::<Location>::new());
+	assert_eq!(finder.get_missing_indexes(), Some(vec![1]));
+}
@@ -6,7 +6,7 @@ use rust_writer::ast::{
 	mutator::{Mutator, ToMutate},
 };
 use rust_writer_proc::{finder, mutator};
-use syn::{parse_quote, visit_mut::VisitMut, ImplItem};
+use syn::{parse_quote, visit_mut::VisitMut, ImplItem, Item};
 use test_builder::TestBuilder;
 
 #[mutator(ItemToFile, ItemToImpl<'a>)]
@@ -17,11 +17,10 @@ struct SomeStruct;
 #[test]
 fn modified_unit_struct() {
 	TestBuilder::default().with_trait_and_impl_block_ast().execute(|mut builder| {
-		let item_to_file = ItemToFile {
-			item: parse_quote!(
-				use std::path::Path;
-			),
-		};
+		let item: Item = parse_quote!(
+			use std::path::Path;
+		);
+		let item_to_file: ItemToFile = item.into();
 
 		let item_to_impl: ItemToImpl = (
 			Some("SomeTrait"),
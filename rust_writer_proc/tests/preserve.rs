@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use rust_writer::{
+	ast::{
+		implementors::{ItemToImpl, ItemToTrait},
+		mutator::{Mutator, ToMutate},
+	},
+	preserver::{Lookup, Preserve, Preserver},
+};
+use rust_writer_proc::mutator;
+use syn::{parse_quote, visit_mut::VisitMut};
+
+#[mutator(ItemToTrait<'a>, ItemToImpl<'a>)]
+#[impl_from]
+struct NewImplementor;
+
+#[test]
+fn mutator_infers_preservers() {
+	let code =
+		"// Some comment\ntrait MyTrait {\n    // Inner comment\n    fn some_func();\n}\n\nimpl \
+	            MyTrait for MyStruct {\n    fn some_func() {}\n}\n\nfn main() {\n    let a = \
+	            ;\n}\n";
+
+	let new_implementor: NewImplementor = (
+		("MyTrait", parse_quote! { type Type1; }).into(),
+		(Some("MyTrait"), "MyStruct", parse_quote! { type Type1 = (); }).into(),
+	)
+		.into();
+
+	let expected_preservers = vec![
+		Preserver::with_lookup(Lookup::Item { kind: "trait".into(), name: "MyTrait".into() }),
+		Preserver::with_lookup(Lookup::Impl {
+			trait_name: Some("MyTrait".into()),
+			implementor_name: "MyStruct".into(),
+		}),
+	];
+	assert_eq!(new_implementor.preservers(), expected_preservers);
+
+	let mut mutator: NewImplementorMutatorWrapper =
+		Mutator::default().to_mutate(&new_implementor).into();
+	assert_eq!(mutator.preservers(), expected_preservers);
+
+	// `fn main` isn't valid code, but it doesn't need to be preserved.
+	let mut ast = rust_writer::preserver::preserve_and_parse_str_for(code, &mutator)
+		.expect("This should be Ok; qed;");
+	assert!(mutator.mutate(&mut ast, None).is_ok());
+
+	assert_eq!(
+		rust_writer::preserver::resolve_preserved_to_string(&ast),
+		code.replace("    fn some_func();\n", "    fn some_func();\n    type Type1;\n")
+			.replace("    fn some_func() {}\n", "    fn some_func() {}\n    type Type1 = ();\n")
+	);
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: GPL-3.0
+
+enum MyEnum{
+    A,
+    B(u8, String),
+    C{
+        field: u8
+    },
+
+    /// Doc
+    #[some_attr]
+    D,
+}
+
+enum Discriminants{
+    A = 1,
+    B = 2,
+}
@@ -5,6 +5,8 @@
 //! Some superusefuldocs
 //! This file is just for testing!!! :)))
 
+use std::path::Path;
+
 // A simple function
 fn my_function() {
     println!("Hello from a function!");
@@ -67,4 +69,3 @@ let a = 1; enum
     let instance = MyStruct::new(42, "Hello");
     instance.trait_method();
 }
-use std::path::Path;
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: GPL-3.0
+
+impl SomeImplementor{
+    fn some_func(&self) -> bool{
+        true
+    }
+}
+
+impl SomeImplementor{
+    fn some_super_func(&self) -> bool{
+        true
+    }
+}
+
+fn main(){
+    my_macro!{
+        A,
+        B
+    }
+
+    my_macro!{
+        C
+    }
+}
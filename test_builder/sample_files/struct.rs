@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: GPL-3.0
+
+use std::path::PathBuf;
+
+struct MyStruct{
+    field1: u8,
+    pub field2: String,
+
+    /// Doc
+    #[some_attr]
+    pub(crate) path: PathBuf,
+}
+
+struct TupleStruct(u8, String);
+
+struct UnitStruct;
@@ -68,7 +68,10 @@ impl<'a> TestBuilder<'a> {
 		[with_impl_block_ast, "impl_block.rs"],
 		[with_trait_and_impl_block_ast, "trait_and_impl_block.rs"],
 		[with_mod_ast, "mod.rs"],
+		[with_enum_ast, "enum.rs"],
+		[with_struct_ast, "struct.rs"],
 		[with_macro_ast, "macro.rs"],
+		[with_multiple_matches_ast, "multiple_matches.rs"],
 		[with_file_ast, "file.rs"],
 		[with_preserved_file_ast, "preserved_file.rs"]
 	}